@@ -3,11 +3,16 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#[cfg(windows)]
 use std::io;
+#[cfg(windows)]
 use std::path::Path;
+#[cfg(windows)]
 use winreg::enums::*;
+#[cfg(windows)]
 use winreg::{HKCU, HKLM};
 
+#[cfg(windows)]
 fn main() -> io::Result<()> {
     println!("Reading some system info...");
     let cur_ver = HKLM.open_subkey("SOFTWARE\\Microsoft\\Windows\\CurrentVersion")?;
@@ -64,3 +69,8 @@ fn main() -> io::Result<()> {
     });
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
@@ -3,9 +3,12 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#[cfg(windows)]
 use std::io;
+#[cfg(windows)]
 use winreg::{HKCR, HKLM};
 
+#[cfg(windows)]
 fn main() -> io::Result<()> {
     println!("File extensions, registered in system:");
     for i in HKCR
@@ -23,3 +26,8 @@ fn main() -> io::Result<()> {
 
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
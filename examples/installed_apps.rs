@@ -3,11 +3,16 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#[cfg(windows)]
 use serde_derive::{Deserialize, Serialize};
+#[cfg(windows)]
 use std::collections::HashMap;
+#[cfg(windows)]
 use std::fmt;
+#[cfg(windows)]
 use winreg::HKLM;
 
+#[cfg(windows)]
 #[allow(non_snake_case)]
 #[derive(Debug, Serialize, Deserialize)]
 struct InstalledApp {
@@ -16,12 +21,14 @@ struct InstalledApp {
     UninstallString: Option<String>,
 }
 
+#[cfg(windows)]
 macro_rules! str_from_opt {
     ($s:expr) => {
         $s.as_ref().map(|x| &**x).unwrap_or("")
     };
 }
 
+#[cfg(windows)]
 impl fmt::Display for InstalledApp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -33,15 +40,26 @@ impl fmt::Display for InstalledApp {
     }
 }
 
+#[cfg(windows)]
 fn main() {
     let uninstall_key = HKLM
         .open_subkey("SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall")
         .expect("key is missing");
 
-    let apps: HashMap<String, InstalledApp> =
-        uninstall_key.decode().expect("deserialization failed");
+    let (apps, errors): (HashMap<String, InstalledApp>, _) = uninstall_key
+        .decode_lenient()
+        .expect("deserialization failed");
+
+    for err in &errors {
+        eprintln!("skipped {}", err);
+    }
 
     for v in apps.values() {
         println!("{}", v);
     }
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
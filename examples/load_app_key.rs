@@ -3,10 +3,14 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#[cfg(windows)]
 use std::io;
+#[cfg(windows)]
 use winreg::enums::*;
+#[cfg(windows)]
 use winreg::RegKey;
 
+#[cfg(windows)]
 fn main() -> io::Result<()> {
     {
         // put this in a block so app_key_1 gets out of scope and doesn't prevent us
@@ -23,3 +27,8 @@ fn main() -> io::Result<()> {
     println!("The Answer is {}", answer);
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
@@ -3,29 +3,37 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#[cfg(windows)]
 use serde_derive::{Deserialize, Serialize};
+#[cfg(windows)]
 use std::collections::HashMap;
+#[cfg(windows)]
 use std::error::Error;
+#[cfg(windows)]
 use winreg::HKCU;
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Coords {
     x: u32,
     y: u32,
 }
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Size {
     w: u32,
     h: u32,
 }
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Rectangle {
     coords: Option<Coords>,
     size: Size,
 }
 
+#[cfg(windows)]
 fn main() -> Result<(), Box<dyn Error>> {
     let (key, _disp) = HKCU.create_subkey("Software\\RustEncodeMapKey")?;
     let mut v1 = HashMap::new();
@@ -52,3 +60,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("Equal to encoded: {:?}", v1 == v2);
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
@@ -3,17 +3,25 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
-extern crate serde_transcode;
+#[cfg(windows)]
 extern crate winreg;
+#[cfg(windows)]
 use std::error::Error;
+#[cfg(windows)]
+use winreg::snapshot::KeySnapshot;
+#[cfg(windows)]
 use winreg::HKCR;
 
+#[cfg(windows)]
 fn main() -> Result<(), Box<dyn Error>> {
     let key = HKCR.open_subkey("Folder")?;
 
-    let mut deserializer = winreg::decoder::Decoder::from_key(&key)?;
-    let mut serializer = serde_json::Serializer::pretty(std::io::stdout());
-
-    serde_transcode::transcode(&mut deserializer, &mut serializer)?;
+    let snapshot = KeySnapshot::from_key(&key, "Folder")?;
+    serde_json::to_writer_pretty(std::io::stdout(), &snapshot)?;
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
@@ -3,29 +3,37 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#[cfg(windows)]
 use serde_derive::{Deserialize, Serialize};
+#[cfg(windows)]
 use std::collections::HashMap;
+#[cfg(windows)]
 use std::error::Error;
+#[cfg(windows)]
 use winreg::HKCU;
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Coords {
     x: u32,
     y: u32,
 }
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Size {
     w: u32,
     h: u32,
 }
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Rectangle {
     coords: Option<Coords>,
     size: Size,
 }
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Test {
     t_bool: bool,
@@ -38,6 +46,8 @@ struct Test {
     t_map: HashMap<String, u32>,
     t_string: String,
     t_optional_string: Option<String>,
+    t_strings: Vec<String>, // REG_MULTI_SZ
+    t_points: Vec<Coords>,  // subkeys `0`, `1`, ...
     #[serde(with = "serde_bytes")]
     t_bytes: Vec<u8>,
     #[serde(rename = "")] // empty name becomes the (Default) value in the registry
@@ -51,6 +61,7 @@ struct Test {
     t_f32: f32,
 }
 
+#[cfg(windows)]
 fn main() -> Result<(), Box<dyn Error>> {
     let (key, _disp) = HKCU.create_subkey("Software\\RustEncode")?;
 
@@ -74,6 +85,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         t_map: map,
         t_string: "test 123!".to_owned(),
         t_optional_string: Some("test 456!".to_owned()),
+        t_strings: vec!["one".to_owned(), "two".to_owned()],
+        t_points: vec![Coords { x: 1, y: 2 }, Coords { x: 3, y: 4 }],
         t_bytes: vec![0xDE, 0xAD, 0xBE, 0xEF],
         t_char: 'a',
         t_i8: -123,
@@ -99,3 +112,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("Equal to encoded: {:?}", v1 == v2);
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
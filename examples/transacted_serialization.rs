@@ -3,31 +3,41 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#[cfg(windows)]
 use serde_derive::{Deserialize, Serialize};
+#[cfg(windows)]
 use std::collections::HashMap;
+#[cfg(windows)]
 use std::error::Error;
+#[cfg(windows)]
 use std::io;
+#[cfg(windows)]
 use winreg::transaction::Transaction;
+#[cfg(windows)]
 use winreg::HKCU;
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Coords {
     x: u32,
     y: u32,
 }
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Size {
     w: u32,
     h: u32,
 }
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Rectangle {
     coords: Option<Coords>,
     size: Size,
 }
 
+#[cfg(windows)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct Test {
     t_bool: bool,
@@ -52,6 +62,7 @@ struct Test {
     t_f32: f32,
 }
 
+#[cfg(windows)]
 fn main() -> Result<(), Box<dyn Error>> {
     let transaction = Transaction::new()?;
     let (key, _disp) = HKCU.create_subkey_transacted("Software\\RustEncode", &transaction)?;
@@ -111,3 +122,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("Equal to encoded: {:?}", v1 == v2);
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
@@ -3,10 +3,14 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#[cfg(windows)]
 use std::io;
+#[cfg(windows)]
 use winreg::transaction::Transaction;
+#[cfg(windows)]
 use winreg::HKCU;
 
+#[cfg(windows)]
 fn main() -> io::Result<()> {
     let t = Transaction::new()?;
     let (key, _disp) = HKCU.create_subkey_transacted("Software\\RustTransaction", &t)?;
@@ -30,3 +34,8 @@ fn main() -> io::Result<()> {
 
     Ok(())
 }
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("This example only works on Windows");
+}
@@ -0,0 +1,242 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Common interface of registry keys, so code written against `RegKey`
+//! can run against other stores as well.
+//!
+//!```no_run
+//!use std::io;
+//!use winreg::RegistryBackend;
+//!
+//!fn read_port<K: RegistryBackend>(root: &K) -> io::Result<u32> {
+//!    root.open_subkey("Software\\MyProduct\\Settings")?
+//!        .get_value("port")
+//!}
+//!```
+use crate::enum_keys::EnumKeys;
+use crate::enum_keys_os_string::EnumKeysOsString;
+use crate::enum_values::EnumValues;
+use crate::enum_values_os_string::EnumValuesOsString;
+use crate::enums::*;
+use crate::expand::{expand_value, EnvSource};
+use crate::reg_key_metadata::RegKeyMetadata;
+use crate::reg_value::RegValue;
+use crate::types::{FromRegValue, ToRegValue};
+use crate::value::Value;
+use std::ffi::{OsStr, OsString};
+use std::io::{self, Write};
+
+/// Enumeration of subkeys and values by index, the read-only part of a key
+/// the `EnumKeys`/`EnumValues` iterators need. Implemented by every `RegistryBackend`
+/// and by `hive::HiveKey`.
+pub trait RegistryEnum {
+    /// Name of the subkey at `index`, `None` when there are no more subkeys
+    fn enum_key(&self, index: u32) -> Option<io::Result<OsString>>;
+
+    /// Value at `index`, `None` when there are no more values
+    fn enum_value(&self, index: u32) -> Option<io::Result<(OsString, RegValue<'static>)>>;
+}
+
+/// Operations of an opened registry key.
+/// Paths use `\` as a separator, key and value names are case insensitive.
+/// Errors are `io::Error`s with the same Windows error codes `RegKey` returns.
+pub trait RegistryBackend: RegistryEnum + Sized {
+    /// Open subkey with desired permissions.
+    /// Will open another handle to itself if `path` is an empty string.
+    fn open_subkey_with_flags<P: AsRef<OsStr>>(&self, path: P, perms: u32) -> io::Result<Self>;
+
+    /// Create subkey (and all missing parent keys) and open it with desired permissions.
+    /// Will just open key if it already exists.
+    fn create_subkey_with_flags<P: AsRef<OsStr>>(
+        &self,
+        path: P,
+        perms: u32,
+    ) -> io::Result<(Self, RegDisposition)>;
+
+    fn rename_subkey<ON: AsRef<OsStr>, NN: AsRef<OsStr>>(
+        &self,
+        old_name: ON,
+        new_name: NN,
+    ) -> io::Result<()>;
+
+    fn query_info(&self) -> io::Result<RegKeyMetadata>;
+
+    /// Delete key. Cannot delete if it has subkeys.
+    fn delete_subkey<P: AsRef<OsStr>>(&self, path: P) -> io::Result<()>;
+
+    /// Recursively delete subkey with all its subkeys and values.
+    /// If `path` is an empty string, the subkeys and values of this key are deleted.
+    fn delete_subkey_all<P: AsRef<OsStr>>(&self, path: P) -> io::Result<()>;
+
+    /// Will get the `Default` value if `name` is an empty string.
+    fn get_raw_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<RegValue<'static>>;
+
+    /// Will set the `Default` value if `name` is an empty string.
+    fn set_raw_value<N: AsRef<OsStr>>(&self, name: N, value: &RegValue) -> io::Result<()>;
+
+    /// Will delete the `Default` value if `name` is an empty string.
+    fn delete_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<()>;
+
+    /// Open subkey with `KEY_READ` permissions.
+    fn open_subkey<P: AsRef<OsStr>>(&self, path: P) -> io::Result<Self> {
+        self.open_subkey_with_flags(path, KEY_READ)
+    }
+
+    /// Create subkey and open it with `KEY_ALL_ACCESS` permissions.
+    fn create_subkey<P: AsRef<OsStr>>(&self, path: P) -> io::Result<(Self, RegDisposition)> {
+        self.create_subkey_with_flags(path, KEY_ALL_ACCESS)
+    }
+
+    /// Copy all the values and subkeys from `path` to `dest` key.
+    /// Will copy the content of `self` if `path` is an empty string.
+    fn copy_tree<P: AsRef<OsStr>>(&self, path: P, dest: &Self) -> io::Result<()> {
+        let src = self.open_subkey_with_flags(path, KEY_READ)?;
+        for value in src.enum_values_os_string() {
+            let (name, value) = value?;
+            dest.set_raw_value(name, &value)?;
+        }
+        for name in src.enum_keys_os_string() {
+            let name = name?;
+            let (sub, _) = dest.create_subkey_with_flags(&name, KEY_ALL_ACCESS)?;
+            src.copy_tree(&name, &sub)?;
+        }
+        Ok(())
+    }
+
+    /// Write the key with all its subkeys and values as a `.reg` file,
+    /// see `RegKey::export_reg`.
+    fn export_reg<P: AsRef<OsStr>, W: Write>(&self, path: P, w: W) -> io::Result<()> {
+        crate::regfile::export(self, &path.as_ref().to_string_lossy())?.write(w)
+    }
+
+    /// Return an iterator over subkeys names as `String`s.
+    fn enum_keys(&self) -> EnumKeys<'_, Self> {
+        EnumKeys {
+            key: self,
+            index: 0,
+        }
+    }
+
+    /// Return an iterator over subkeys names as `OsString`s.
+    fn enum_keys_os_string(&self) -> EnumKeysOsString<'_, Self> {
+        EnumKeysOsString {
+            key: self,
+            index: 0,
+        }
+    }
+
+    /// Return an iterator over values represented as `(String, RegValue)`.
+    fn enum_values(&self) -> EnumValues<'_, Self> {
+        EnumValues {
+            key: self,
+            index: 0,
+        }
+    }
+
+    /// Return an iterator over values represented as `(OsString, RegValue)`.
+    fn enum_values_os_string(&self) -> EnumValuesOsString<'_, Self> {
+        EnumValuesOsString {
+            key: self,
+            index: 0,
+        }
+    }
+
+    /// Get a value and convert it to the specified rust type.
+    fn get_value<T: FromRegValue, N: AsRef<OsStr>>(&self, name: N) -> io::Result<T> {
+        T::from_reg_value(&self.get_raw_value(name)?)
+    }
+
+    /// Convert a value from a rust type and write it.
+    fn set_value<T: ToRegValue, N: AsRef<OsStr>>(&self, name: N, value: &T) -> io::Result<()> {
+        self.set_raw_value(name, &value.to_reg_value())
+    }
+
+    /// Get a value decoded according to its type, see `RegKey::get_typed_value`.
+    fn get_typed_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<Value> {
+        self.get_value(name)
+    }
+
+    /// Write a typed value with its type.
+    fn set_typed_value<N: AsRef<OsStr>>(&self, name: N, value: &Value) -> io::Result<()> {
+        self.set_value(name, value)
+    }
+
+    /// Get a `REG_SZ` or `REG_EXPAND_SZ` value with `%VAR%` references expanded
+    /// from `env`, see `RegKey::get_expanded_value`.
+    fn get_expanded_value<N: AsRef<OsStr>, E: EnvSource>(
+        &self,
+        name: N,
+        env: E,
+    ) -> io::Result<String> {
+        expand_value(&self.get_raw_value(name)?, env)
+    }
+
+    /// Save `Encodable` type to the key, see `RegKey::encode`.
+    /// Changes are not transacted unless the implementation says otherwise,
+    /// so a failed serialization may leave a part of the data written.
+    /// Part of `serialization-serde` feature.
+    #[cfg(feature = "serialization-serde")]
+    fn encode<T: serde::Serialize>(&self, value: &T) -> crate::encoder::EncodeResult<()> {
+        let mut encoder = crate::encoder::Encoder::from_backend(self)?;
+        value.serialize(&mut encoder)
+    }
+
+    /// Save `Encodable` type to the key removing everything under it first,
+    /// see `RegKey::encode_destructive`.
+    /// Part of `serialization-serde` feature.
+    #[cfg(feature = "serialization-serde")]
+    fn encode_destructive<T: serde::Serialize>(
+        &self,
+        value: &T,
+    ) -> crate::encoder::EncodeResult<()> {
+        let mut encoder = crate::encoder::Encoder::from_backend(self)?;
+        encoder.wipe()?;
+        value.serialize(&mut encoder)
+    }
+
+    /// Save `Encodable` type to the key with the number formats of `options`,
+    /// see `RegKey::encode_with_options`.
+    /// Part of `serialization-serde` feature.
+    #[cfg(feature = "serialization-serde")]
+    fn encode_with_options<T: serde::Serialize>(
+        &self,
+        value: &T,
+        options: crate::encoder::EncoderOptions,
+    ) -> crate::encoder::EncodeResult<()> {
+        let mut encoder = crate::encoder::Encoder::from_backend(self)?.with_options(options);
+        value.serialize(&mut encoder)
+    }
+
+    /// Load `Decodable` type from the key.
+    /// Part of `serialization-serde` feature.
+    #[cfg(feature = "serialization-serde")]
+    fn decode<'de, T: serde::Deserialize<'de>>(&self) -> crate::decoder::DecodeResult<T> {
+        let mut decoder = crate::decoder::Decoder::from_key(self)?;
+        T::deserialize(&mut decoder)
+    }
+
+    /// Load `Decodable` type from the key skipping the subkeys and values that fail,
+    /// see `RegKey::decode_lenient`.
+    /// Part of `serialization-serde` feature.
+    #[cfg(feature = "serialization-serde")]
+    fn decode_lenient<'de, T: serde::Deserialize<'de>>(
+        &self,
+    ) -> crate::decoder::DecodeResult<(T, Vec<crate::decoder::DecoderError>)> {
+        let mut decoder = crate::decoder::Decoder::from_key(self)?;
+        decoder.decode_lenient()
+    }
+
+    /// Load `Decodable` type from the key with the number formats of `options`.
+    /// Part of `serialization-serde` feature.
+    #[cfg(feature = "serialization-serde")]
+    fn decode_with_options<'de, T: serde::Deserialize<'de>>(
+        &self,
+        options: crate::decoder::DecoderOptions,
+    ) -> crate::decoder::DecodeResult<T> {
+        let mut decoder = crate::decoder::Decoder::from_key(self)?.with_options(options);
+        T::deserialize(&mut decoder)
+    }
+}
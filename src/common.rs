@@ -4,9 +4,10 @@
 // may not be copied, modified, or distributed
 // except according to those terms.
 #![macro_use]
-use std::ffi::OsStr;
-use std::os::windows::ffi::OsStrExt;
-use std::slice;
+use std::cmp::Ordering;
+use std::ffi::{OsStr, OsString};
+#[cfg(windows)]
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
 
 macro_rules! werr {
     ($e:expr) => {
@@ -14,10 +15,72 @@ macro_rules! werr {
     };
 }
 
+/// Encode a string as UTF-16 with a trailing `NULL`.
+/// Lossless on Windows, elsewhere an `OsStr` that is not valid Unicode
+/// is converted lossily.
+#[cfg(windows)]
 pub(crate) fn to_utf16<P: AsRef<OsStr>>(s: P) -> Vec<u16> {
     s.as_ref().encode_wide().chain(Some(0)).collect()
 }
 
+#[cfg(not(windows))]
+pub(crate) fn to_utf16<P: AsRef<OsStr>>(s: P) -> Vec<u16> {
+    s.as_ref()
+        .to_string_lossy()
+        .encode_utf16()
+        .chain(Some(0))
+        .collect()
+}
+
+/// Decode UTF-16 code units into an `OsString`.
+/// Lossless on Windows, elsewhere invalid data is replaced with `U+FFFD`.
+#[cfg(windows)]
+pub(crate) fn utf16_to_os_string(v: &[u16]) -> OsString {
+    OsString::from_wide(v)
+}
+
+#[cfg(not(windows))]
+pub(crate) fn utf16_to_os_string(v: &[u16]) -> OsString {
+    String::from_utf16_lossy(v).into()
+}
+
+/// Registry data is always little-endian
 pub(crate) fn v16_to_v8(v: &[u16]) -> Vec<u8> {
-    unsafe { slice::from_raw_parts(v.as_ptr() as *const u8, v.len() * 2).to_vec() }
+    v.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
+
+/// Registry data is always little-endian. A trailing odd byte is ignored.
+pub(crate) fn v8_to_v16(v: &[u8]) -> Vec<u16> {
+    v.chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect()
+}
+
+/// Upcase a single UTF-16 code unit the way the registry compares names:
+/// characters without a single-character uppercase form are left as is.
+pub(crate) fn upcase_utf16(c: u16) -> u16 {
+    match char::from_u32(c as u32) {
+        Some(ch) => {
+            let mut up = ch.to_uppercase();
+            match (up.next(), up.next()) {
+                (Some(u), None) if (u as u32) <= 0xFFFF => u as u16,
+                _ => c,
+            }
+        }
+        None => c,
+    }
+}
+
+/// Compare two registry key or value names ignoring case
+pub(crate) fn names_eq(a: &str, b: &str) -> bool {
+    a.encode_utf16()
+        .map(upcase_utf16)
+        .eq(b.encode_utf16().map(upcase_utf16))
+}
+
+/// Order of key names in the registry: by upcased UTF-16 code units
+pub(crate) fn compare_names(a: &str, b: &str) -> Ordering {
+    a.encode_utf16()
+        .map(upcase_utf16)
+        .cmp(b.encode_utf16().map(upcase_utf16))
 }
@@ -3,21 +3,17 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+use crate::backend::RegistryBackend;
+use crate::encoder::fmt_location;
+pub use crate::encoder::{FloatFormat, SignedFormat};
 use crate::enums::*;
-use crate::reg_key::RegKey;
 use crate::reg_value::RegValue;
 use crate::types::FromRegValue;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
 use std::io;
-
-macro_rules! parse_string {
-    ($s:ident) => {{
-        let s: String = $s.read_value()?;
-        s.parse()
-            .map_err(|e| DecoderError::ParseError(format!("{:?}", e)))
-    }};
-}
+use std::str::FromStr;
 
 macro_rules! no_impl {
     ($e:expr) => {
@@ -35,15 +31,66 @@ pub enum DecoderError {
     IoError(io::Error),
     ParseError(String),
     NoFieldName,
+    /// `error` happened in the subkey at `path`, relative to the decoded key,
+    /// and in the value `value` if it's about one
+    At {
+        path: String,
+        value: Option<String>,
+        error: Box<DecoderError>,
+    },
+}
+
+impl DecoderError {
+    /// Path of the subkey the error happened in, relative to the decoded key
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            DecoderError::At { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Name of the value the error happened in
+    pub fn value_name(&self) -> Option<&str> {
+        match self {
+            DecoderError::At { value, .. } => value.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The error without its location
+    pub fn inner(&self) -> &DecoderError {
+        match self {
+            DecoderError::At { error, .. } => error,
+            err => err,
+        }
+    }
 }
 
 impl fmt::Display for DecoderError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            DecoderError::DecodeNotImplemented(what) => write!(f, "not implemented: {}", what),
+            DecoderError::DeserializerError(msg) => f.write_str(msg),
+            DecoderError::IoError(err) => write!(f, "{}", err),
+            DecoderError::ParseError(msg) => write!(f, "parse error: {}", msg),
+            DecoderError::NoFieldName => f.write_str("no field name"),
+            DecoderError::At { path, value, error } => {
+                fmt_location(f, path, value.as_deref())?;
+                write!(f, ": {}", error)
+            }
+        }
     }
 }
 
-impl Error for DecoderError {}
+impl Error for DecoderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DecoderError::IoError(err) => Some(err),
+            DecoderError::At { error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
 
 impl From<io::Error> for DecoderError {
     fn from(err: io::Error) -> DecoderError {
@@ -64,25 +111,112 @@ enum DecoderCursor {
     FieldVal(u32, String),
 }
 
+/// How `REG_DWORD` and `REG_QWORD` values are read into signed integers and floats,
+/// the format they were encoded with. Strings are always accepted.
+/// By default the values are read as unsigned numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecoderOptions {
+    signed: SignedFormat,
+    floats: FloatFormat,
+}
+
+impl DecoderOptions {
+    pub fn new() -> DecoderOptions {
+        DecoderOptions::default()
+    }
+
+    /// Format of `i8`, `i16`, `i32` and `i64`
+    pub fn signed(mut self, format: SignedFormat) -> DecoderOptions {
+        self.signed = format;
+        self
+    }
+
+    /// Format of `f32` and `f64`
+    pub fn floats(mut self, format: FloatFormat) -> DecoderOptions {
+        self.floats = format;
+        self
+    }
+}
+
+/// Number stored as a value of any type
+enum Number {
+    Dword(u32),
+    Qword(u64),
+    Str(String),
+}
+
 #[derive(Debug)]
-pub struct Decoder {
-    key: RegKey,
+pub struct Decoder<K> {
+    key: K,
     cursor: DecoderCursor,
+    options: DecoderOptions,
+    /// Path of `key` relative to the decoded key
+    path: String,
+    /// Skip the entries that fail to decode, see `decode_lenient`
+    lenient: bool,
+    /// Errors of the skipped entries
+    errors: Vec<DecoderError>,
 }
 
 const DECODER_SAM: u32 = KEY_QUERY_VALUE | KEY_ENUMERATE_SUB_KEYS;
 
-impl Decoder {
-    pub fn from_key(key: &RegKey) -> DecodeResult<Decoder> {
+impl<K: RegistryBackend> Decoder<K> {
+    pub fn from_key(key: &K) -> DecodeResult<Decoder<K>> {
         key.open_subkey_with_flags("", DECODER_SAM)
             .map(Decoder::new)
             .map_err(DecoderError::IoError)
     }
 
-    fn new(key: RegKey) -> Decoder {
+    /// Use `options` instead of the default ones
+    pub fn with_options(mut self, options: DecoderOptions) -> Decoder<K> {
+        self.options = options;
+        self
+    }
+
+    fn new(key: K) -> Decoder<K> {
         Decoder {
             key,
             cursor: DecoderCursor::Start,
+            options: DecoderOptions::default(),
+            path: String::new(),
+            lenient: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Decoder for the subkey `name`, with the same options
+    fn nested(&self, key: K, name: &str) -> Decoder<K> {
+        Decoder {
+            key,
+            cursor: DecoderCursor::Start,
+            options: self.options,
+            path: join_path(&self.path, name),
+            lenient: self.lenient,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Add the location to `err` unless it has one
+    fn at(&self, value: Option<&str>, err: DecoderError) -> DecoderError {
+        match err {
+            DecoderError::At { .. } => err,
+            err => DecoderError::At {
+                path: self.path.clone(),
+                value: value.map(str::to_owned),
+                error: Box::new(err),
+            },
+        }
+    }
+
+    /// Add the location of the subkey `name` to `err` unless it has one
+    fn at_subkey(&self, name: &str, err: DecoderError) -> DecoderError {
+        match err {
+            DecoderError::At { .. } => err,
+            err => DecoderError::At {
+                path: join_path(&self.path, name),
+                value: None,
+                error: Box::new(err),
+            },
         }
     }
 
@@ -113,4 +247,72 @@ impl Decoder {
             _ => Err(DecoderError::DeserializerError("Not a value".to_owned())),
         }
     }
+
+    fn read_number(&mut self) -> Result<Number, DecoderError> {
+        let cursor = self.cursor.clone();
+        match cursor {
+            DecoderCursor::FieldVal(index, name) => {
+                self.cursor = DecoderCursor::Field(index + 1);
+                let v = self.key.get_raw_value(name)?;
+                Ok(match v.vtype {
+                    REG_DWORD => Number::Dword(u32::from_reg_value(&v)?),
+                    REG_QWORD => Number::Qword(u64::from_reg_value(&v)?),
+                    _ => Number::Str(String::from_reg_value(&v)?),
+                })
+            }
+            _ => Err(DecoderError::DeserializerError("Not a value".to_owned())),
+        }
+    }
+
+    fn read_signed<T>(&mut self) -> Result<T, DecoderError>
+    where
+        T: TryFrom<i64> + FromStr,
+        T::Err: fmt::Debug,
+    {
+        let twos_complement = self.options.signed == SignedFormat::TwosComplement;
+        let n = match self.read_number()? {
+            Number::Str(s) => return parse(&s),
+            Number::Dword(n) if twos_complement => n as i32 as i64,
+            Number::Dword(n) => n as i64,
+            Number::Qword(n) if twos_complement => n as i64,
+            Number::Qword(n) => i64::try_from(n).map_err(|_| out_of_range(n))?,
+        };
+        T::try_from(n).map_err(|_| out_of_range(n))
+    }
+
+    fn read_float<T>(&mut self, from_f64: fn(f64) -> T) -> Result<T, DecoderError>
+    where
+        T: FromStr,
+        T::Err: fmt::Debug,
+    {
+        let bits = self.options.floats == FloatFormat::Bits;
+        let n = match self.read_number()? {
+            Number::Str(s) => return parse(&s),
+            Number::Dword(n) if bits => f32::from_bits(n) as f64,
+            Number::Dword(n) => n as f64,
+            Number::Qword(n) if bits => f64::from_bits(n),
+            Number::Qword(n) => n as f64,
+        };
+        Ok(from_f64(n))
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}\\{}", path, name)
+    }
+}
+
+fn parse<T: FromStr>(s: &str) -> Result<T, DecoderError>
+where
+    T::Err: fmt::Debug,
+{
+    s.parse()
+        .map_err(|e| DecoderError::ParseError(format!("{:?}", e)))
+}
+
+fn out_of_range<N: fmt::Display>(n: N) -> DecoderError {
+    DecoderError::ParseError(format!("{} is out of range", n))
 }
@@ -4,9 +4,16 @@
 // may not be copied, modified, or distributed
 // except according to those terms.
 use super::{DecodeResult, Decoder, DecoderCursor, DecoderError, DECODER_SAM};
-use crate::{types::FromRegValue, RegValue};
+use crate::backend::RegistryBackend;
+use crate::serde_with::{raw_to_bytes, RAW_VALUE};
+use crate::{types::FromRegValue, RegValue, Value};
 use serde::de::*;
+use std::convert::TryFrom;
+use std::ffi::OsStr;
 use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
 use windows_sys::Win32::Foundation;
 
 impl Error for DecoderError {
@@ -15,7 +22,56 @@ impl Error for DecoderError {
     }
 }
 
-impl<'de> Deserializer<'de> for &mut Decoder {
+impl<K: RegistryBackend> Decoder<K> {
+    /// Decode `T` skipping the subkeys and values that fail to decode, with their errors.
+    /// A failed map entry is left out of the map, a failed `Option` field or sequence element
+    /// reads as `None`. Any other failure makes the key it is in fail instead, so this returns
+    /// the error of the entry if it can't be skipped up to the decoded key.
+    pub fn decode_lenient<'de, T: Deserialize<'de>>(
+        &mut self,
+    ) -> DecodeResult<(T, Vec<DecoderError>)> {
+        self.cursor = DecoderCursor::Start;
+        self.lenient = true;
+        let res = T::deserialize(&mut *self);
+        self.lenient = false;
+        let errors = mem::take(&mut self.errors);
+        res.map(|value| (value, errors))
+    }
+
+    /// Move past the entry at `cursor` that failed with `err` and add its location.
+    /// Errors that aren't about an entry are returned.
+    fn skip_entry(
+        &mut self,
+        cursor: DecoderCursor,
+        err: DecoderError,
+    ) -> DecodeResult<DecoderError> {
+        use super::DecoderCursor::*;
+        match cursor {
+            KeyName(index, name) | KeyVal(index, name) => {
+                self.cursor = Key(index + 1);
+                Ok(self.at_subkey(&name, err))
+            }
+            FieldName(index, name) | FieldVal(index, name) => {
+                self.cursor = Field(index + 1);
+                Ok(self.at(Some(&name), err))
+            }
+            _ => Err(err),
+        }
+    }
+
+    /// Read a failed entry like a missing one, `None` for an `Option`, and keep its error
+    fn skip_missing<'de, T: Deserialize<'de>>(&mut self, err: DecoderError) -> DecodeResult<T> {
+        match T::deserialize(value::UnitDeserializer::<DecoderError>::new()) {
+            Ok(value) => {
+                self.errors.push(err);
+                Ok(value)
+            }
+            Err(_) => Err(err),
+        }
+    }
+}
+
+impl<'de, R: RegistryBackend> Deserializer<'de> for &mut Decoder<R> {
     type Error = DecoderError;
     fn deserialize_any<V>(self, visitor: V) -> DecodeResult<V::Value>
     where
@@ -28,20 +84,24 @@ impl<'de> Deserializer<'de> for &mut Decoder {
             KeyName(..) | FieldName(..) => self.deserialize_string(visitor),
             FieldVal(index, name) => {
                 use crate::enums::RegType::*;
-                let v = self.key.get_raw_value(name)?;
+                let v = self.key.get_raw_value(&name)?;
+                if v.vtype == REG_MULTI_SZ {
+                    return self.deserialize_seq(visitor);
+                }
                 self.cursor = Field(index + 1);
                 match v.vtype {
-                    REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ => {
-                        visitor.visit_string(String::from_reg_value(&v)?)
-                    }
+                    REG_SZ => visitor.visit_string(String::from_reg_value(&v)?),
                     REG_DWORD => visitor.visit_u32(u32::from_reg_value(&v)?),
                     REG_QWORD => visitor.visit_u64(u64::from_reg_value(&v)?),
-                    REG_BINARY => visitor.visit_byte_buf(v.bytes.into_owned()),
                     REG_NONE => visitor.visit_none(),
-                    _ => no_impl!(format!(
-                        "value type deserialization not implemented {:?}",
-                        v.vtype
-                    )),
+                    // other types are passed like `serde_with` fields, so these can read them
+                    // when serde buffers the value
+                    _ => {
+                        let raw = raw_to_bytes(&v);
+                        visitor.visit_newtype_struct(value::BytesDeserializer::<DecoderError>::new(
+                            &raw,
+                        ))
+                    }
                 }
             }
             _ => no_impl!("deserialize_any"),
@@ -87,42 +147,42 @@ impl<'de> Deserializer<'de> for &mut Decoder {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i8(parse_string!(self)?)
+        visitor.visit_i8(self.read_signed()?)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i16(parse_string!(self)?)
+        visitor.visit_i16(self.read_signed()?)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i32(parse_string!(self)?)
+        visitor.visit_i32(self.read_signed()?)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i64(parse_string!(self)?)
+        visitor.visit_i64(self.read_signed()?)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f32(parse_string!(self)?)
+        visitor.visit_f32(self.read_float(|n| n as f32)?)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f64(parse_string!(self)?)
+        visitor.visit_f64(self.read_float(|n| n)?)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> DecodeResult<V::Value>
@@ -191,7 +251,7 @@ impl<'de> Deserializer<'de> for &mut Decoder {
                             self.cursor = DecoderCursor::Field(index + 1);
                             Err(DecoderError::DeserializerError("Found REG_NONE".to_owned()))
                         }
-                        val => Ok(val),
+                        val => val,
                     }
                 }
                 _ => Err(DecoderError::DeserializerError("Nothing found".to_owned())),
@@ -217,41 +277,68 @@ impl<'de> Deserializer<'de> for &mut Decoder {
         no_impl!("deserialize_unit_struct")
     }
 
-    fn deserialize_newtype_struct<V>(
-        self,
-        _name: &'static str,
-        _visitor: V,
-    ) -> DecodeResult<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        no_impl!("deserialize_newtype_struct")
+        use super::DecoderCursor::*;
+        let cursor = self.cursor.clone();
+        match cursor {
+            FieldVal(index, value_name) if name == RAW_VALUE => {
+                let v = self.key.get_raw_value(value_name)?;
+                self.cursor = Field(index + 1);
+                let raw = raw_to_bytes(&v);
+                visitor.visit_newtype_struct(value::BytesDeserializer::<DecoderError>::new(&raw))
+            }
+            _ => no_impl!("deserialize_newtype_struct"),
+        }
     }
 
-    fn deserialize_seq<V>(self, _visitor: V) -> DecodeResult<V::Value>
+    fn deserialize_seq<V>(self, visitor: V) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        no_impl!("deserialize_seq")
+        use super::DecoderCursor::*;
+        let cursor = self.cursor.clone();
+        match cursor {
+            Start => visitor.visit_seq(NumberedSeq::new(self)?),
+            FieldVal(index, name) => {
+                let v = self.key.get_raw_value(name)?;
+                self.cursor = Field(index + 1);
+                let strings = match Value::try_from(&v)? {
+                    Value::MultiSz(strings) => strings,
+                    _ => {
+                        return Err(DecoderError::IoError(io::Error::from_raw_os_error(
+                            Foundation::ERROR_BAD_FILE_TYPE as i32,
+                        )))
+                    }
+                };
+                let mut seq = value::SeqDeserializer::<_, DecoderError>::new(strings.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            _ => no_impl!("deserialize_seq"),
+        }
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> DecodeResult<V::Value>
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        no_impl!("deserialize_tuple")
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_tuple_struct<V>(
         self,
         _name: &'static str,
         _len: usize,
-        _visitor: V,
+        visitor: V,
     ) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        no_impl!("deserialize_tuple_struct")
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_map<V>(self, visitor: V) -> DecodeResult<V::Value>
@@ -283,13 +370,48 @@ impl<'de> Deserializer<'de> for &mut Decoder {
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
-        _visitor: V,
+        variants: &'static [&'static str],
+        visitor: V,
     ) -> DecodeResult<V::Value>
     where
         V: Visitor<'de>,
     {
-        no_impl!("deserialize_enum")
+        use super::DecoderCursor::*;
+        match self.cursor {
+            // unit variant
+            FieldVal(..) => {
+                let variant: String = self.read_value()?;
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            // the first variant with a subkey or value
+            Start => {
+                for variant in variants {
+                    match self.key.open_subkey_with_flags(variant, DECODER_SAM) {
+                        Ok(subkey) => {
+                            return visitor.visit_enum(VariantDecoder {
+                                variant,
+                                nested: Some(self.nested(subkey, variant)),
+                                dec: self,
+                            })
+                        }
+                        Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}
+                        Err(err) => return Err(DecoderError::IoError(err)),
+                    }
+                    if self.key.get_raw_value(variant).is_ok() {
+                        self.cursor = FieldVal(0, (*variant).to_owned());
+                        return visitor.visit_enum(VariantDecoder {
+                            variant,
+                            nested: None,
+                            dec: self,
+                        });
+                    }
+                }
+                Err(DecoderError::DeserializerError(
+                    "No enum variant found".to_owned(),
+                ))
+            }
+            _ => no_impl!("deserialize_enum"),
+        }
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> DecodeResult<V::Value>
@@ -300,7 +422,7 @@ impl<'de> Deserializer<'de> for &mut Decoder {
     }
 }
 
-impl<'de> MapAccess<'de> for Decoder {
+impl<'de, R: RegistryBackend> MapAccess<'de> for Decoder<R> {
     type Error = DecoderError;
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
@@ -314,12 +436,10 @@ impl<'de> MapAccess<'de> for Decoder {
             }
             Key(index) => match self.key.enum_key(index) {
                 Some(res) => {
-                    self.cursor = KeyName(
-                        index,
-                        res?.into_string().map_err(|_| {
-                            std::io::Error::from_raw_os_error(Foundation::ERROR_INVALID_DATA as i32)
-                        })?,
-                    );
+                    let name = res?.into_string().map_err(|_| {
+                        std::io::Error::from_raw_os_error(Foundation::ERROR_INVALID_DATA as i32)
+                    })?;
+                    self.cursor = KeyName(index, name);
                     seed.deserialize(&mut *self).map(Some)
                 }
                 None => {
@@ -331,14 +451,10 @@ impl<'de> MapAccess<'de> for Decoder {
                 let next_value = self.key.enum_value(index);
                 match next_value {
                     Some(res) => {
-                        self.cursor = FieldName(
-                            index,
-                            res?.0.into_string().map_err(|_| {
-                                std::io::Error::from_raw_os_error(
-                                    Foundation::ERROR_INVALID_DATA as i32,
-                                )
-                            })?,
-                        );
+                        let name = res?.0.into_string().map_err(|_| {
+                            std::io::Error::from_raw_os_error(Foundation::ERROR_INVALID_DATA as i32)
+                        })?;
+                        self.cursor = FieldName(index, name);
                         seed.deserialize(&mut *self).map(Some)
                     }
                     None => Ok(None),
@@ -353,17 +469,225 @@ impl<'de> MapAccess<'de> for Decoder {
         V: DeserializeSeed<'de>,
     {
         use super::DecoderCursor::*;
-        match self.cursor {
-            KeyVal(index, ref name) => match self.key.open_subkey_with_flags(name, DECODER_SAM) {
+        let cursor = self.cursor.clone();
+        match cursor {
+            KeyVal(index, name) => match self.key.open_subkey_with_flags(&name, DECODER_SAM) {
                 Ok(subkey) => {
-                    let mut nested = Decoder::new(subkey);
+                    let mut nested = self.nested(subkey, &name);
                     self.cursor = Key(index + 1);
-                    seed.deserialize(&mut nested)
+                    let value = seed
+                        .deserialize(&mut nested)
+                        .map_err(|err| nested.at(None, err))?;
+                    self.errors.append(&mut nested.errors);
+                    Ok(value)
                 }
-                Err(err) => Err(DecoderError::IoError(err)),
+                Err(err) => Err(self.at_subkey(&name, DecoderError::IoError(err))),
             },
-            FieldVal(..) => seed.deserialize(&mut *self),
+            FieldVal(_, name) => seed
+                .deserialize(&mut *self)
+                .map_err(|err| self.at(Some(&name), err)),
             _ => no_impl!("Wrong cursor state (field)"),
         }
     }
+
+    // lenient decoding skips failed entries here, where their type is known
+
+    fn next_value<V: Deserialize<'de>>(&mut self) -> DecodeResult<V> {
+        let cursor = self.cursor.clone();
+        match self.next_value_seed(PhantomData) {
+            Err(err) if self.lenient => {
+                let err = self.skip_entry(cursor, err)?;
+                self.skip_missing(err)
+            }
+            res => res,
+        }
+    }
+
+    fn next_entry<K: Deserialize<'de>, V: Deserialize<'de>>(
+        &mut self,
+    ) -> DecodeResult<Option<(K, V)>> {
+        if !self.lenient {
+            return self.next_entry_seed(PhantomData, PhantomData);
+        }
+        loop {
+            let err = match self.next_key_seed(PhantomData) {
+                Ok(Some(key)) => {
+                    let cursor = self.cursor.clone();
+                    match self.next_value_seed(PhantomData) {
+                        Ok(value) => return Ok(Some((key, value))),
+                        Err(err) => self.skip_entry(cursor, err)?,
+                    }
+                }
+                Ok(None) => return Ok(None),
+                Err(err) => {
+                    let cursor = self.cursor.clone();
+                    self.skip_entry(cursor, err)?
+                }
+            };
+            // leave the entry out
+            self.errors.push(err);
+        }
+    }
+}
+
+/// Elements of a sequence stored as subkeys and values named `0`, `1`, ...
+/// Missing elements are read as `None`.
+struct NumberedSeq<'a, R> {
+    dec: &'a mut Decoder<R>,
+    index: u32,
+    len: u32,
+}
+
+/// Index of the element stored under `name`, if it is one
+fn element_index(name: &OsStr) -> Option<u32> {
+    let name = name.to_str()?;
+    name.parse()
+        .ok()
+        .filter(|index: &u32| index.to_string() == name)
+}
+
+impl<'a, R: RegistryBackend> NumberedSeq<'a, R> {
+    fn new(dec: &'a mut Decoder<R>) -> DecodeResult<NumberedSeq<'a, R>> {
+        let mut len = 0;
+        let mut i = 0;
+        while let Some(res) = dec.key.enum_key(i) {
+            if let Some(index) = element_index(&res?) {
+                len = len.max(index + 1);
+            }
+            i += 1;
+        }
+        i = 0;
+        while let Some(res) = dec.key.enum_value(i) {
+            if let Some(index) = element_index(&res?.0) {
+                len = len.max(index + 1);
+            }
+            i += 1;
+        }
+        Ok(NumberedSeq { dec, index: 0, len })
+    }
+}
+
+impl<'de, R: RegistryBackend> SeqAccess<'de> for NumberedSeq<'_, R> {
+    type Error = DecoderError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> DecodeResult<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let index = self.index;
+        let name = index.to_string();
+        self.index += 1;
+        let subkey = match self.dec.key.open_subkey_with_flags(&name, DECODER_SAM) {
+            Ok(subkey) => Some(subkey),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(self.dec.at(None, DecoderError::IoError(err))),
+        };
+        match subkey {
+            Some(subkey) => {
+                let mut nested = self.dec.nested(subkey, &name);
+                let value = seed
+                    .deserialize(&mut nested)
+                    .map_err(|err| nested.at(None, err))?;
+                self.dec.errors.append(&mut nested.errors);
+                Ok(Some(value))
+            }
+            None => {
+                self.dec.cursor = DecoderCursor::FieldVal(index, name.clone());
+                seed.deserialize(&mut *self.dec)
+                    .map(Some)
+                    .map_err(|err| self.dec.at(Some(&name), err))
+            }
+        }
+    }
+
+    fn next_element<T: Deserialize<'de>>(&mut self) -> DecodeResult<Option<T>> {
+        match self.next_element_seed(PhantomData) {
+            Err(err) if self.dec.lenient => self.dec.skip_missing(err).map(Some),
+            res => res,
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some((self.len - self.index) as usize)
+    }
+}
+
+/// Payload of a data-carrying enum variant: a subkey or a value named after the variant
+struct VariantDecoder<'a, R> {
+    variant: &'static str,
+    nested: Option<Decoder<R>>,
+    dec: &'a mut Decoder<R>,
+}
+
+impl<R: RegistryBackend> VariantDecoder<'_, R> {
+    fn payload(&mut self) -> &mut Decoder<R> {
+        match self.nested {
+            Some(ref mut nested) => nested,
+            None => self.dec,
+        }
+    }
+
+    /// Locate the error of the payload, keep the errors of the entries it skipped
+    fn finish<T>(self, res: DecodeResult<T>) -> DecodeResult<T> {
+        match self.nested {
+            Some(mut nested) => {
+                let value = res.map_err(|err| nested.at(None, err))?;
+                self.dec.errors.append(&mut nested.errors);
+                Ok(value)
+            }
+            None => res.map_err(|err| self.dec.at(Some(self.variant), err)),
+        }
+    }
+}
+
+impl<'de, R: RegistryBackend> EnumAccess<'de> for VariantDecoder<'_, R> {
+    type Error = DecoderError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> DecodeResult<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let name: value::StrDeserializer<DecoderError> = self.variant.into_deserializer();
+        Ok((seed.deserialize(name)?, self))
+    }
+}
+
+impl<'de, R: RegistryBackend> VariantAccess<'de> for VariantDecoder<'_, R> {
+    type Error = DecoderError;
+
+    fn unit_variant(self) -> DecodeResult<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(mut self, seed: T) -> DecodeResult<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let res = seed.deserialize(self.payload());
+        self.finish(res)
+    }
+
+    fn tuple_variant<V>(mut self, _len: usize, visitor: V) -> DecodeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let res = self.payload().deserialize_seq(visitor);
+        self.finish(res)
+    }
+
+    fn struct_variant<V>(
+        mut self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> DecodeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let res = self.payload().deserialize_map(visitor);
+        self.finish(res)
+    }
 }
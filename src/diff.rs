@@ -0,0 +1,352 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Comparison of two key trees, e.g. a snapshot taken before an installer ran and the live
+//! registry after it. The trees can be anything that implements `ExportSource`:
+//! `RegKey`s, `HiveKey`s of hive files or other `RegistryBackend`s, in any combination.
+//!
+//! Key and value names are compared ignoring case the way the registry does,
+//! so a key that only changed the case of its name is not a difference.
+//! The result can be saved as a `.reg` file that turns the first tree into the second one.
+//!
+//!```no_run
+//!use std::io;
+//!use winreg::diff::DiffOptions;
+//!use winreg::hive::Hive;
+//!
+//!fn main() -> io::Result<()> {
+//!    let before = Hive::open("SOFTWARE.before")?;
+//!    let after = Hive::open("SOFTWARE.after")?;
+//!    let diff = DiffOptions::new()
+//!        .ignore_key("Microsoft\\Windows\\CurrentVersion\\WindowsUpdate")
+//!        .ignore_value("Microsoft\\Windows\\CurrentVersion\\*", "LastRun")
+//!        .diff(&before.root_key(), &after.root_key())?;
+//!    for entry in &diff.entries {
+//!        println!("{:?}", entry);
+//!    }
+//!    diff.to_reg_file("HKEY_LOCAL_MACHINE\\SOFTWARE").save("installer.reg")
+//!}
+//!```
+use crate::common::*;
+use crate::reg_value::RegValue;
+use crate::regfile::{to_static, ExportSource, RegFile, RegFileKey, RegFileValue, RegFileVersion};
+use std::cmp::Ordering;
+use std::ffi::OsString;
+use std::io;
+
+/// A single difference. Paths are relative to the compared keys,
+/// an empty path is the compared key itself.
+#[derive(Debug, PartialEq)]
+pub enum DiffEntry {
+    /// Key exists only in the second tree. Its values and subkeys follow as separate entries.
+    KeyAdded(String),
+    /// Key exists only in the first tree. Its values and subkeys follow as separate entries.
+    KeyRemoved(String),
+    ValueAdded {
+        key: String,
+        name: String,
+        value: RegValue<'static>,
+    },
+    ValueRemoved {
+        key: String,
+        name: String,
+        value: RegValue<'static>,
+    },
+    /// Value with a different type or data
+    ValueModified {
+        key: String,
+        name: String,
+        old: RegValue<'static>,
+        new: RegValue<'static>,
+    },
+}
+
+/// Differences between two trees, in the registry order of keys and values.
+/// Keys come before their values and the values before the subkeys.
+#[derive(Debug, Default)]
+pub struct Diff {
+    pub entries: Vec<DiffEntry>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Build a `.reg` patch that turns the first tree into the second one.
+    /// `root` is the full path of the compared keys, e.g. `HKEY_LOCAL_MACHINE\SOFTWARE`.
+    /// Removed keys become `[-Key]` sections, removed values `"Name"=-` lines.
+    pub fn to_reg_file(&self, root: &str) -> RegFile {
+        let root = root.trim_end_matches('\\');
+        let mut file = RegFile::new(RegFileVersion::Regedit5);
+        let mut removed: Option<&str> = None;
+        for entry in &self.entries {
+            let (key, value) = match entry {
+                DiffEntry::KeyAdded(key) => {
+                    file.keys.push(RegFileKey {
+                        path: join(root, key),
+                        delete: false,
+                        values: Vec::new(),
+                    });
+                    continue;
+                }
+                DiffEntry::KeyRemoved(key) => {
+                    if !removed.map_or(false, |r| is_under(key, r)) {
+                        removed = Some(key);
+                        file.keys.push(RegFileKey {
+                            path: join(root, key),
+                            delete: true,
+                            values: Vec::new(),
+                        });
+                    }
+                    continue;
+                }
+                DiffEntry::ValueRemoved { key, name, .. } => {
+                    if removed.map_or(false, |r| is_under(key, r)) {
+                        continue;
+                    }
+                    (
+                        key,
+                        RegFileValue {
+                            name: name.clone(),
+                            value: None,
+                        },
+                    )
+                }
+                DiffEntry::ValueAdded { key, name, value }
+                | DiffEntry::ValueModified {
+                    key,
+                    name,
+                    new: value,
+                    ..
+                } => (
+                    key,
+                    RegFileValue {
+                        name: name.clone(),
+                        value: Some(to_static(value)),
+                    },
+                ),
+            };
+            let path = join(root, key);
+            match file.keys.last_mut() {
+                Some(last) if !last.delete && last.path == path => last.values.push(value),
+                _ => file.keys.push(RegFileKey {
+                    path,
+                    delete: false,
+                    values: vec![value],
+                }),
+            }
+        }
+        file
+    }
+}
+
+fn join(root: &str, path: &str) -> String {
+    match (root.is_empty(), path.is_empty()) {
+        (_, true) => root.to_owned(),
+        (true, false) => path.to_owned(),
+        (false, false) => format!("{}\\{}", root, path),
+    }
+}
+
+/// `path` is `parent` or one of its subkeys
+fn is_under(path: &str, parent: &str) -> bool {
+    parent.is_empty()
+        || (path.len() >= parent.len()
+            && path.is_char_boundary(parent.len())
+            && names_eq(&path[..parent.len()], parent)
+            && (path.len() == parent.len() || path[parent.len()..].starts_with('\\')))
+}
+
+/// Key path pattern: names separated by `\`, compared ignoring case,
+/// `*` stands for any single name
+#[derive(Debug, Clone)]
+struct Pattern(Vec<String>);
+
+impl Pattern {
+    fn new(pattern: &str) -> Pattern {
+        Pattern(
+            pattern
+                .split('\\')
+                .filter(|n| !n.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        )
+    }
+
+    /// Matches the path or, if `subkeys` is set, one of its parents
+    fn matches(&self, path: &str, subkeys: bool) -> bool {
+        let names: Vec<&str> = path.split('\\').filter(|n| !n.is_empty()).collect();
+        if names.len() < self.0.len() || (!subkeys && names.len() != self.0.len()) {
+            return false;
+        }
+        self.0
+            .iter()
+            .zip(names)
+            .all(|(p, n)| p == "*" || names_eq(p, n))
+    }
+}
+
+/// Filters for `diff`
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    keys: Vec<Pattern>,
+    values: Vec<(Pattern, String)>,
+}
+
+impl DiffOptions {
+    pub fn new() -> DiffOptions {
+        DiffOptions::default()
+    }
+
+    /// Skip keys matching `pattern` with all their subkeys and values.
+    /// The pattern is a path relative to the compared keys, `*` matches any key name.
+    pub fn ignore_key(mut self, pattern: &str) -> DiffOptions {
+        self.keys.push(Pattern::new(pattern));
+        self
+    }
+
+    /// Skip values named `name` in keys matching `key_pattern`.
+    /// Use `*` as the name to skip all values of the keys, `""` for the default value.
+    pub fn ignore_value(mut self, key_pattern: &str, name: &str) -> DiffOptions {
+        self.values
+            .push((Pattern::new(key_pattern), name.to_owned()));
+        self
+    }
+
+    /// Compare the trees under `a` and `b`
+    pub fn diff<A: ExportSource, B: ExportSource>(&self, a: &A, b: &B) -> io::Result<Diff> {
+        let mut diff = Diff::default();
+        self.diff_keys(&mut diff.entries, "", Some(a), Some(b))?;
+        Ok(diff)
+    }
+
+    fn key_ignored(&self, path: &str) -> bool {
+        self.keys.iter().any(|p| p.matches(path, true))
+    }
+
+    fn value_ignored(&self, path: &str, name: &str) -> bool {
+        self.values
+            .iter()
+            .any(|(p, n)| p.matches(path, false) && (n == "*" || names_eq(n, name)))
+    }
+
+    /// Compare a key that exists in at least one of the trees
+    fn diff_keys<A: ExportSource, B: ExportSource>(
+        &self,
+        out: &mut Vec<DiffEntry>,
+        path: &str,
+        a: Option<&A>,
+        b: Option<&B>,
+    ) -> io::Result<()> {
+        match (a.is_some(), b.is_some()) {
+            (true, false) => out.push(DiffEntry::KeyRemoved(path.to_owned())),
+            (false, true) => out.push(DiffEntry::KeyAdded(path.to_owned())),
+            _ => {}
+        }
+
+        let a_values = sorted(a.map(|a| a.values()).transpose()?.unwrap_or_default());
+        let b_values = sorted(b.map(|b| b.values()).transpose()?.unwrap_or_default());
+        for (a_value, b_value) in merge(a_values, b_values) {
+            let name = match (&a_value, &b_value) {
+                (Some((name, _)), _) | (None, Some((name, _))) => name.clone(),
+                (None, None) => continue,
+            };
+            if self.value_ignored(path, &name) {
+                continue;
+            }
+            let key = path.to_owned();
+            out.push(match (a_value, b_value) {
+                (Some((_, old)), Some((_, new))) => {
+                    if old == new {
+                        continue;
+                    }
+                    DiffEntry::ValueModified {
+                        key,
+                        name,
+                        old,
+                        new,
+                    }
+                }
+                (Some((_, value)), None) => DiffEntry::ValueRemoved { key, name, value },
+                (None, Some((_, value))) => DiffEntry::ValueAdded { key, name, value },
+                (None, None) => continue,
+            });
+        }
+
+        let a_keys = sorted(names(a)?);
+        let b_keys = sorted(names(b)?);
+        for (a_key, b_key) in merge(a_keys, b_keys) {
+            let name = match (&a_key, &b_key) {
+                (Some((name, _)), _) | (None, Some((name, _))) => name.clone(),
+                (None, None) => continue,
+            };
+            let sub_path = join(path, &name);
+            if self.key_ignored(&sub_path) {
+                continue;
+            }
+            let a_sub = match (a, a_key) {
+                (Some(a), Some((_, os_name))) => Some(a.open(&os_name)?),
+                _ => None,
+            };
+            let b_sub = match (b, b_key) {
+                (Some(b), Some((_, os_name))) => Some(b.open(&os_name)?),
+                _ => None,
+            };
+            self.diff_keys(out, &sub_path, a_sub.as_ref(), b_sub.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare the trees under `a` and `b` without filters
+pub fn diff<A: ExportSource, B: ExportSource>(a: &A, b: &B) -> io::Result<Diff> {
+    DiffOptions::new().diff(a, b)
+}
+
+fn names<S: ExportSource>(key: Option<&S>) -> io::Result<Vec<(OsString, OsString)>> {
+    Ok(match key {
+        Some(key) => key
+            .subkey_names()?
+            .into_iter()
+            .map(|n| (n.clone(), n))
+            .collect(),
+        None => Vec::new(),
+    })
+}
+
+/// Convert names to `String`s and sort them in the registry order
+fn sorted<T>(items: Vec<(OsString, T)>) -> Vec<(String, T)> {
+    let mut items: Vec<(String, T)> = items
+        .into_iter()
+        .map(|(n, v)| (n.to_string_lossy().into_owned(), v))
+        .collect();
+    items.sort_by(|a, b| compare_names(&a.0, &b.0));
+    items
+}
+
+/// Pair up items of two lists sorted by name, missing items are `None`
+fn merge<T, U>(a: Vec<(String, T)>, b: Vec<(String, U)>) -> Vec<ItemPair<T, U>> {
+    let mut out = Vec::new();
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+    loop {
+        let order = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) => compare_names(&x.0, &y.0),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => break,
+        };
+        out.push(match order {
+            Ordering::Less => (a.next(), None),
+            Ordering::Greater => (None, b.next()),
+            Ordering::Equal => (a.next(), b.next()),
+        });
+    }
+    out
+}
+
+type ItemPair<T, U> = (Option<(String, T)>, Option<(String, U)>);
@@ -4,24 +4,16 @@
 // may not be copied, modified, or distributed
 // except according to those terms.
 use self::EncoderState::*;
+use crate::backend::RegistryBackend;
 use crate::enums::*;
+#[cfg(windows)]
 use crate::reg_key::RegKey;
+#[cfg(windows)]
 use crate::transaction::Transaction;
 use std::error::Error;
 use std::fmt;
 use std::io;
 
-macro_rules! emit_value {
-    ($s:ident, $v:ident) => {
-        match mem::replace(&mut $s.state, Start) {
-            NextKey(ref s) => $s.keys[$s.keys.len() - 1]
-                .set_value(s, &$v)
-                .map_err(EncoderError::IoError),
-            Start => Err(EncoderError::NoFieldName),
-        }
-    };
-}
-
 macro_rules! no_impl {
     ($e:expr) => {
         Err(EncoderError::EncodeNotImplemented($e.to_owned()))
@@ -38,15 +30,75 @@ pub enum EncoderError {
     IoError(io::Error),
     NoFieldName,
     KeyMustBeAString,
+    /// `error` happened in the subkey at `path`, relative to the target key,
+    /// and in the value `value` if it's about one
+    At {
+        path: String,
+        value: Option<String>,
+        error: Box<EncoderError>,
+    },
+}
+
+impl EncoderError {
+    /// Path of the subkey the error happened in, relative to the target key
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            EncoderError::At { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Name of the value the error happened in
+    pub fn value_name(&self) -> Option<&str> {
+        match self {
+            EncoderError::At { value, .. } => value.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The error without its location
+    pub fn inner(&self) -> &EncoderError {
+        match self {
+            EncoderError::At { error, .. } => error,
+            err => err,
+        }
+    }
+}
+
+/// Where an error happened: `key "a\b", value "c"`
+pub(crate) fn fmt_location(f: &mut fmt::Formatter, path: &str, value: Option<&str>) -> fmt::Result {
+    match value {
+        Some(value) if path.is_empty() => write!(f, "value \"{}\"", value),
+        Some(value) => write!(f, "key \"{}\", value \"{}\"", path, value),
+        None => write!(f, "key \"{}\"", path),
+    }
 }
 
 impl fmt::Display for EncoderError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            EncoderError::EncodeNotImplemented(what) => write!(f, "not implemented: {}", what),
+            EncoderError::SerializerError(msg) => f.write_str(msg),
+            EncoderError::IoError(err) => write!(f, "{}", err),
+            EncoderError::NoFieldName => f.write_str("no field name"),
+            EncoderError::KeyMustBeAString => f.write_str("map key must be a string"),
+            EncoderError::At { path, value, error } => {
+                fmt_location(f, path, value.as_deref())?;
+                write!(f, ": {}", error)
+            }
+        }
     }
 }
 
-impl Error for EncoderError {}
+impl Error for EncoderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EncoderError::IoError(err) => Some(err),
+            EncoderError::At { error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
 
 pub type EncodeResult<T> = Result<T, EncoderError>;
 
@@ -56,6 +108,62 @@ impl From<io::Error> for EncoderError {
     }
 }
 
+/// How signed integers are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignedFormat {
+    /// `REG_SZ` with the decimal number
+    String,
+    /// Two's complement `REG_DWORD` for `i8`, `i16` and `i32`, `REG_QWORD` for `i64`
+    TwosComplement,
+}
+
+impl Default for SignedFormat {
+    fn default() -> SignedFormat {
+        SignedFormat::String
+    }
+}
+
+/// How floating point numbers are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// `REG_SZ` with the decimal number
+    String,
+    /// Bit pattern, `REG_DWORD` for `f32` and `REG_QWORD` for `f64`
+    Bits,
+}
+
+impl Default for FloatFormat {
+    fn default() -> FloatFormat {
+        FloatFormat::String
+    }
+}
+
+/// Representation of numbers that have no registry type of their own.
+/// By default they are stored as strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EncoderOptions {
+    signed: SignedFormat,
+    floats: FloatFormat,
+}
+
+impl EncoderOptions {
+    pub fn new() -> EncoderOptions {
+        EncoderOptions::default()
+    }
+
+    /// Format of `i8`, `i16`, `i32` and `i64`
+    pub fn signed(mut self, format: SignedFormat) -> EncoderOptions {
+        self.signed = format;
+        self
+    }
+
+    /// Format of `f32` and `f64`
+    pub fn floats(mut self, format: FloatFormat) -> EncoderOptions {
+        self.floats = format;
+        self
+    }
+}
+
 #[derive(Debug)]
 enum EncoderState {
     Start,
@@ -64,33 +172,96 @@ enum EncoderState {
 }
 
 #[derive(Debug)]
-pub struct Encoder<Tr: AsRef<Transaction>> {
-    keys: Vec<RegKey>,
+pub struct Encoder<K, Tr> {
+    keys: Vec<K>,
     tr: Tr,
     state: EncoderState,
+    options: EncoderOptions,
+    /// Names of the subkeys in `keys` after the first one
+    names: Vec<String>,
 }
 
 const ENCODER_SAM: u32 =
     KEY_CREATE_SUB_KEY | KEY_SET_VALUE | DELETE | KEY_ENUMERATE_SUB_KEYS | KEY_QUERY_VALUE;
 
-impl Encoder<Transaction> {
-    pub fn from_key(key: &RegKey) -> EncodeResult<Encoder<Transaction>> {
-        let tr = Transaction::new()?;
-        key.open_subkey_transacted_with_flags("", &tr, ENCODER_SAM)
-            .map(|k| Encoder::new(k, tr))
-            .map_err(EncoderError::IoError)
+/// How the encoder creates subkeys for nested structures
+pub trait EncoderMode<K> {
+    fn create_subkey(&self, parent: &K, name: &str) -> io::Result<K>;
+}
+
+/// Subkeys are created directly, without a transaction
+#[derive(Debug)]
+pub struct Direct;
+
+impl<K: RegistryBackend> EncoderMode<K> for Direct {
+    fn create_subkey(&self, parent: &K, name: &str) -> io::Result<K> {
+        parent
+            .create_subkey_with_flags(name, ENCODER_SAM)
+            .map(|(key, _disp)| key)
     }
+}
 
-    fn new(key: RegKey, tr: Transaction) -> Encoder<Transaction> {
+#[cfg(windows)]
+impl EncoderMode<RegKey> for Transaction {
+    fn create_subkey(&self, parent: &RegKey, name: &str) -> io::Result<RegKey> {
+        parent
+            .create_subkey_transacted_with_flags(name, self, ENCODER_SAM)
+            .map(|(key, _disp)| key)
+    }
+}
+
+#[cfg(windows)]
+impl EncoderMode<RegKey> for &Transaction {
+    fn create_subkey(&self, parent: &RegKey, name: &str) -> io::Result<RegKey> {
+        EncoderMode::<RegKey>::create_subkey(*self, parent, name)
+    }
+}
+
+impl<K, Tr> Encoder<K, Tr> {
+    fn with_mode(key: K, tr: Tr) -> Encoder<K, Tr> {
         let mut keys = Vec::with_capacity(5);
         keys.push(key);
         Encoder {
             keys,
             tr,
             state: Start,
+            options: EncoderOptions::default(),
+            names: Vec::new(),
         }
     }
 
+    /// Use `options` instead of the default ones
+    pub fn with_options(mut self, options: EncoderOptions) -> Encoder<K, Tr> {
+        self.options = options;
+        self
+    }
+}
+
+impl<K: RegistryBackend> Encoder<K, Direct> {
+    /// Encoder for any `RegistryBackend`, changes are not transacted
+    pub fn from_backend(key: &K) -> EncodeResult<Encoder<K, Direct>> {
+        key.open_subkey_with_flags("", ENCODER_SAM)
+            .map(|k| Encoder::with_mode(k, Direct))
+            .map_err(EncoderError::IoError)
+    }
+
+    /// use this to clear the old key content before starting serialization
+    pub(crate) fn wipe(&self) -> EncodeResult<()> {
+        self.keys[0]
+            .delete_subkey_all("")
+            .map_err(EncoderError::IoError)
+    }
+}
+
+#[cfg(windows)]
+impl Encoder<RegKey, Transaction> {
+    pub fn from_key(key: &RegKey) -> EncodeResult<Encoder<RegKey, Transaction>> {
+        let tr = Transaction::new()?;
+        key.open_subkey_transacted_with_flags("", &tr, ENCODER_SAM)
+            .map(|k| Encoder::with_mode(k, tr))
+            .map_err(EncoderError::IoError)
+    }
+
     /// use this to clear the old key content before starting serialization
     pub(crate) fn wipe(&self) -> EncodeResult<()> {
         // since key is opened with transaction RegDeleteTreeW happens inside this transaction
@@ -104,26 +275,17 @@ impl Encoder<Transaction> {
     }
 }
 
-impl Encoder<&Transaction> {
-    pub fn from_key_transacted<'a>(
+#[cfg(windows)]
+impl<'a> Encoder<RegKey, &'a Transaction> {
+    pub fn from_key_transacted(
         key: &RegKey,
         tr: &'a Transaction,
-    ) -> EncodeResult<Encoder<&'a Transaction>> {
+    ) -> EncodeResult<Encoder<RegKey, &'a Transaction>> {
         key.open_subkey_transacted_with_flags("", tr, ENCODER_SAM)
-            .map(|k| Encoder::new_transacted(k, tr))
+            .map(|k| Encoder::with_mode(k, tr))
             .map_err(EncoderError::IoError)
     }
 
-    fn new_transacted(key: RegKey, tr: &Transaction) -> Encoder<&Transaction> {
-        let mut keys = Vec::with_capacity(5);
-        keys.push(key);
-        Encoder {
-            keys,
-            tr,
-            state: Start,
-        }
-    }
-
     /// use this to clear the old key content before starting serialization
     pub(crate) fn wipe(&self) -> EncodeResult<()> {
         // since key is opened with transaction RegDeleteTreeW happens inside this transaction
@@ -4,12 +4,16 @@
 // may not be copied, modified, or distributed
 // except according to those terms.
 use super::EncoderState::*;
-use super::{EncodeResult, Encoder, EncoderError, ENCODER_SAM};
-use crate::enums::RegType;
-use crate::transaction::Transaction;
+use super::{
+    EncodeResult, Encoder, EncoderError, EncoderMode, EncoderOptions, FloatFormat, SignedFormat,
+};
+use crate::backend::RegistryBackend;
+use crate::serde_with::{raw_from_bytes, RAW_VALUE};
+use crate::value::Value;
 use crate::RegValue;
 use serde::ser::*;
 use std::fmt;
+use std::io;
 use std::mem;
 
 impl Error for EncoderError {
@@ -18,98 +22,76 @@ impl Error for EncoderError {
     }
 }
 
-impl<'a, Tr: AsRef<Transaction>> Serializer for &'a mut Encoder<Tr> {
+impl<'a, K: RegistryBackend, Tr: EncoderMode<K>> Serializer for &'a mut Encoder<K, Tr> {
     type Ok = ();
     type Error = EncoderError;
 
-    type SerializeSeq = SeqEncoder;
-    type SerializeTuple = TupleEncoder;
-    type SerializeTupleStruct = TupleStructEncoder;
-    type SerializeTupleVariant = TupleVariantEncoder;
-    type SerializeMap = StructMapEncoder<'a, Tr>;
-    type SerializeStruct = StructMapEncoder<'a, Tr>;
-    type SerializeStructVariant = StructVariantEncoder;
+    type SerializeSeq = SeqEncoder<'a, K, Tr>;
+    type SerializeTuple = SeqEncoder<'a, K, Tr>;
+    type SerializeTupleStruct = SeqEncoder<'a, K, Tr>;
+    type SerializeTupleVariant = SeqEncoder<'a, K, Tr>;
+    type SerializeMap = StructMapEncoder<'a, K, Tr>;
+    type SerializeStruct = StructMapEncoder<'a, K, Tr>;
+    type SerializeStructVariant = StructMapEncoder<'a, K, Tr>;
 
     fn serialize_bool(self, value: bool) -> EncodeResult<Self::Ok> {
-        self.serialize_u32(value as u32)
+        self.emit(self.scalars().serialize_bool(value)?)
     }
 
     fn serialize_i8(self, value: i8) -> EncodeResult<Self::Ok> {
-        self.serialize_i64(value as i64)
+        self.emit(self.scalars().serialize_i8(value)?)
     }
 
     fn serialize_i16(self, value: i16) -> EncodeResult<Self::Ok> {
-        self.serialize_i64(value as i64)
+        self.emit(self.scalars().serialize_i16(value)?)
     }
 
     fn serialize_i32(self, value: i32) -> EncodeResult<Self::Ok> {
-        self.serialize_i64(value as i64)
+        self.emit(self.scalars().serialize_i32(value)?)
     }
 
     fn serialize_i64(self, value: i64) -> EncodeResult<Self::Ok> {
-        let s = value.to_string();
-        emit_value!(self, s)
+        self.emit(self.scalars().serialize_i64(value)?)
     }
 
     fn serialize_u8(self, value: u8) -> EncodeResult<Self::Ok> {
-        self.serialize_u32(value as u32)
+        self.emit(self.scalars().serialize_u8(value)?)
     }
 
     fn serialize_u16(self, value: u16) -> EncodeResult<Self::Ok> {
-        self.serialize_u32(value as u32)
+        self.emit(self.scalars().serialize_u16(value)?)
     }
 
     fn serialize_u32(self, value: u32) -> EncodeResult<Self::Ok> {
-        emit_value!(self, value)
+        self.emit(self.scalars().serialize_u32(value)?)
     }
 
     fn serialize_u64(self, value: u64) -> EncodeResult<Self::Ok> {
-        emit_value!(self, value)
+        self.emit(self.scalars().serialize_u64(value)?)
     }
 
     fn serialize_f32(self, value: f32) -> EncodeResult<Self::Ok> {
-        let s = value.to_string();
-        emit_value!(self, s)
+        self.emit(self.scalars().serialize_f32(value)?)
     }
 
     fn serialize_f64(self, value: f64) -> EncodeResult<Self::Ok> {
-        let s = value.to_string();
-        emit_value!(self, s)
+        self.emit(self.scalars().serialize_f64(value)?)
     }
 
     fn serialize_char(self, value: char) -> EncodeResult<Self::Ok> {
-        let mut s = String::new();
-        s.push(value);
-        emit_value!(self, s)
+        self.emit(self.scalars().serialize_char(value)?)
     }
 
     fn serialize_str(self, value: &str) -> EncodeResult<Self::Ok> {
-        emit_value!(self, value)
+        self.emit(self.scalars().serialize_str(value)?)
     }
 
     fn serialize_bytes(self, value: &[u8]) -> EncodeResult<Self::Ok> {
-        match mem::replace(&mut self.state, Start) {
-            NextKey(ref s) => {
-                let vec = Vec::from(value);
-                self.keys[self.keys.len() - 1]
-                    .set_raw_value(
-                        s,
-                        &RegValue {
-                            bytes: vec.into(),
-                            vtype: RegType::REG_BINARY,
-                        },
-                    )
-                    .map_err(EncoderError::IoError)
-            }
-            Start => Err(EncoderError::NoFieldName),
-        }
+        self.emit(self.scalars().serialize_bytes(value)?)
     }
 
     fn serialize_none(self) -> EncodeResult<Self::Ok> {
-        match mem::replace(&mut self.state, Start) {
-            NextKey(..) => Ok(()),
-            Start => Err(EncoderError::NoFieldName),
-        }
+        self.emit(Scalar::None)
     }
 
     fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> EncodeResult<Self::Ok> {
@@ -126,55 +108,91 @@ impl<'a, Tr: AsRef<Transaction>> Serializer for &'a mut Encoder<Tr> {
 
     fn serialize_unit_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
     ) -> EncodeResult<Self::Ok> {
-        no_impl!("serialize_unit_variant")
+        // drop the subkey of a data-carrying variant
+        if let NextKey(ref field) = self.state {
+            self.remove_subkey(field)?;
+        }
+        self.emit(
+            self.scalars()
+                .serialize_unit_variant(name, variant_index, variant)?,
+        )
     }
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
         self,
-        _name: &'static str,
-        _value: &T,
+        name: &'static str,
+        value: &T,
     ) -> EncodeResult<Self::Ok> {
-        no_impl!("serialize_newtype_struct")
+        if name != RAW_VALUE {
+            return no_impl!("serialize_newtype_struct");
+        }
+        self.emit(self.scalars().serialize_newtype_struct(name, value)?)
     }
 
     fn serialize_newtype_variant<T: ?Sized + Serialize>(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
+        variant: &'static str,
+        value: &T,
     ) -> EncodeResult<Self::Ok> {
-        no_impl!("serialize_newtype_variant")
+        let outer = self.open_variant()?;
+        self.state = NextKey(variant.to_owned());
+        value
+            .serialize(&mut *self)
+            .map_err(|err| self.at(variant, err))?;
+        if outer {
+            self.pop_subkey();
+        }
+        Ok(())
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> EncodeResult<Self::SerializeSeq> {
-        no_impl!("serialize_seq")
+        let target = match mem::replace(&mut self.state, Start) {
+            // the key itself holds the elements
+            Start => SeqTarget::Key { pop: false },
+            NextKey(name) => SeqTarget::Value(name),
+        };
+        Ok(SeqEncoder {
+            enc: self,
+            target,
+            pending: Vec::new(),
+            index: 0,
+            outer: false,
+        })
     }
 
-    fn serialize_tuple(self, _len: usize) -> EncodeResult<Self::SerializeTuple> {
-        no_impl!("serialize_tuple")
+    fn serialize_tuple(self, len: usize) -> EncodeResult<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
     }
 
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> EncodeResult<Self::SerializeTupleStruct> {
-        no_impl!("serialize_tuple_struct")
+        self.serialize_seq(Some(len))
     }
 
     fn serialize_tuple_variant(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> EncodeResult<Self::SerializeTupleVariant> {
-        no_impl!("serialize_tuple_variant")
+        let outer = self.open_variant()?;
+        Ok(SeqEncoder {
+            enc: self,
+            target: SeqTarget::Value(variant.to_owned()),
+            pending: Vec::new(),
+            index: 0,
+            outer,
+        })
     }
 
     fn serialize_map(self, _len: Option<usize>) -> EncodeResult<Self::SerializeMap> {
@@ -185,24 +203,17 @@ impl<'a, Tr: AsRef<Transaction>> Serializer for &'a mut Encoder<Tr> {
                 Ok(StructMapEncoder {
                     enc: self,
                     is_root: true,
+                    outer: false,
                 })
             }
             NextKey(ref s) => {
                 // nested structure
-                match self.keys[self.keys.len() - 1].create_subkey_transacted_with_flags(
-                    s,
-                    self.tr.as_ref(),
-                    ENCODER_SAM,
-                ) {
-                    Ok((subkey, _disp)) => {
-                        self.keys.push(subkey);
-                        Ok(StructMapEncoder {
-                            enc: self,
-                            is_root: true,
-                        })
-                    }
-                    Err(err) => Err(EncoderError::IoError(err)),
-                }
+                self.push_subkey(s)?;
+                Ok(StructMapEncoder {
+                    enc: self,
+                    is_root: true,
+                    outer: false,
+                })
             }
         }
     }
@@ -219,68 +230,498 @@ impl<'a, Tr: AsRef<Transaction>> Serializer for &'a mut Encoder<Tr> {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> EncodeResult<Self::SerializeStructVariant> {
-        no_impl!("serialize_struct_variant")
+        let outer = self.open_variant()?;
+        self.push_subkey(variant)?;
+        Ok(StructMapEncoder {
+            enc: self,
+            is_root: true,
+            outer,
+        })
     }
 }
 
-pub struct SeqEncoder {}
+impl<K: RegistryBackend, Tr> Encoder<K, Tr> {
+    fn scalars(&self) -> ScalarSerializer {
+        ScalarSerializer(self.options)
+    }
 
-impl SerializeSeq for SeqEncoder {
-    type Ok = ();
+    fn emit(&mut self, scalar: Scalar) -> EncodeResult<()> {
+        match mem::replace(&mut self.state, Start) {
+            NextKey(ref name) => self.write(name, scalar),
+            Start => Err(EncoderError::NoFieldName),
+        }
+    }
+
+    /// Data-carrying enum variants are stored in a subkey named after the field,
+    /// as a value or subkey named after the variant. The subkey replaces a unit
+    /// variant value and is cleared first, so it only holds the current variant.
+    /// At the root the current key is used. Returns `true` if the subkey was pushed.
+    fn open_variant(&mut self) -> EncodeResult<bool>
+    where
+        Tr: EncoderMode<K>,
+    {
+        match mem::replace(&mut self.state, Start) {
+            Start => Ok(false),
+            NextKey(ref name) => {
+                self.push_empty_subkey(name)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Replace the value or subkey `name` of the last key with an empty subkey
+    /// and make it the last one
+    fn push_empty_subkey(&mut self, name: &str) -> EncodeResult<()>
+    where
+        Tr: EncoderMode<K>,
+    {
+        self.remove_value(name)?;
+        self.push_subkey(name)?;
+        self.keys[self.keys.len() - 1].delete_subkey_all("")?;
+        Ok(())
+    }
+
+    /// Delete the value `name` of the last key if it exists
+    fn remove_value(&self, name: &str) -> EncodeResult<()> {
+        ignore_not_found(self.keys[self.keys.len() - 1].delete_value(name))
+    }
+
+    /// Delete the subkey `name` of the last key with all its content if it exists
+    fn remove_subkey(&self, name: &str) -> EncodeResult<()> {
+        ignore_not_found(self.keys[self.keys.len() - 1].delete_subkey_all(name))
+    }
+
+    /// Create the subkey `name` of the last key and make it the last one
+    fn push_subkey(&mut self, name: &str) -> EncodeResult<()>
+    where
+        Tr: EncoderMode<K>,
+    {
+        let subkey = self
+            .tr
+            .create_subkey(&self.keys[self.keys.len() - 1], name)?;
+        self.keys.push(subkey);
+        self.names.push(name.to_owned());
+        Ok(())
+    }
+
+    fn pop_subkey(&mut self) {
+        self.keys.pop();
+        self.names.pop();
+    }
+
+    /// Add the location of the value `name` in the last key to `err` unless it has one
+    fn at(&self, name: &str, err: EncoderError) -> EncoderError {
+        match err {
+            EncoderError::At { .. } => err,
+            err => EncoderError::At {
+                path: self.names.join("\\"),
+                value: Some(name.to_owned()),
+                error: Box::new(err),
+            },
+        }
+    }
+
+    fn write(&self, name: &str, scalar: Scalar) -> EncodeResult<()> {
+        let value = match scalar {
+            Scalar::Str(s) => RegValue::from(Value::Sz(s)),
+            Scalar::Value(v) => RegValue::from(v),
+            Scalar::Raw(v) => v,
+            Scalar::None | Scalar::Complex => return Ok(()),
+        };
+        self.keys[self.keys.len() - 1]
+            .set_raw_value(name, &value)
+            .map_err(EncoderError::IoError)
+    }
+}
+
+fn ignore_not_found(res: io::Result<()>) -> EncodeResult<()> {
+    match res {
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        res => res.map_err(EncoderError::IoError),
+    }
+}
+
+/// Scalar form of a value, written as a single registry value
+enum Scalar {
+    /// Strings are kept apart so sequences of them can become `REG_MULTI_SZ`
+    Str(String),
+    Value(Value),
+    /// Value of the exact type from `serde_with`
+    Raw(RegValue<'static>),
+    None,
+    /// Sequences, maps and structures, they need a subkey
+    Complex,
+}
+
+/// Converts scalars to registry values without writing them
+struct ScalarSerializer(EncoderOptions);
+
+impl Serializer for ScalarSerializer {
+    type Ok = Scalar;
     type Error = EncoderError;
-    fn serialize_element<T: ?Sized + Serialize>(&mut self, _value: &T) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeSeq::serialize_element")
+
+    type SerializeSeq = Complex;
+    type SerializeTuple = Complex;
+    type SerializeTupleStruct = Complex;
+    type SerializeTupleVariant = Complex;
+    type SerializeMap = Complex;
+    type SerializeStruct = Complex;
+    type SerializeStructVariant = Complex;
+
+    fn serialize_bool(self, value: bool) -> EncodeResult<Scalar> {
+        self.serialize_u32(value as u32)
     }
-    fn end(self) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeSeq::end")
+
+    fn serialize_i8(self, value: i8) -> EncodeResult<Scalar> {
+        self.serialize_i32(value as i32)
+    }
+
+    fn serialize_i16(self, value: i16) -> EncodeResult<Scalar> {
+        self.serialize_i32(value as i32)
+    }
+
+    fn serialize_i32(self, value: i32) -> EncodeResult<Scalar> {
+        Ok(Scalar::Value(match self.0.signed {
+            SignedFormat::String => Value::Sz(value.to_string()),
+            SignedFormat::TwosComplement => Value::Dword(value as u32),
+        }))
+    }
+
+    fn serialize_i64(self, value: i64) -> EncodeResult<Scalar> {
+        Ok(Scalar::Value(match self.0.signed {
+            SignedFormat::String => Value::Sz(value.to_string()),
+            SignedFormat::TwosComplement => Value::Qword(value as u64),
+        }))
+    }
+
+    fn serialize_u8(self, value: u8) -> EncodeResult<Scalar> {
+        self.serialize_u32(value as u32)
+    }
+
+    fn serialize_u16(self, value: u16) -> EncodeResult<Scalar> {
+        self.serialize_u32(value as u32)
+    }
+
+    fn serialize_u32(self, value: u32) -> EncodeResult<Scalar> {
+        Ok(Scalar::Value(Value::Dword(value)))
+    }
+
+    fn serialize_u64(self, value: u64) -> EncodeResult<Scalar> {
+        Ok(Scalar::Value(Value::Qword(value)))
+    }
+
+    fn serialize_f32(self, value: f32) -> EncodeResult<Scalar> {
+        Ok(Scalar::Value(match self.0.floats {
+            FloatFormat::String => Value::Sz(value.to_string()),
+            FloatFormat::Bits => Value::Dword(value.to_bits()),
+        }))
+    }
+
+    fn serialize_f64(self, value: f64) -> EncodeResult<Scalar> {
+        Ok(Scalar::Value(match self.0.floats {
+            FloatFormat::String => Value::Sz(value.to_string()),
+            FloatFormat::Bits => Value::Qword(value.to_bits()),
+        }))
+    }
+
+    fn serialize_char(self, value: char) -> EncodeResult<Scalar> {
+        Ok(Scalar::Str(value.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> EncodeResult<Scalar> {
+        Ok(Scalar::Str(value.to_owned()))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> EncodeResult<Scalar> {
+        Ok(Scalar::Value(Value::Binary(value.to_vec())))
+    }
+
+    fn serialize_none(self) -> EncodeResult<Scalar> {
+        Ok(Scalar::None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> EncodeResult<Scalar> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> EncodeResult<Scalar> {
+        Ok(Scalar::Complex)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> EncodeResult<Scalar> {
+        Ok(Scalar::Complex)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> EncodeResult<Scalar> {
+        Ok(Scalar::Str(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> EncodeResult<Scalar> {
+        if name != RAW_VALUE {
+            return Ok(Scalar::Complex);
+        }
+        match value.serialize(self)? {
+            Scalar::Value(Value::Binary(raw)) => raw_from_bytes(raw).map(Scalar::Raw),
+            _ => None,
+        }
+        .ok_or_else(|| EncoderError::SerializerError("Invalid raw value".to_owned()))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> EncodeResult<Scalar> {
+        Ok(Scalar::Complex)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> EncodeResult<Complex> {
+        Ok(Complex)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> EncodeResult<Complex> {
+        Ok(Complex)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> EncodeResult<Complex> {
+        Ok(Complex)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> EncodeResult<Complex> {
+        Ok(Complex)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> EncodeResult<Complex> {
+        Ok(Complex)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> EncodeResult<Complex> {
+        Ok(Complex)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> EncodeResult<Complex> {
+        Ok(Complex)
     }
 }
 
-pub struct TupleEncoder {}
+/// Skips the content of a complex value, only its kind matters
+struct Complex;
+
+macro_rules! complex_impl {
+    ($($tr:ident::$f:ident($($arg:ident: $t:ty),*);)*) => {
+        $(
+            impl $tr for Complex {
+                type Ok = Scalar;
+                type Error = EncoderError;
 
-impl SerializeTuple for TupleEncoder {
+                fn $f<T: ?Sized + Serialize>(&mut self, $($arg: $t,)* _value: &T) -> EncodeResult<()> {
+                    Ok(())
+                }
+
+                fn end(self) -> EncodeResult<Scalar> {
+                    Ok(Scalar::Complex)
+                }
+            }
+        )*
+    };
+}
+
+complex_impl! {
+    SerializeSeq::serialize_element();
+    SerializeTuple::serialize_element();
+    SerializeTupleStruct::serialize_field();
+    SerializeTupleVariant::serialize_field();
+    SerializeStruct::serialize_field(_key: &'static str);
+    SerializeStructVariant::serialize_field(_key: &'static str);
+}
+
+impl SerializeMap for Complex {
+    type Ok = Scalar;
+    type Error = EncoderError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, _key: &T) -> EncodeResult<()> {
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, _value: &T) -> EncodeResult<()> {
+        Ok(())
+    }
+
+    fn end(self) -> EncodeResult<Scalar> {
+        Ok(Scalar::Complex)
+    }
+}
+
+/// Where the elements of a sequence go
+enum SeqTarget {
+    /// Value or subkey with this name, not decided yet
+    Value(String),
+    /// Numbered values and subkeys of the last key, `pop` it at the end
+    Key { pop: bool },
+}
+
+/// Sequences of strings are stored as a `REG_MULTI_SZ` value.
+/// Other sequences become a subkey with values and subkeys named by the element index:
+/// `0`, `1`, ... Scalars are stored as values the same way as structure fields,
+/// structures, maps and nested sequences as subkeys. `None` elements are skipped.
+/// Sequences of strings that contain an empty string use the subkey layout too,
+/// since `REG_MULTI_SZ` can't hold empty strings.
+pub struct SeqEncoder<'a, K, Tr> {
+    enc: &'a mut Encoder<K, Tr>,
+    target: SeqTarget,
+    /// Scalar elements waiting for the layout to be decided
+    pending: Vec<Scalar>,
+    index: u32,
+    /// Pop the subkey of an enum variant at the end too
+    outer: bool,
+}
+
+impl<K: RegistryBackend, Tr: EncoderMode<K>> SeqEncoder<'_, K, Tr> {
+    /// Switch to the subkey layout
+    fn open(&mut self) -> EncodeResult<()> {
+        if let SeqTarget::Value(ref name) = self.target {
+            // drop the `REG_MULTI_SZ` value or elements left from a longer sequence
+            self.enc.push_empty_subkey(name)?;
+            self.target = SeqTarget::Key { pop: true };
+            for (index, scalar) in mem::take(&mut self.pending).into_iter().enumerate() {
+                let name = index.to_string();
+                self.enc
+                    .write(&name, scalar)
+                    .map_err(|err| self.enc.at(&name, err))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> EncodeResult<()> {
+        let name = self.index.to_string();
+        self.index += 1;
+        self.write_element(&name, value)
+            .map_err(|err| self.enc.at(&name, err))
+    }
+
+    fn write_element<T: ?Sized + Serialize>(&mut self, name: &str, value: &T) -> EncodeResult<()> {
+        match value.serialize(self.enc.scalars())? {
+            Scalar::Complex => {
+                self.open()?;
+                self.enc.state = NextKey(name.to_owned());
+                value.serialize(&mut *self.enc)
+            }
+            scalar => match self.target {
+                SeqTarget::Value(_) => {
+                    self.pending.push(scalar);
+                    Ok(())
+                }
+                SeqTarget::Key { .. } => self.enc.write(name, scalar),
+            },
+        }
+    }
+
+    fn finish(mut self) -> EncodeResult<()> {
+        if let SeqTarget::Value(ref name) = self.target {
+            let mut strings = Vec::with_capacity(self.pending.len());
+            for scalar in &self.pending {
+                match scalar {
+                    Scalar::Str(s) if !s.is_empty() => strings.push(s.clone()),
+                    _ => break,
+                }
+            }
+            if strings.len() == self.pending.len() {
+                // drop the subkey if the sequence used the other layout before
+                self.enc.remove_subkey(name)?;
+                let value: RegValue = Value::MultiSz(strings).into();
+                let key = &self.enc.keys[self.enc.keys.len() - 1];
+                key.set_raw_value(name, &value)
+                    .map_err(EncoderError::IoError)?;
+            } else {
+                self.open()?;
+            }
+        }
+        if let SeqTarget::Key { pop: true } = self.target {
+            self.enc.pop_subkey();
+        }
+        if self.outer {
+            self.enc.pop_subkey();
+        }
+        Ok(())
+    }
+}
+
+impl<K: RegistryBackend, Tr: EncoderMode<K>> SerializeSeq for SeqEncoder<'_, K, Tr> {
     type Ok = ();
     type Error = EncoderError;
 
-    fn serialize_element<T: ?Sized + Serialize>(&mut self, _value: &T) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeTuple::serialize_element")
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> EncodeResult<Self::Ok> {
+        self.element(value)
     }
 
     fn end(self) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeTuple::end")
+        self.finish()
     }
 }
 
-pub struct TupleStructEncoder {}
-
-impl SerializeTupleStruct for TupleStructEncoder {
+impl<K: RegistryBackend, Tr: EncoderMode<K>> SerializeTuple for SeqEncoder<'_, K, Tr> {
     type Ok = ();
     type Error = EncoderError;
 
-    fn serialize_field<T: ?Sized + Serialize>(&mut self, _value: &T) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeTupleStruct::serialize_field")
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> EncodeResult<Self::Ok> {
+        self.element(value)
     }
 
     fn end(self) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeTupleStruct::end")
+        self.finish()
     }
 }
 
-pub struct TupleVariantEncoder {}
+impl<K: RegistryBackend, Tr: EncoderMode<K>> SerializeTupleStruct for SeqEncoder<'_, K, Tr> {
+    type Ok = ();
+    type Error = EncoderError;
 
-impl SerializeTupleVariant for TupleVariantEncoder {
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> EncodeResult<Self::Ok> {
+        self.element(value)
+    }
+
+    fn end(self) -> EncodeResult<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl<K: RegistryBackend, Tr: EncoderMode<K>> SerializeTupleVariant for SeqEncoder<'_, K, Tr> {
     type Ok = ();
     type Error = EncoderError;
 
-    fn serialize_field<T: ?Sized + Serialize>(&mut self, _value: &T) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeTupleVariant::serialize_field")
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> EncodeResult<Self::Ok> {
+        self.element(value)
     }
 
     fn end(self) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeTupleVariant::end")
+        self.finish()
     }
 }
 
@@ -466,12 +907,26 @@ impl serde::Serializer for MapKeySerializer {
     }
 }
 
-pub struct StructMapEncoder<'a, Tr: AsRef<Transaction>> {
-    enc: &'a mut Encoder<Tr>,
+pub struct StructMapEncoder<'a, K, Tr> {
+    enc: &'a mut Encoder<K, Tr>,
     is_root: bool,
+    /// Pop the subkey of an enum variant at the end too
+    outer: bool,
+}
+
+impl<K: RegistryBackend, Tr> StructMapEncoder<'_, K, Tr> {
+    fn finish(self) -> EncodeResult<()> {
+        if self.is_root {
+            self.enc.pop_subkey();
+        }
+        if self.outer {
+            self.enc.pop_subkey();
+        }
+        Ok(())
+    }
 }
 
-impl<Tr: AsRef<Transaction>> SerializeStruct for StructMapEncoder<'_, Tr> {
+impl<K: RegistryBackend, Tr: EncoderMode<K>> SerializeStruct for StructMapEncoder<'_, K, Tr> {
     type Ok = ();
     type Error = EncoderError;
 
@@ -481,18 +936,17 @@ impl<Tr: AsRef<Transaction>> SerializeStruct for StructMapEncoder<'_, Tr> {
         value: &T,
     ) -> EncodeResult<Self::Ok> {
         self.enc.state = NextKey(String::from(key));
-        value.serialize(&mut *self.enc)
+        value
+            .serialize(&mut *self.enc)
+            .map_err(|err| self.enc.at(key, err))
     }
 
     fn end(self) -> EncodeResult<Self::Ok> {
-        if self.is_root {
-            self.enc.keys.pop();
-        }
-        Ok(())
+        self.finish()
     }
 }
 
-impl<Tr: AsRef<Transaction>> SerializeMap for StructMapEncoder<'_, Tr> {
+impl<K: RegistryBackend, Tr: EncoderMode<K>> SerializeMap for StructMapEncoder<'_, K, Tr> {
     type Ok = ();
     type Error = EncoderError;
 
@@ -502,32 +956,35 @@ impl<Tr: AsRef<Transaction>> SerializeMap for StructMapEncoder<'_, Tr> {
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> EncodeResult<Self::Ok> {
-        value.serialize(&mut *self.enc)
+        let name = match self.enc.state {
+            NextKey(ref name) => name.clone(),
+            Start => String::new(),
+        };
+        value
+            .serialize(&mut *self.enc)
+            .map_err(|err| self.enc.at(&name, err))
     }
 
     fn end(self) -> EncodeResult<Self::Ok> {
-        if self.is_root {
-            self.enc.keys.pop();
-        }
-        Ok(())
+        self.finish()
     }
 }
 
-pub struct StructVariantEncoder {}
-
-impl SerializeStructVariant for StructVariantEncoder {
+impl<K: RegistryBackend, Tr: EncoderMode<K>> SerializeStructVariant
+    for StructMapEncoder<'_, K, Tr>
+{
     type Ok = ();
     type Error = EncoderError;
 
     fn serialize_field<T: ?Sized + Serialize>(
         &mut self,
-        _key: &'static str,
-        _value: &T,
+        key: &'static str,
+        value: &T,
     ) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeStructVariant::serialize_field")
+        SerializeStruct::serialize_field(self, key, value)
     }
 
     fn end(self) -> EncodeResult<Self::Ok> {
-        no_impl!("SerializeStructVariant::end")
+        self.finish()
     }
 }
@@ -3,17 +3,17 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
-use crate::RegKey;
+use crate::backend::RegistryEnum;
 use std::io;
 use windows_sys::Win32::Foundation;
 
 /// Iterator over subkeys names
-pub struct EnumKeys<'key> {
-    pub(crate) key: &'key RegKey,
+pub struct EnumKeys<'key, K> {
+    pub(crate) key: &'key K,
     pub(crate) index: u32,
 }
 
-impl Iterator for EnumKeys<'_> {
+impl<K: RegistryEnum> Iterator for EnumKeys<'_, K> {
     type Item = io::Result<String>;
 
     fn next(&mut self) -> Option<io::Result<String>> {
@@ -3,16 +3,16 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
-use crate::RegKey;
+use crate::backend::RegistryEnum;
 use std::{ffi::OsString, io};
 
 /// Iterator over subkeys names that uses `OsString`
-pub struct EnumKeysOsString<'key> {
-    pub(crate) key: &'key RegKey,
+pub struct EnumKeysOsString<'key, K> {
+    pub(crate) key: &'key K,
     pub(crate) index: u32,
 }
 
-impl Iterator for EnumKeysOsString<'_> {
+impl<K: RegistryEnum> Iterator for EnumKeysOsString<'_, K> {
     type Item = io::Result<OsString>;
 
     fn next(&mut self) -> Option<io::Result<OsString>> {
@@ -3,17 +3,18 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
-use crate::{RegKey, RegValue};
+use crate::backend::RegistryEnum;
+use crate::RegValue;
 use std::io;
 use windows_sys::Win32::Foundation;
 
 /// Iterator over values
-pub struct EnumValues<'key> {
-    pub(crate) key: &'key RegKey,
+pub struct EnumValues<'key, K> {
+    pub(crate) key: &'key K,
     pub(crate) index: u32,
 }
 
-impl<'a> Iterator for EnumValues<'a> {
+impl<K: RegistryEnum> Iterator for EnumValues<'_, K> {
     type Item = io::Result<(String, RegValue<'static>)>;
 
     fn next(&mut self) -> Option<io::Result<(String, RegValue<'static>)>> {
@@ -3,16 +3,17 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
-use crate::{RegKey, RegValue};
+use crate::backend::RegistryEnum;
+use crate::RegValue;
 use std::{ffi::OsString, io};
 
 /// Iterator over values that uses `OsString` for names
-pub struct EnumValuesOsString<'key> {
-    pub(crate) key: &'key RegKey,
+pub struct EnumValuesOsString<'key, K> {
+    pub(crate) key: &'key K,
     pub(crate) index: u32,
 }
 
-impl<'a> Iterator for EnumValuesOsString<'a> {
+impl<K: RegistryEnum> Iterator for EnumValuesOsString<'_, K> {
     type Item = io::Result<(OsString, RegValue<'static>)>;
 
     fn next(&mut self) -> Option<io::Result<(OsString, RegValue<'static>)>> {
@@ -27,22 +27,80 @@ macro_rules! winapi_enum{
     )
 }
 
-winapi_enum!(RegType, "Enumeration of possible registry value types" => [
-REG_NONE,
-REG_SZ,
-REG_EXPAND_SZ,
-REG_BINARY,
-REG_DWORD,
-REG_DWORD_BIG_ENDIAN,
-REG_LINK,
-REG_MULTI_SZ,
-REG_RESOURCE_LIST,
-REG_FULL_RESOURCE_DESCRIPTOR,
-REG_RESOURCE_REQUIREMENTS_LIST,
-REG_QWORD
-]);
+/// Enumeration of possible registry value types
+///
+/// Types without a `REG_*` name, e.g. the `0xFFFF00xx` types of device properties,
+/// are kept as `Other`. `Other` must not hold a type that has a name,
+/// convert raw types with `RegType::from_raw()` to get that right.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegType {
+    REG_NONE,
+    REG_SZ,
+    REG_EXPAND_SZ,
+    REG_BINARY,
+    REG_DWORD,
+    REG_DWORD_BIG_ENDIAN,
+    REG_LINK,
+    REG_MULTI_SZ,
+    REG_RESOURCE_LIST,
+    REG_FULL_RESOURCE_DESCRIPTOR,
+    REG_RESOURCE_REQUIREMENTS_LIST,
+    REG_QWORD,
+    /// Any other raw value type
+    Other(u32),
+}
 pub use self::RegType::*;
 
+impl RegType {
+    /// Convert a raw `REG_*` value type
+    ///
+    /// ```
+    /// use winreg::enums::*;
+    ///
+    /// assert_eq!(RegType::from_raw(4), REG_DWORD);
+    /// assert_eq!(RegType::from_raw(0xFFFF_0012).raw(), 0xFFFF_0012);
+    /// ```
+    pub fn from_raw(t: u32) -> RegType {
+        use windows_sys::Win32::System::Registry as R;
+        match t {
+            R::REG_NONE => REG_NONE,
+            R::REG_SZ => REG_SZ,
+            R::REG_EXPAND_SZ => REG_EXPAND_SZ,
+            R::REG_BINARY => REG_BINARY,
+            R::REG_DWORD => REG_DWORD,
+            R::REG_DWORD_BIG_ENDIAN => REG_DWORD_BIG_ENDIAN,
+            R::REG_LINK => REG_LINK,
+            R::REG_MULTI_SZ => REG_MULTI_SZ,
+            R::REG_RESOURCE_LIST => REG_RESOURCE_LIST,
+            R::REG_FULL_RESOURCE_DESCRIPTOR => REG_FULL_RESOURCE_DESCRIPTOR,
+            R::REG_RESOURCE_REQUIREMENTS_LIST => REG_RESOURCE_REQUIREMENTS_LIST,
+            R::REG_QWORD => REG_QWORD,
+            t => Other(t),
+        }
+    }
+
+    /// Raw `REG_*` value type as it is stored in the registry
+    pub fn raw(&self) -> u32 {
+        use windows_sys::Win32::System::Registry as R;
+        match self {
+            REG_NONE => R::REG_NONE,
+            REG_SZ => R::REG_SZ,
+            REG_EXPAND_SZ => R::REG_EXPAND_SZ,
+            REG_BINARY => R::REG_BINARY,
+            REG_DWORD => R::REG_DWORD,
+            REG_DWORD_BIG_ENDIAN => R::REG_DWORD_BIG_ENDIAN,
+            REG_LINK => R::REG_LINK,
+            REG_MULTI_SZ => R::REG_MULTI_SZ,
+            REG_RESOURCE_LIST => R::REG_RESOURCE_LIST,
+            REG_FULL_RESOURCE_DESCRIPTOR => R::REG_FULL_RESOURCE_DESCRIPTOR,
+            REG_RESOURCE_REQUIREMENTS_LIST => R::REG_RESOURCE_REQUIREMENTS_LIST,
+            REG_QWORD => R::REG_QWORD,
+            Other(t) => *t,
+        }
+    }
+}
+
 winapi_enum!(RegDisposition, "Enumeration of possible disposition values" => [
 REG_CREATED_NEW_KEY,
 REG_OPENED_EXISTING_KEY
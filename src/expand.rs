@@ -0,0 +1,197 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Expansion of `%VAR%` references in `REG_EXPAND_SZ` values
+//! the way `ExpandEnvironmentStrings` does it, in pure Rust.
+//!
+//! Variables come from an `EnvSource`: the environment of the current process (`ProcessEnv`),
+//! a `HashMap` or the `Environment` keys of the registry (`RegistryEnv`).
+//! Variable names are not case sensitive. References to unknown variables,
+//! empty references (`%%`) and an unterminated `%` are left as they are.
+//!
+//!```
+//!use std::collections::HashMap;
+//!use winreg::expand::expand;
+//!
+//!let mut env = HashMap::new();
+//!env.insert("SystemRoot".to_owned(), "C:\\Windows".to_owned());
+//!assert_eq!(expand("%systemroot%\\System32", &env), "C:\\Windows\\System32");
+//!assert_eq!(expand("%Missing%\\50%", &env), "%Missing%\\50%");
+//!```
+use crate::backend::RegistryBackend;
+use crate::common::*;
+use crate::enums::*;
+#[cfg(windows)]
+use crate::reg_key::{RegKey, HKCU, HKLM};
+use crate::reg_value::RegValue;
+use crate::types::FromRegValue;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::env;
+use std::io;
+use windows_sys::Win32::Foundation;
+
+/// Source of environment variables for `expand`
+pub trait EnvSource {
+    /// Value of the variable `name`, compared ignoring case
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+impl<T: EnvSource + ?Sized> EnvSource for &T {
+    fn var(&self, name: &str) -> Option<String> {
+        (**self).var(name)
+    }
+}
+
+/// Environment of the current process
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn var(&self, name: &str) -> Option<String> {
+        if let Ok(value) = env::var(name) {
+            return Some(value);
+        }
+        env::vars_os()
+            .find(|(n, _)| n.to_str().map_or(false, |n| names_eq(n, name)))
+            .and_then(|(_, value)| value.into_string().ok())
+    }
+}
+
+impl<S: std::hash::BuildHasher> EnvSource for HashMap<String, String, S> {
+    fn var(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.get(name) {
+            return Some(value.clone());
+        }
+        self.iter()
+            .find(|(n, _)| names_eq(n, name))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// Variables stored as values of registry keys, e.g. `HKCU\Environment`
+/// and `HKLM\SYSTEM\CurrentControlSet\Control\Session Manager\Environment`.
+/// Keys added first take precedence. `REG_EXPAND_SZ` variables are expanded
+/// with the same keys, one level deep.
+#[derive(Debug, Clone)]
+pub struct RegistryEnv<K> {
+    keys: Vec<K>,
+}
+
+impl<K: RegistryBackend> RegistryEnv<K> {
+    pub fn new() -> RegistryEnv<K> {
+        RegistryEnv { keys: Vec::new() }
+    }
+
+    /// Add a key to look variables up in
+    pub fn key(mut self, key: K) -> RegistryEnv<K> {
+        self.keys.push(key);
+        self
+    }
+
+    fn lookup(&self, name: &str, nested: bool) -> Option<String> {
+        for key in &self.keys {
+            let value = match key.get_raw_value(name) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            return match Value::try_from(&value).ok()? {
+                Value::Sz(s) => Some(s),
+                Value::ExpandSz(s) if nested => Some(s),
+                Value::ExpandSz(s) => Some(expand(&s, Nested(self))),
+                _ => None,
+            };
+        }
+        None
+    }
+}
+
+impl<K: RegistryBackend> Default for RegistryEnv<K> {
+    fn default() -> RegistryEnv<K> {
+        RegistryEnv::new()
+    }
+}
+
+#[cfg(windows)]
+impl RegistryEnv<RegKey> {
+    /// Environment of the current user followed by the system environment,
+    /// the variables a new process of the user starts with.
+    /// Keys that don't exist are skipped.
+    pub fn system() -> io::Result<RegistryEnv<RegKey>> {
+        let mut env = RegistryEnv::new();
+        let paths = [
+            (HKCU, "Environment"),
+            (
+                HKLM,
+                "SYSTEM\\CurrentControlSet\\Control\\Session Manager\\Environment",
+            ),
+        ];
+        for (root, path) in paths.iter() {
+            match root.open_subkey_with_flags(path, KEY_READ) {
+                Ok(key) => env.keys.push(key),
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(env)
+    }
+}
+
+impl<K: RegistryBackend> EnvSource for RegistryEnv<K> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.lookup(name, false)
+    }
+}
+
+/// Lookups while expanding a variable of `RegistryEnv`
+struct Nested<'a, K>(&'a RegistryEnv<K>);
+
+impl<K: RegistryBackend> EnvSource for Nested<'_, K> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.0.lookup(name, true)
+    }
+}
+
+/// String data of a `REG_SZ` or `REG_EXPAND_SZ` value, expanded if it's `REG_EXPAND_SZ`.
+/// Other types fail with `ERROR_BAD_FILE_TYPE`.
+pub fn expand_value<E: EnvSource>(value: &RegValue, env: E) -> io::Result<String> {
+    let expandable = match value.vtype {
+        REG_SZ => false,
+        REG_EXPAND_SZ => true,
+        _ => return werr!(Foundation::ERROR_BAD_FILE_TYPE),
+    };
+    let s = String::from_reg_value(value)?;
+    Ok(if expandable { expand(&s, env) } else { s })
+}
+
+/// Replace `%VAR%` references in `s` with the values of the variables
+pub fn expand<E: EnvSource>(s: &str, env: E) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = match after.find('%') {
+            Some(end) => end,
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+        let name = &after[..end];
+        match Some(name)
+            .filter(|n| !n.is_empty())
+            .and_then(|n| env.var(n))
+        {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,313 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Low-level layout of the cells stored in hive bins
+use crate::common::*;
+use std::io;
+use windows_sys::Win32::Foundation;
+
+pub(crate) const BASE_BLOCK_SIZE: usize = 4096;
+pub(crate) const HBIN_ALIGNMENT: usize = 4096;
+pub(crate) const HBIN_HEADER_SIZE: usize = 32;
+pub(crate) const CELL_ALIGNMENT: usize = 8;
+pub(crate) const NO_CELL: u32 = 0xFFFF_FFFF;
+
+pub(crate) const KEY_HIVE_ENTRY: u16 = 0x0004;
+pub(crate) const KEY_NO_DELETE: u16 = 0x0008;
+pub(crate) const KEY_COMP_NAME: u16 = 0x0020;
+pub(crate) const VALUE_COMP_NAME: u16 = 0x0001;
+
+pub(crate) const DATA_IS_RESIDENT: u32 = 0x8000_0000;
+pub(crate) const BIG_DATA_SEGMENT_SIZE: usize = 16344;
+
+pub(crate) const NK_NAME_OFFSET: usize = 0x4C;
+pub(crate) const VK_NAME_OFFSET: usize = 0x14;
+
+pub(crate) fn corrupt<T>() -> io::Result<T> {
+    werr!(Foundation::ERROR_REGISTRY_CORRUPT)
+}
+
+pub(crate) fn read_u16(data: &[u8], off: usize) -> io::Result<u16> {
+    match data.get(off..off + 2) {
+        Some(b) => Ok(u16::from_le_bytes([b[0], b[1]])),
+        None => corrupt(),
+    }
+}
+
+pub(crate) fn read_u32(data: &[u8], off: usize) -> io::Result<u32> {
+    match data.get(off..off + 4) {
+        Some(b) => Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        None => corrupt(),
+    }
+}
+
+pub(crate) fn read_u64(data: &[u8], off: usize) -> io::Result<u64> {
+    let lo = read_u32(data, off)? as u64;
+    let hi = read_u32(data, off + 4)? as u64;
+    Ok((hi << 32) | lo)
+}
+
+/// Names stored with `KEY_COMP_NAME`/`VALUE_COMP_NAME` use one byte per character
+pub(crate) fn decode_name(raw: &[u8], compressed: bool) -> Vec<u16> {
+    if compressed {
+        raw.iter().map(|b| *b as u16).collect()
+    } else {
+        v8_to_v16(raw)
+    }
+}
+
+/// Base block checksum: XOR of the first 127 dwords
+pub(crate) fn base_block_checksum(block: &[u8]) -> u32 {
+    let sum = block[..508].chunks_exact(4).fold(0u32, |acc, c| {
+        acc ^ u32::from_le_bytes([c[0], c[1], c[2], c[3]])
+    });
+    match sum {
+        0xFFFF_FFFF => 0xFFFF_FFFE,
+        0 => 1,
+        s => s,
+    }
+}
+
+/// Hash used in `lh` subkey lists
+pub(crate) fn name_hash(name: &[u16]) -> u32 {
+    name.iter().fold(0u32, |hash, c| {
+        hash.wrapping_mul(37).wrapping_add(upcase_utf16(*c) as u32)
+    })
+}
+
+/// Hive bins data, cell offsets are relative to its beginning
+#[derive(Clone, Copy)]
+pub(crate) struct Cells<'a> {
+    pub(crate) bins: &'a [u8],
+}
+
+impl<'a> Cells<'a> {
+    /// Data of a cell without its size field
+    pub(crate) fn cell(&self, offset: u32) -> io::Result<&'a [u8]> {
+        let start = offset as usize;
+        let size = read_u32(self.bins, start)? as i32;
+        let size = size.unsigned_abs() as usize;
+        if size < 4 {
+            return corrupt();
+        }
+        match self.bins.get(start + 4..start + size) {
+            Some(data) => Ok(data),
+            None => corrupt(),
+        }
+    }
+
+    pub(crate) fn key_node(&self, offset: u32) -> io::Result<KeyNode<'a>> {
+        KeyNode::parse(self.cell(offset)?)
+    }
+
+    pub(crate) fn value_key(&self, offset: u32) -> io::Result<ValueKey<'a>> {
+        ValueKey::parse(self.cell(offset)?)
+    }
+
+    /// Offset of the `index`-th key node referenced by a subkey list
+    pub(crate) fn subkey_at(&self, list: u32, index: u32) -> io::Result<Option<u32>> {
+        let data = self.cell(list)?;
+        let count = read_u16(data, 2)? as u32;
+        match &data[..2] {
+            b"li" => leaf_entry(data, 4, index, count),
+            b"lf" | b"lh" => leaf_entry(data, 8, index, count),
+            b"ri" => {
+                let mut index = index;
+                for i in 0..count as usize {
+                    let leaf = self.cell(read_u32(data, 4 + i * 4)?)?;
+                    let leaf_count = read_u16(leaf, 2)? as u32;
+                    let step = leaf_step(leaf)?;
+                    if index < leaf_count {
+                        return leaf_entry(leaf, step, index, leaf_count);
+                    }
+                    index -= leaf_count;
+                }
+                Ok(None)
+            }
+            _ => corrupt(),
+        }
+    }
+
+    /// Offsets of all key nodes referenced by a subkey list
+    pub(crate) fn subkeys(&self, list: u32) -> io::Result<Vec<u32>> {
+        let data = self.cell(list)?;
+        let mut res = Vec::new();
+        if data.starts_with(b"ri") {
+            let count = read_u16(data, 2)? as usize;
+            for i in 0..count {
+                leaf_entries(self.cell(read_u32(data, 4 + i * 4)?)?, &mut res)?;
+            }
+        } else {
+            leaf_entries(data, &mut res)?;
+        }
+        Ok(res)
+    }
+
+    /// Offset of the `index`-th value key referenced by a value list
+    pub(crate) fn value_at(&self, list: u32, count: u32, index: u32) -> io::Result<Option<u32>> {
+        if index >= count {
+            return Ok(None);
+        }
+        let data = self.cell(list)?;
+        read_u32(data, index as usize * 4).map(Some)
+    }
+
+    /// Read value data, following big data (`db`) segments if needed.
+    /// With `free` only unallocated cells are used, as for the data of deleted values.
+    pub(crate) fn value_data(
+        &self,
+        vk: &ValueKey,
+        big_data: bool,
+        free: bool,
+    ) -> io::Result<Vec<u8>> {
+        let cell = |offset: u32| {
+            // allocated cells have a negative size
+            if free && (read_u32(self.bins, offset as usize)? as i32) < 0 {
+                return corrupt();
+            }
+            self.cell(offset)
+        };
+        let size = vk.data_size();
+        if size & DATA_IS_RESIDENT != 0 {
+            let size = (size & !DATA_IS_RESIDENT) as usize;
+            if size > 4 {
+                return corrupt();
+            }
+            return Ok(vk.data_offset().to_le_bytes()[..size].to_vec());
+        }
+        let size = size as usize;
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+        let data = cell(vk.data_offset())?;
+        if big_data && size > BIG_DATA_SEGMENT_SIZE && data.starts_with(b"db") {
+            let segments = read_u16(data, 2)? as usize;
+            let list = cell(read_u32(data, 4)?)?;
+            let mut res = Vec::with_capacity(size);
+            for i in 0..segments {
+                let segment = cell(read_u32(list, i * 4)?)?;
+                let chunk = (size - res.len()).min(BIG_DATA_SEGMENT_SIZE);
+                match segment.get(..chunk) {
+                    Some(b) => res.extend_from_slice(b),
+                    None => return corrupt(),
+                }
+                if res.len() == size {
+                    break;
+                }
+            }
+            if res.len() != size {
+                return corrupt();
+            }
+            return Ok(res);
+        }
+        match data.get(..size) {
+            Some(b) => Ok(b.to_vec()),
+            None => corrupt(),
+        }
+    }
+}
+
+/// Size of the entries of an `li`, `lf` or `lh` list
+fn leaf_step(data: &[u8]) -> io::Result<usize> {
+    match &data[..2] {
+        b"li" => Ok(4),
+        b"lf" | b"lh" => Ok(8),
+        _ => corrupt(),
+    }
+}
+
+/// Append all offsets of an `li`, `lf` or `lh` list to `res`
+fn leaf_entries(data: &[u8], res: &mut Vec<u32>) -> io::Result<()> {
+    let count = read_u16(data, 2)? as usize;
+    let step = leaf_step(data)?;
+    res.reserve(count);
+    for i in 0..count {
+        res.push(read_u32(data, 4 + i * step)?);
+    }
+    Ok(())
+}
+
+fn leaf_entry(data: &[u8], step: usize, index: u32, count: u32) -> io::Result<Option<u32>> {
+    if index >= count {
+        return Ok(None);
+    }
+    read_u32(data, 4 + index as usize * step).map(Some)
+}
+
+/// Key node (`nk`) cell
+#[derive(Clone, Copy)]
+pub(crate) struct KeyNode<'a> {
+    data: &'a [u8],
+}
+
+macro_rules! field {
+    ($name:ident, $read:ident => $t:ty, $off:expr) => {
+        pub(crate) fn $name(&self) -> $t {
+            $read(self.data, $off).unwrap_or_default()
+        }
+    };
+}
+
+impl<'a> KeyNode<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> io::Result<KeyNode<'a>> {
+        if data.len() < NK_NAME_OFFSET || &data[..2] != b"nk" {
+            return corrupt();
+        }
+        let node = KeyNode { data };
+        if data.len() < NK_NAME_OFFSET + node.name_len() as usize {
+            return corrupt();
+        }
+        Ok(node)
+    }
+
+    field!(flags, read_u16 => u16, 0x02);
+    field!(last_write_time, read_u64 => u64, 0x04);
+    field!(parent, read_u32 => u32, 0x10);
+    field!(subkey_count, read_u32 => u32, 0x14);
+    field!(subkey_list, read_u32 => u32, 0x1C);
+    field!(value_count, read_u32 => u32, 0x24);
+    field!(value_list, read_u32 => u32, 0x28);
+    field!(max_subkey_name_len, read_u32 => u32, 0x34);
+    field!(max_class_len, read_u32 => u32, 0x38);
+    field!(max_value_name_len, read_u32 => u32, 0x3C);
+    field!(max_value_data_len, read_u32 => u32, 0x40);
+    field!(name_len, read_u16 => u16, 0x48);
+
+    pub(crate) fn name(&self) -> Vec<u16> {
+        let raw = &self.data[NK_NAME_OFFSET..NK_NAME_OFFSET + self.name_len() as usize];
+        decode_name(raw, self.flags() & KEY_COMP_NAME != 0)
+    }
+}
+
+/// Value key (`vk`) cell
+#[derive(Clone, Copy)]
+pub(crate) struct ValueKey<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ValueKey<'a> {
+    pub(crate) fn parse(data: &'a [u8]) -> io::Result<ValueKey<'a>> {
+        if data.len() < VK_NAME_OFFSET || &data[..2] != b"vk" {
+            return corrupt();
+        }
+        let value = ValueKey { data };
+        if data.len() < VK_NAME_OFFSET + value.name_len() as usize {
+            return corrupt();
+        }
+        Ok(value)
+    }
+
+    field!(name_len, read_u16 => u16, 0x02);
+    field!(data_size, read_u32 => u32, 0x04);
+    field!(data_offset, read_u32 => u32, 0x08);
+    field!(data_type, read_u32 => u32, 0x0C);
+    field!(flags, read_u16 => u16, 0x10);
+
+    pub(crate) fn name(&self) -> Vec<u16> {
+        let raw = &self.data[VK_NAME_OFFSET..VK_NAME_OFFSET + self.name_len() as usize];
+        decode_name(raw, self.flags() & VALUE_COMP_NAME != 0)
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Replay of transaction logs (`.LOG1`/`.LOG2`) in the new format (Windows 8.1+),
+//! where every log entry (`HvLE`) carries a set of dirty pages of hive bins data
+use super::cell::*;
+use super::BaseBlock;
+use std::io;
+use windows_sys::Win32::Foundation;
+
+/// A log file has a shortened base block, log entries follow it
+const LOG_BASE_BLOCK_SIZE: usize = 512;
+const LOG_ENTRY_ALIGNMENT: usize = 512;
+const LOG_ENTRY_HEADER_SIZE: usize = 40;
+/// File type of a transaction log in the new format
+const FILE_TYPE_LOG_NEW: u32 = 6;
+const MARVIN32_SEED: u64 = 0x82EF_4D88_7A4E_55C5;
+
+struct LogEntry<'a> {
+    sequence: u32,
+    hbins_size: u32,
+    /// Offsets relative to the first hive bin and page data
+    pages: Vec<(usize, &'a [u8])>,
+}
+
+struct LogFile<'a> {
+    base: &'a [u8],
+    entries: Vec<LogEntry<'a>>,
+}
+
+/// Marvin32 hash, used to protect log entries
+fn marvin32(data: &[u8], seed: u64) -> u64 {
+    fn block(lo: &mut u32, hi: &mut u32) {
+        *hi ^= *lo;
+        *lo = lo.rotate_left(20);
+        *lo = lo.wrapping_add(*hi);
+        *hi = hi.rotate_left(9);
+        *hi ^= *lo;
+        *lo = lo.rotate_left(27);
+        *lo = lo.wrapping_add(*hi);
+        *hi = hi.rotate_left(19);
+    }
+    let mut lo = seed as u32;
+    let mut hi = (seed >> 32) as u32;
+    let mut chunks = data.chunks_exact(4);
+    for c in &mut chunks {
+        lo = lo.wrapping_add(u32::from_le_bytes([c[0], c[1], c[2], c[3]]));
+        block(&mut lo, &mut hi);
+    }
+    let tail = chunks
+        .remainder()
+        .iter()
+        .rev()
+        .fold(0x80u32, |acc, b| (acc << 8) | *b as u32);
+    lo = lo.wrapping_add(tail);
+    block(&mut lo, &mut hi);
+    block(&mut lo, &mut hi);
+    ((hi as u64) << 32) | lo as u64
+}
+
+/// `None` if the log is empty, has an invalid base block or is in the old format
+fn parse_log(data: &[u8]) -> Option<LogFile<'_>> {
+    let base = BaseBlock::parse(data).ok()?;
+    if base.file_type != FILE_TYPE_LOG_NEW {
+        // old format logs (`DIRT`) are not supported
+        return None;
+    }
+    let mut entries = Vec::new();
+    let mut offset = LOG_BASE_BLOCK_SIZE;
+    let mut expected = base.primary_sequence;
+    // the first invalid or out of order entry ends the log
+    while let Some(entry) = parse_entry(data, offset) {
+        let (entry, size) = entry;
+        if entry.sequence != expected {
+            break;
+        }
+        entries.push(entry);
+        expected = expected.wrapping_add(1);
+        offset += size;
+    }
+    Some(LogFile {
+        base: &data[..LOG_BASE_BLOCK_SIZE],
+        entries,
+    })
+}
+
+fn parse_entry(data: &[u8], offset: usize) -> Option<(LogEntry<'_>, usize)> {
+    let header = data.get(offset..offset + LOG_ENTRY_HEADER_SIZE)?;
+    if &header[..4] != b"HvLE" {
+        return None;
+    }
+    let size = read_u32(header, 0x04).ok()? as usize;
+    if size < LOG_ENTRY_HEADER_SIZE || size % LOG_ENTRY_ALIGNMENT != 0 {
+        return None;
+    }
+    let entry = data.get(offset..offset + size)?;
+    let hash1 = read_u64(header, 0x18).ok()?;
+    let hash2 = read_u64(header, 0x20).ok()?;
+    if marvin32(&entry[..0x20], MARVIN32_SEED) != hash2
+        || marvin32(&entry[LOG_ENTRY_HEADER_SIZE..], MARVIN32_SEED) != hash1
+    {
+        return None;
+    }
+    let hbins_size = read_u32(header, 0x10).ok()?;
+    if hbins_size as usize % HBIN_ALIGNMENT != 0 {
+        return None;
+    }
+    let count = read_u32(header, 0x14).ok()? as usize;
+    let mut data_offset = LOG_ENTRY_HEADER_SIZE.checked_add(count.checked_mul(8)?)?;
+    let mut pages = Vec::with_capacity(count.min(size / 8));
+    for i in 0..count {
+        let page_offset = read_u32(entry, LOG_ENTRY_HEADER_SIZE + i * 8).ok()? as usize;
+        let page_size = read_u32(entry, LOG_ENTRY_HEADER_SIZE + i * 8 + 4).ok()? as usize;
+        if page_offset + page_size > hbins_size as usize {
+            return None;
+        }
+        pages.push((
+            page_offset,
+            entry.get(data_offset..data_offset + page_size)?,
+        ));
+        data_offset += page_size;
+    }
+    let sequence = read_u32(header, 0x0C).ok()?;
+    Some((
+        LogEntry {
+            sequence,
+            hbins_size,
+            pages,
+        },
+        size,
+    ))
+}
+
+/// Apply valid log entries to a dirty hive.
+/// Entries of both logs are applied in the order of their sequence numbers,
+/// starting from the earliest one that is not older than the last complete write.
+/// Logs that can't be used are skipped, the hive stays dirty if nothing was applied.
+/// Fails if an entry makes the hive larger than the hive bins and dirty pages
+/// of the logs together.
+pub(crate) fn replay(hive: &mut Vec<u8>, logs: &[&[u8]]) -> io::Result<()> {
+    let base = BaseBlock::parse(hive)?;
+    let mut files = Vec::with_capacity(logs.len());
+    for log in logs {
+        let file = match parse_log(log) {
+            Some(file) => file,
+            None => continue,
+        };
+        let first = file.entries.first().map(|e| e.sequence);
+        // a log that starts before the last complete write is stale
+        if first.map_or(false, |s| s >= base.secondary_sequence) {
+            files.push(file);
+        }
+    }
+    let mut expected = match files
+        .iter()
+        .filter_map(|f| f.entries.first())
+        .min_by_key(|e| e.sequence)
+    {
+        Some(e) => e.sequence,
+        None => return Ok(()),
+    };
+    // new hive bins come in dirty pages, a larger size is forged
+    let max_hbins_size = hive.len().saturating_sub(BASE_BLOCK_SIZE)
+        + files
+            .iter()
+            .flat_map(|f| &f.entries)
+            .flat_map(|e| &e.pages)
+            .map(|(_, page)| page.len())
+            .sum::<usize>();
+    let mut last_base = None;
+    loop {
+        let found = files.iter().find_map(|f| {
+            f.entries
+                .iter()
+                .find(|e| e.sequence == expected)
+                .map(|e| (f.base, e))
+        });
+        let (log_base, entry) = match found {
+            Some(found) => found,
+            None => break,
+        };
+        if entry.hbins_size as usize > max_hbins_size {
+            return werr!(Foundation::ERROR_INVALID_DATA);
+        }
+        hive.resize(BASE_BLOCK_SIZE + entry.hbins_size as usize, 0);
+        for (offset, page) in &entry.pages {
+            let start = BASE_BLOCK_SIZE + offset;
+            hive[start..start + page.len()].copy_from_slice(page);
+        }
+        last_base = Some(log_base);
+        expected = expected.wrapping_add(1);
+    }
+
+    // the hive is consistent now, take the base block of the log and mark it clean
+    if let Some(log_base) = last_base {
+        let hbins_size = (hive.len() - BASE_BLOCK_SIZE) as u32;
+        let block = &mut hive[..BASE_BLOCK_SIZE];
+        block[..LOG_BASE_BLOCK_SIZE].copy_from_slice(log_base);
+        block[0x04..0x08].copy_from_slice(&expected.to_le_bytes());
+        block[0x08..0x0C].copy_from_slice(&expected.to_le_bytes());
+        block[0x1C..0x20].copy_from_slice(&0u32.to_le_bytes());
+        block[0x28..0x2C].copy_from_slice(&hbins_size.to_le_bytes());
+        let checksum = base_block_checksum(block);
+        block[0x1FC..0x200].copy_from_slice(&checksum.to_le_bytes());
+    }
+    Ok(())
+}
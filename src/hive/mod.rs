@@ -0,0 +1,439 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Pure-Rust reader for offline registry hive files (`regf` format),
+//! e.g. `NTUSER.DAT`, `SOFTWARE` or `SYSTEM` copied from a disk image.
+//! [`KeyTree`] builds new hive files that can be loaded with `RegKey::load_app_key`.
+//!
+//!```no_run
+//!use std::io;
+//!use winreg::hive::Hive;
+//!
+//!fn main() -> io::Result<()> {
+//!    let hive = Hive::open("SOFTWARE")?;
+//!    let cur_ver = hive.root_key().open_subkey("Microsoft\\Windows\\CurrentVersion")?;
+//!    let pf: String = cur_ver.get_value("ProgramFilesDir")?;
+//!    println!("ProgramFiles = {}", pf);
+//!    for (name, value) in cur_ver.enum_values().map(|x| x.unwrap()) {
+//!        println!("{} = {:?}", name, value);
+//!    }
+//!    Ok(())
+//!}
+//!```
+use self::cell::*;
+use crate::backend::RegistryEnum;
+use crate::common::*;
+use crate::enum_keys::EnumKeys;
+use crate::enum_keys_os_string::EnumKeysOsString;
+use crate::enum_values::EnumValues;
+use crate::enum_values_os_string::EnumValuesOsString;
+use crate::enums::*;
+use crate::reg_key_metadata::{FileTime, RegKeyMetadata};
+use crate::reg_value::RegValue;
+use crate::regfile;
+use crate::types::FromRegValue;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use windows_sys::Win32::Foundation;
+
+pub(crate) mod cell;
+mod log;
+mod recover;
+mod writer;
+
+pub use self::recover::{DeletedKey, DeletedValue};
+pub use self::writer::KeyTree;
+
+/// Header of a hive file
+#[derive(Debug)]
+pub struct BaseBlock {
+    /// Incremented when a write to the hive starts
+    pub primary_sequence: u32,
+    /// Set equal to `primary_sequence` when the write is complete
+    pub secondary_sequence: u32,
+    pub last_written: FileTime,
+    pub major_version: u32,
+    pub minor_version: u32,
+    /// `0` for a primary hive file
+    pub file_type: u32,
+    /// Offset of the root key node, relative to the first hive bin
+    pub root_cell: u32,
+    /// Size of all hive bins
+    pub hbins_size: u32,
+    /// Last 64 bytes of the hive file path, as stored in the header
+    pub file_name: String,
+}
+
+impl BaseBlock {
+    fn parse(data: &[u8]) -> io::Result<BaseBlock> {
+        // transaction logs only store the first 512 bytes of a base block
+        if data.len() < 512 || &data[..4] != b"regf" {
+            return werr!(Foundation::ERROR_BADDB);
+        }
+        if base_block_checksum(data) != read_u32(data, 0x1FC)? {
+            return werr!(Foundation::ERROR_BADDB);
+        }
+        let mut file_name = v8_to_v16(&data[0x30..0x70]);
+        while let Some(0) = file_name.last() {
+            file_name.pop();
+        }
+        let base = BaseBlock {
+            primary_sequence: read_u32(data, 0x04)?,
+            secondary_sequence: read_u32(data, 0x08)?,
+            last_written: FileTime::from_u64(read_u64(data, 0x0C)?),
+            major_version: read_u32(data, 0x14)?,
+            minor_version: read_u32(data, 0x18)?,
+            file_type: read_u32(data, 0x1C)?,
+            root_cell: read_u32(data, 0x24)?,
+            hbins_size: read_u32(data, 0x28)?,
+            file_name: String::from_utf16_lossy(&file_name),
+        };
+        if base.major_version != 1 {
+            return werr!(Foundation::ERROR_BADDB);
+        }
+        Ok(base)
+    }
+
+    /// A hive is dirty if the last write to it didn't complete,
+    /// its actual state is then partially stored in transaction logs.
+    pub fn is_dirty(&self) -> bool {
+        self.primary_sequence != self.secondary_sequence
+    }
+}
+
+/// Registry hive loaded into memory
+pub struct Hive {
+    data: Vec<u8>,
+    base: BaseBlock,
+}
+
+impl Hive {
+    /// Read a hive file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use winreg::hive::Hive;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let hive = Hive::open("NTUSER.DAT")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// If the hive is dirty, transaction logs found next to it
+    /// (`NTUSER.DAT.LOG1` and `NTUSER.DAT.LOG2`) are replayed.
+    /// Without them the hive stays dirty and may contain stale data,
+    /// this can be checked with `base_block().is_dirty()`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Hive> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        if !BaseBlock::parse(&data)?.is_dirty() {
+            return Hive::from_bytes(data);
+        }
+        let mut logs = Vec::new();
+        for ext in ["LOG1", "LOG2"].iter() {
+            for ext in [ext.to_string(), ext.to_lowercase()].iter() {
+                let mut log_path = path.as_os_str().to_owned();
+                log_path.push(".");
+                log_path.push(ext);
+                match fs::read(&log_path) {
+                    Ok(log) => {
+                        logs.push(log);
+                        break;
+                    }
+                    Err(ref err) if err.kind() == io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+        let logs: Vec<&[u8]> = logs.iter().map(|l| l.as_slice()).collect();
+        Hive::from_bytes_with_logs(data, &logs)
+    }
+
+    /// Parse a hive from its raw content.
+    /// Fails with `ERROR_BADDB` if the base block is invalid
+    /// and with `ERROR_REGISTRY_CORRUPT` if the root key can't be read.
+    pub fn from_bytes(data: Vec<u8>) -> io::Result<Hive> {
+        if data.len() < BASE_BLOCK_SIZE {
+            return werr!(Foundation::ERROR_BADDB);
+        }
+        let base = BaseBlock::parse(&data)?;
+        let hive = Hive { data, base };
+        hive.cells().key_node(hive.base.root_cell)?;
+        Ok(hive)
+    }
+
+    /// Parse a dirty hive and replay its transaction logs (contents of
+    /// `.LOG1` and `.LOG2` files) in the new format, used since Windows 8.1.
+    /// Log entries that are stale or fail their hash checks are skipped,
+    /// as well as logs that are empty, damaged or in the old format.
+    /// The hive stays dirty if no entry could be applied.
+    /// A hive that is not dirty is parsed as is.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use std::fs;
+    /// # use winreg::hive::Hive;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let log1 = fs::read("SYSTEM.LOG1")?;
+    /// let log2 = fs::read("SYSTEM.LOG2")?;
+    /// let hive = Hive::from_bytes_with_logs(fs::read("SYSTEM")?, &[&log1, &log2])?;
+    /// assert!(!hive.base_block().is_dirty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bytes_with_logs(mut data: Vec<u8>, logs: &[&[u8]]) -> io::Result<Hive> {
+        if data.len() < BASE_BLOCK_SIZE {
+            return werr!(Foundation::ERROR_BADDB);
+        }
+        if BaseBlock::parse(&data)?.is_dirty() {
+            log::replay(&mut data, logs)?;
+        }
+        Hive::from_bytes(data)
+    }
+
+    pub fn base_block(&self) -> &BaseBlock {
+        &self.base
+    }
+
+    /// Raw content of the hive
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The root key of the hive
+    pub fn root_key(&self) -> HiveKey<'_> {
+        HiveKey {
+            hive: self,
+            offset: self.base.root_cell,
+        }
+    }
+
+    pub(crate) fn cells(&self) -> Cells<'_> {
+        let end = (BASE_BLOCK_SIZE + self.base.hbins_size as usize).min(self.data.len());
+        Cells {
+            bins: &self.data[BASE_BLOCK_SIZE.min(end)..end],
+        }
+    }
+
+    /// Big data (`db`) cells were introduced in hive version 1.4
+    fn has_big_data(&self) -> bool {
+        self.base.minor_version > 3
+    }
+}
+
+/// Key of an offline hive
+#[derive(Clone, Copy)]
+pub struct HiveKey<'h> {
+    hive: &'h Hive,
+    offset: u32,
+}
+
+impl<'h> HiveKey<'h> {
+    fn node(&self) -> io::Result<KeyNode<'h>> {
+        self.hive.cells().key_node(self.offset)
+    }
+
+    /// Offset of the key node, relative to the first hive bin
+    pub fn cell_offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Name of the key as stored in the hive
+    pub fn name(&self) -> io::Result<String> {
+        Ok(String::from_utf16_lossy(&self.node()?.name()))
+    }
+
+    /// Open subkey. Key names are not case sensitive.
+    /// Returns the key itself if `path` is an empty string.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use winreg::hive::Hive;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let hive = Hive::open("SOFTWARE")?;
+    /// let key = hive.root_key().open_subkey("Microsoft\\Windows")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open_subkey<P: AsRef<OsStr>>(&self, path: P) -> io::Result<HiveKey<'h>> {
+        let path = path.as_ref().to_string_lossy();
+        let mut key = *self;
+        for name in path.split('\\').filter(|n| !n.is_empty()) {
+            key = key.find_subkey(name)?;
+        }
+        Ok(key)
+    }
+
+    fn find_subkey(&self, name: &str) -> io::Result<HiveKey<'h>> {
+        let cells = self.hive.cells();
+        let node = self.node()?;
+        if node.subkey_count() > 0 {
+            for offset in cells.subkeys(node.subkey_list())? {
+                let sub = cells.key_node(offset)?;
+                if names_eq(&String::from_utf16_lossy(&sub.name()), name) {
+                    return Ok(HiveKey {
+                        hive: self.hive,
+                        offset,
+                    });
+                }
+            }
+        }
+        werr!(Foundation::ERROR_FILE_NOT_FOUND)
+    }
+
+    pub fn query_info(&self) -> io::Result<RegKeyMetadata> {
+        let node = self.node()?;
+        Ok(RegKeyMetadata {
+            sub_keys: node.subkey_count(),
+            max_sub_key_len: (node.max_subkey_name_len() & 0xFFFF) / 2,
+            max_class_len: node.max_class_len() / 2,
+            values: node.value_count(),
+            max_value_name_len: node.max_value_name_len() / 2,
+            max_value_len: node.max_value_data_len(),
+            last_write_time: FileTime::from_u64(node.last_write_time()),
+        })
+    }
+
+    /// Write the key with all its subkeys and values as a `.reg` file
+    /// in the `Windows Registry Editor Version 5.00` format.
+    /// `path` is the name of this key in the file, e.g. `HKEY_LOCAL_MACHINE\SOFTWARE`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use std::fs::File;
+    /// # use winreg::hive::Hive;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let hive = Hive::open("SOFTWARE")?;
+    /// let key = hive.root_key().open_subkey("Microsoft\\Windows")?;
+    /// let file = File::create("windows.reg")?;
+    /// key.export_reg("HKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Windows", file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn export_reg<P: AsRef<OsStr>, W: Write>(&self, path: P, w: W) -> io::Result<()> {
+        regfile::export(self, &path.as_ref().to_string_lossy())?.write(w)
+    }
+
+    /// Return an iterator over subkeys names as `String`s.
+    pub fn enum_keys(&self) -> EnumKeys<'_, Self> {
+        EnumKeys {
+            key: self,
+            index: 0,
+        }
+    }
+
+    /// Return an iterator over subkeys names as `OsString`s.
+    pub fn enum_keys_os_string(&self) -> EnumKeysOsString<'_, Self> {
+        EnumKeysOsString {
+            key: self,
+            index: 0,
+        }
+    }
+
+    /// Return an iterator over values represented as `(String, RegValue)`.
+    pub fn enum_values(&self) -> EnumValues<'_, Self> {
+        EnumValues {
+            key: self,
+            index: 0,
+        }
+    }
+
+    /// Return an iterator over values represented as `(OsString, RegValue)`.
+    pub fn enum_values_os_string(&self) -> EnumValuesOsString<'_, Self> {
+        EnumValuesOsString {
+            key: self,
+            index: 0,
+        }
+    }
+
+    /// Get a value and convert it to the specified rust type.
+    /// Will get the `Default` value if `name` is an empty string.
+    pub fn get_value<T: FromRegValue, N: AsRef<OsStr>>(&self, name: N) -> io::Result<T> {
+        match self.get_raw_value(name) {
+            Ok(ref val) => FromRegValue::from_reg_value(val),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get raw bytes of a value. Value names are not case sensitive.
+    /// Will get the `Default` value if `name` is an empty string.
+    pub fn get_raw_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<RegValue<'static>> {
+        let name = name.as_ref().to_string_lossy();
+        let cells = self.hive.cells();
+        let node = self.node()?;
+        for i in 0..node.value_count() {
+            let offset = match cells.value_at(node.value_list(), node.value_count(), i)? {
+                Some(offset) => offset,
+                None => break,
+            };
+            let vk = cells.value_key(offset)?;
+            if names_eq(&String::from_utf16_lossy(&vk.name()), &name) {
+                return self.read_value(&vk);
+            }
+        }
+        werr!(Foundation::ERROR_FILE_NOT_FOUND)
+    }
+
+    fn read_value(&self, vk: &ValueKey) -> io::Result<RegValue<'static>> {
+        let bytes = self
+            .hive
+            .cells()
+            .value_data(vk, self.hive.has_big_data(), false)?;
+        Ok(RegValue {
+            bytes: bytes.into(),
+            vtype: RegType::from_raw(vk.data_type()),
+        })
+    }
+}
+
+impl RegistryEnum for HiveKey<'_> {
+    fn enum_key(&self, index: u32) -> Option<io::Result<OsString>> {
+        let cells = self.hive.cells();
+        let node = match self.node() {
+            Ok(node) => node,
+            Err(err) => return Some(Err(err)),
+        };
+        if index >= node.subkey_count() {
+            return None;
+        }
+        let res = cells
+            .subkey_at(node.subkey_list(), index)
+            .and_then(|offset| match offset {
+                Some(offset) => cells.key_node(offset),
+                None => corrupt(),
+            })
+            .map(|sub| utf16_to_os_string(&sub.name()));
+        Some(res)
+    }
+
+    fn enum_value(&self, index: u32) -> Option<io::Result<(OsString, RegValue<'static>)>> {
+        let cells = self.hive.cells();
+        let node = match self.node() {
+            Ok(node) => node,
+            Err(err) => return Some(Err(err)),
+        };
+        let offset = match cells.value_at(node.value_list(), node.value_count(), index) {
+            Ok(Some(offset)) => offset,
+            Ok(None) => return None,
+            Err(err) => return Some(Err(err)),
+        };
+        let res = cells.value_key(offset).and_then(|vk| {
+            let value = self.read_value(&vk)?;
+            Ok((utf16_to_os_string(&vk.name()), value))
+        });
+        Some(res)
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Recovery of deleted keys and values from unallocated cells
+use super::cell::*;
+use super::Hive;
+use crate::enums::RegType;
+use crate::reg_key_metadata::FileTime;
+use std::collections::HashSet;
+
+/// Parent chains longer than that are considered broken
+const MAX_DEPTH: usize = 512;
+
+/// Key node found in an unallocated cell
+#[derive(Debug)]
+pub struct DeletedKey {
+    /// Offset of the key node, relative to the first hive bin
+    pub cell_offset: u32,
+    pub name: String,
+    pub last_write_time: FileTime,
+    /// Offset of the parent key node as stored in the deleted one
+    pub parent_offset: u32,
+    /// Path of the parent key relative to the root key,
+    /// `None` if the chain of parents can't be followed up to the root anymore.
+    /// Parents can be deleted keys as well.
+    pub parent_path: Option<String>,
+}
+
+/// Value key found in an unallocated cell
+#[derive(Debug)]
+pub struct DeletedValue {
+    /// Offset of the value key, relative to the first hive bin
+    pub cell_offset: u32,
+    pub name: String,
+    pub vtype: RegType,
+    /// `None` if the data cells are gone or were allocated again
+    pub data: Option<Vec<u8>>,
+    /// Last write time of the deleted key that owned the value, if it was found
+    pub last_write_time: Option<FileTime>,
+    /// Path of the key that owned the value, relative to the root key,
+    /// `None` if it can't be resolved
+    pub parent_path: Option<String>,
+}
+
+/// Record spotted in the slack space: `(offset, is_key)`
+fn scan(cells: Cells) -> Vec<(u32, bool)> {
+    let bins = cells.bins;
+    let mut res = Vec::new();
+    let mut bin = 0;
+    while bin + HBIN_HEADER_SIZE <= bins.len() && &bins[bin..bin + 4] == b"hbin" {
+        let bin_size = match read_u32(bins, bin + 8) {
+            Ok(size) if size as usize >= HBIN_ALIGNMENT => size as usize,
+            _ => break,
+        };
+        let bin_end = (bin + bin_size).min(bins.len());
+        let mut cell = bin + HBIN_HEADER_SIZE;
+        while cell + 4 <= bin_end {
+            let size = match read_u32(bins, cell) {
+                Ok(size) => size as i32,
+                Err(_) => break,
+            };
+            let len = size.unsigned_abs() as usize;
+            if len < 8 || cell + len > bin_end {
+                break;
+            }
+            if size > 0 {
+                scan_free_cell(bins, cell, cell + len, &mut res);
+            }
+            cell += len;
+        }
+        bin += bin_size;
+    }
+    res
+}
+
+/// A free cell may be a result of several freed cells merged together,
+/// so look for records at every possible cell boundary
+fn scan_free_cell(bins: &[u8], start: usize, end: usize, res: &mut Vec<(u32, bool)>) {
+    let mut pos = start;
+    while pos + 6 <= end {
+        let data = &bins[pos + 4..end];
+        let len = match &data[..2] {
+            b"nk" => KeyNode::parse(data)
+                .ok()
+                .filter(|nk| plausible_key(nk))
+                .map(|nk| NK_NAME_OFFSET + nk.name_len() as usize),
+            b"vk" => ValueKey::parse(data)
+                .ok()
+                .filter(|vk| plausible_value(vk))
+                .map(|vk| VK_NAME_OFFSET + vk.name_len() as usize),
+            _ => None,
+        };
+        match len {
+            Some(len) => {
+                res.push((pos as u32, &data[..2] == b"nk"));
+                pos += (len + 4 + CELL_ALIGNMENT - 1) & !(CELL_ALIGNMENT - 1);
+            }
+            None => pos += CELL_ALIGNMENT,
+        }
+    }
+}
+
+fn plausible_key(nk: &KeyNode) -> bool {
+    let len = nk.name_len() as usize;
+    let max = if nk.flags() & KEY_COMP_NAME != 0 {
+        255
+    } else {
+        510
+    };
+    len > 0 && len <= max && nk.last_write_time() != 0
+}
+
+fn plausible_value(vk: &ValueKey) -> bool {
+    let size = vk.data_size();
+    let resident_ok = size & DATA_IS_RESIDENT == 0 || size & !DATA_IS_RESIDENT <= 4;
+    vk.name_len() as usize <= 2 * 16383 && resident_ok
+}
+
+impl Hive {
+    /// Path of a key node relative to the root key, following parent offsets
+    /// through both allocated and unallocated cells
+    fn resolve_path(&self, offset: u32) -> Option<String> {
+        let cells = self.cells();
+        let mut names = Vec::new();
+        let mut offset = offset;
+        for _ in 0..MAX_DEPTH {
+            let node = cells.key_node(offset).ok()?;
+            if node.flags() & KEY_HIVE_ENTRY != 0 {
+                names.reverse();
+                return Some(names.join("\\"));
+            }
+            names.push(String::from_utf16_lossy(&node.name()));
+            offset = node.parent();
+        }
+        None
+    }
+
+    /// Scan unallocated cells for key nodes that look valid.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use winreg::hive::Hive;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let hive = Hive::open("NTUSER.DAT")?;
+    /// for key in hive.deleted_keys() {
+    ///     let parent = key.parent_path.as_deref().unwrap_or("<unknown>");
+    ///     println!("{}\\{} {:?}", parent, key.name, key.last_write_time);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn deleted_keys(&self) -> Vec<DeletedKey> {
+        let cells = self.cells();
+        scan(cells)
+            .into_iter()
+            .filter(|(_, is_key)| *is_key)
+            .filter_map(|(offset, _)| {
+                let node = cells.key_node(offset).ok()?;
+                Some(DeletedKey {
+                    cell_offset: offset,
+                    name: String::from_utf16_lossy(&node.name()),
+                    last_write_time: FileTime::from_u64(node.last_write_time()),
+                    parent_offset: node.parent(),
+                    parent_path: self.resolve_path(node.parent()),
+                })
+            })
+            .collect()
+    }
+
+    /// Scan unallocated cells for value keys that look valid.
+    /// Values still referenced by value lists of deleted keys
+    /// get their owner's path and last write time.
+    pub fn deleted_values(&self) -> Vec<DeletedValue> {
+        let cells = self.cells();
+        let found = scan(cells);
+        let values: HashSet<u32> = found
+            .iter()
+            .filter(|(_, is_key)| !is_key)
+            .map(|(offset, _)| *offset)
+            .collect();
+
+        let mut res = Vec::new();
+        let mut seen = HashSet::new();
+        for (offset, _) in found.iter().filter(|(_, is_key)| *is_key) {
+            let node = match cells.key_node(*offset) {
+                Ok(node) => node,
+                Err(_) => continue,
+            };
+            let path = self.resolve_path(*offset);
+            let time = node.last_write_time();
+            for i in 0..node.value_count() {
+                let value = match cells.value_at(node.value_list(), node.value_count(), i) {
+                    Ok(Some(value)) => value,
+                    _ => break,
+                };
+                if values.contains(&value) && seen.insert(value) {
+                    if let Some(mut v) = self.deleted_value(value) {
+                        v.last_write_time = Some(FileTime::from_u64(time));
+                        v.parent_path = path.clone();
+                        res.push(v);
+                    }
+                }
+            }
+        }
+        for (offset, _) in found.iter().filter(|(_, is_key)| !is_key) {
+            if !seen.contains(offset) {
+                res.extend(self.deleted_value(*offset));
+            }
+        }
+        res.sort_by_key(|v| v.cell_offset);
+        res
+    }
+
+    fn deleted_value(&self, offset: u32) -> Option<DeletedValue> {
+        let cells = self.cells();
+        let vk = cells.value_key(offset).ok()?;
+        Some(DeletedValue {
+            cell_offset: offset,
+            name: String::from_utf16_lossy(&vk.name()),
+            vtype: RegType::from_raw(vk.data_type()),
+            data: cells.value_data(&vk, self.has_big_data(), true).ok(),
+            last_write_time: None,
+            parent_path: None,
+        })
+    }
+}
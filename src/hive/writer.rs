@@ -0,0 +1,490 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Pure-Rust writer for hive files that can be loaded with `RegKey::load_app_key`
+use super::cell::*;
+use crate::common::*;
+use crate::reg_key_metadata::FileTime;
+use crate::reg_value::RegValue;
+use crate::types::ToRegValue;
+use std::cmp::Ordering;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use windows_sys::Win32::Foundation;
+
+const MAX_KEY_NAME_LEN: usize = 255;
+const MAX_VALUE_NAME_LEN: usize = 16383;
+const MAX_LEAF_ENTRIES: usize = 1012;
+const KEY_ALL_ACCESS: u32 = 0x000F_003F;
+
+/// A tree of keys and values to be written into a hive file.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::error::Error;
+/// use winreg::hive::KeyTree;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut root = KeyTree::new("MyAppHive");
+/// let settings = root.create_subkey("Software\\MyProduct\\Settings");
+/// settings.set_value("server", &"www.example.com");
+/// settings.set_value("port", &8080u32);
+/// root.save("myhive.dat")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct KeyTree {
+    name: String,
+    values: Vec<(String, RegValue<'static>)>,
+    subkeys: Vec<KeyTree>,
+    last_write_time: u64,
+}
+
+impl KeyTree {
+    /// Create a key without values and subkeys.
+    /// Its last write time is zero, so output is reproducible
+    /// unless `set_last_write_time` is used.
+    pub fn new<N: Into<String>>(name: N) -> KeyTree {
+        KeyTree {
+            name: name.into(),
+            values: Vec::new(),
+            subkeys: Vec::new(),
+            last_write_time: 0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn values(&self) -> &[(String, RegValue<'static>)] {
+        &self.values
+    }
+
+    pub fn subkeys(&self) -> &[KeyTree] {
+        &self.subkeys
+    }
+
+    pub fn set_last_write_time(&mut self, time: FileTime) {
+        self.last_write_time = time.as_u64();
+    }
+
+    /// Find a subkey. Key names are not case sensitive.
+    pub fn subkey<P: AsRef<OsStr>>(&self, path: P) -> Option<&KeyTree> {
+        let path = path.as_ref().to_string_lossy();
+        let mut key = self;
+        for name in path.split('\\').filter(|n| !n.is_empty()) {
+            key = key.subkeys.iter().find(|k| names_eq(&k.name, name))?;
+        }
+        Some(key)
+    }
+
+    /// Create subkey (and all missing parent keys) or return an existing one.
+    /// Key names are not case sensitive, but the case of new keys is preserved.
+    pub fn create_subkey<P: AsRef<OsStr>>(&mut self, path: P) -> &mut KeyTree {
+        let path = path.as_ref().to_string_lossy();
+        let mut key = self;
+        for name in path.split('\\').filter(|n| !n.is_empty()) {
+            let pos = match key.subkeys.iter().position(|k| names_eq(&k.name, name)) {
+                Some(pos) => pos,
+                None => {
+                    key.subkeys.push(KeyTree::new(name));
+                    key.subkeys.len() - 1
+                }
+            };
+            key = &mut key.subkeys[pos];
+        }
+        key
+    }
+
+    /// Remove a direct subkey with all its content
+    pub fn delete_subkey<N: AsRef<OsStr>>(&mut self, name: N) -> Option<KeyTree> {
+        let name = name.as_ref().to_string_lossy();
+        let pos = self.subkeys.iter().position(|k| names_eq(&k.name, &name))?;
+        Some(self.subkeys.remove(pos))
+    }
+
+    /// Convert a value from a rust type and set it.
+    /// Will set the `Default` value if `name` is an empty string.
+    pub fn set_value<T: ToRegValue, N: AsRef<OsStr>>(&mut self, name: N, value: &T) {
+        self.set_raw_value(name, &value.to_reg_value())
+    }
+
+    /// Set a value, replacing an existing one with the same name (case insensitive).
+    /// Will set the `Default` value if `name` is an empty string.
+    pub fn set_raw_value<N: AsRef<OsStr>>(&mut self, name: N, value: &RegValue) {
+        let name = name.as_ref().to_string_lossy();
+        let value = RegValue {
+            bytes: value.bytes.to_vec().into(),
+            vtype: value.vtype.clone(),
+        };
+        match self.values.iter_mut().find(|(n, _)| names_eq(n, &name)) {
+            Some(v) => v.1 = value,
+            None => self.values.push((name.into_owned(), value)),
+        }
+    }
+
+    pub fn get_raw_value<N: AsRef<OsStr>>(&self, name: N) -> Option<&RegValue<'static>> {
+        let name = name.as_ref().to_string_lossy();
+        self.values
+            .iter()
+            .find(|(n, _)| names_eq(n, &name))
+            .map(|(_, v)| v)
+    }
+
+    pub fn delete_value<N: AsRef<OsStr>>(&mut self, name: N) -> Option<RegValue<'static>> {
+        let name = name.as_ref().to_string_lossy();
+        let pos = self.values.iter().position(|(n, _)| names_eq(n, &name))?;
+        Some(self.values.remove(pos).1)
+    }
+
+    /// Write the tree as a hive file, this key becomes the root key.
+    /// Fails with `ERROR_INVALID_PARAMETER` if a key or value name is too long.
+    pub fn write<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&self.to_bytes()?)
+    }
+
+    /// Save the tree into a hive file, this key becomes the root key.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_bytes()?)
+    }
+
+    /// Serialize the tree as a hive file, this key becomes the root key.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut bins = Bins::new(self.last_write_time);
+        let sd = default_security_descriptor();
+        let sk = bins.alloc(0x14 + sd.len());
+        let key_count = self.write_key(&mut bins, NO_CELL, sk, true)?;
+        {
+            let cell = bins.cell_mut(sk);
+            cell[..2].copy_from_slice(b"sk");
+            put_u32(cell, 0x04, sk);
+            put_u32(cell, 0x08, sk);
+            put_u32(cell, 0x0C, key_count);
+            put_u32(cell, 0x10, sd.len() as u32);
+            cell[0x14..0x14 + sd.len()].copy_from_slice(&sd);
+        }
+        let root = bins.first_key;
+        let bins = bins.finish();
+
+        let mut base = vec![0u8; BASE_BLOCK_SIZE];
+        base[..4].copy_from_slice(b"regf");
+        put_u32(&mut base, 0x04, 1); // primary sequence number
+        put_u32(&mut base, 0x08, 1); // secondary sequence number
+        put_u64(&mut base, 0x0C, self.last_write_time);
+        put_u32(&mut base, 0x14, 1); // major version
+        put_u32(&mut base, 0x18, 5); // minor version
+        put_u32(&mut base, 0x1C, 0); // primary file
+        put_u32(&mut base, 0x20, 1); // direct memory load
+        put_u32(&mut base, 0x24, root);
+        put_u32(&mut base, 0x28, bins.len() as u32);
+        put_u32(&mut base, 0x2C, 1); // clustering factor
+        let checksum = base_block_checksum(&base);
+        put_u32(&mut base, 0x1FC, checksum);
+        base.extend_from_slice(&bins);
+        Ok(base)
+    }
+
+    /// Returns the number of written keys
+    fn write_key(&self, bins: &mut Bins, parent: u32, sk: u32, root: bool) -> io::Result<u32> {
+        let (name, compressed) = encode_name(&self.name, MAX_KEY_NAME_LEN)?;
+        let offset = bins.alloc(NK_NAME_OFFSET + name.len());
+        if root {
+            bins.first_key = offset;
+        }
+
+        let mut value_offsets = Vec::with_capacity(self.values.len());
+        let mut max_value_name_len = 0;
+        let mut max_value_data_len = 0;
+        for (value_name, value) in &self.values {
+            value_offsets.push(write_value(bins, value_name, value)?);
+            max_value_name_len = max_value_name_len.max(value_name.encode_utf16().count() * 2);
+            max_value_data_len = max_value_data_len.max(value.bytes.len());
+        }
+        let value_list = match value_offsets.len() {
+            0 => NO_CELL,
+            n => {
+                let list = bins.alloc(n * 4);
+                let cell = bins.cell_mut(list);
+                for (i, v) in value_offsets.iter().enumerate() {
+                    put_u32(cell, i * 4, *v);
+                }
+                list
+            }
+        };
+
+        // subkey lists must be sorted, lookups use binary search
+        let mut subkeys: Vec<&KeyTree> = self.subkeys.iter().collect();
+        subkeys.sort_by(|a, b| compare_names(&a.name, &b.name));
+        let mut entries = Vec::with_capacity(subkeys.len());
+        let mut max_subkey_name_len = 0;
+        let mut key_count = 1;
+        for (i, sub) in subkeys.iter().enumerate() {
+            if i > 0 && compare_names(&subkeys[i - 1].name, &sub.name) == Ordering::Equal {
+                return werr!(Foundation::ERROR_ALREADY_EXISTS);
+            }
+            key_count += sub.write_key(bins, offset, sk, false)?;
+            let sub_name: Vec<u16> = sub.name.encode_utf16().collect();
+            max_subkey_name_len = max_subkey_name_len.max(sub_name.len() * 2);
+            entries.push((bins.last_key, name_hash(&sub_name)));
+        }
+        let subkey_list = match entries.len() {
+            0 => NO_CELL,
+            n if n <= MAX_LEAF_ENTRIES => write_lh(bins, &entries),
+            _ => {
+                let leaves: Vec<u32> = entries
+                    .chunks(MAX_LEAF_ENTRIES)
+                    .map(|chunk| write_lh(bins, chunk))
+                    .collect();
+                let ri = bins.alloc(4 + leaves.len() * 4);
+                let cell = bins.cell_mut(ri);
+                cell[..2].copy_from_slice(b"ri");
+                put_u16(cell, 2, leaves.len() as u16);
+                for (i, leaf) in leaves.iter().enumerate() {
+                    put_u32(cell, 4 + i * 4, *leaf);
+                }
+                ri
+            }
+        };
+
+        let cell = bins.cell_mut(offset);
+        let mut flags = if compressed { KEY_COMP_NAME } else { 0 };
+        if root {
+            flags |= KEY_HIVE_ENTRY | KEY_NO_DELETE;
+        }
+        cell[..2].copy_from_slice(b"nk");
+        put_u16(cell, 0x02, flags);
+        put_u64(cell, 0x04, self.last_write_time);
+        put_u32(cell, 0x10, parent);
+        put_u32(cell, 0x14, entries.len() as u32);
+        put_u32(cell, 0x18, 0); // volatile subkeys
+        put_u32(cell, 0x1C, subkey_list);
+        put_u32(cell, 0x20, NO_CELL);
+        put_u32(cell, 0x24, value_offsets.len() as u32);
+        put_u32(cell, 0x28, value_list);
+        put_u32(cell, 0x2C, sk);
+        put_u32(cell, 0x30, NO_CELL); // class name
+        put_u32(cell, 0x34, max_subkey_name_len as u32);
+        put_u32(cell, 0x38, 0);
+        put_u32(cell, 0x3C, max_value_name_len as u32);
+        put_u32(cell, 0x40, max_value_data_len as u32);
+        put_u16(cell, 0x48, name.len() as u16);
+        put_u16(cell, 0x4A, 0);
+        cell[NK_NAME_OFFSET..NK_NAME_OFFSET + name.len()].copy_from_slice(&name);
+        bins.last_key = offset;
+        Ok(key_count)
+    }
+}
+
+fn write_lh(bins: &mut Bins, entries: &[(u32, u32)]) -> u32 {
+    let lh = bins.alloc(4 + entries.len() * 8);
+    let cell = bins.cell_mut(lh);
+    cell[..2].copy_from_slice(b"lh");
+    put_u16(cell, 2, entries.len() as u16);
+    for (i, (offset, hash)) in entries.iter().enumerate() {
+        put_u32(cell, 4 + i * 8, *offset);
+        put_u32(cell, 8 + i * 8, *hash);
+    }
+    lh
+}
+
+fn write_value(bins: &mut Bins, name: &str, value: &RegValue) -> io::Result<u32> {
+    let (name, compressed) = encode_name(name, MAX_VALUE_NAME_LEN)?;
+    let data = &value.bytes;
+    let (size, data_offset) = if data.len() <= 4 {
+        let mut inline = [0u8; 4];
+        inline[..data.len()].copy_from_slice(data);
+        (
+            data.len() as u32 | DATA_IS_RESIDENT,
+            u32::from_le_bytes(inline),
+        )
+    } else if data.len() <= BIG_DATA_SEGMENT_SIZE {
+        let cell = bins.alloc(data.len());
+        bins.cell_mut(cell)[..data.len()].copy_from_slice(data);
+        (data.len() as u32, cell)
+    } else {
+        let segments: Vec<u32> = data
+            .chunks(BIG_DATA_SEGMENT_SIZE)
+            .map(|chunk| {
+                let cell = bins.alloc(chunk.len());
+                bins.cell_mut(cell)[..chunk.len()].copy_from_slice(chunk);
+                cell
+            })
+            .collect();
+        if segments.len() > u16::MAX as usize {
+            return werr!(Foundation::ERROR_INVALID_PARAMETER);
+        }
+        let list = bins.alloc(segments.len() * 4);
+        let cell = bins.cell_mut(list);
+        for (i, s) in segments.iter().enumerate() {
+            put_u32(cell, i * 4, *s);
+        }
+        let db = bins.alloc(8);
+        let cell = bins.cell_mut(db);
+        cell[..2].copy_from_slice(b"db");
+        put_u16(cell, 2, segments.len() as u16);
+        put_u32(cell, 4, list);
+        (data.len() as u32, db)
+    };
+    let offset = bins.alloc(VK_NAME_OFFSET + name.len());
+    let cell = bins.cell_mut(offset);
+    cell[..2].copy_from_slice(b"vk");
+    put_u16(cell, 0x02, name.len() as u16);
+    put_u32(cell, 0x04, size);
+    put_u32(cell, 0x08, data_offset);
+    put_u32(cell, 0x0C, value.vtype.raw());
+    put_u16(cell, 0x10, if compressed { VALUE_COMP_NAME } else { 0 });
+    cell[VK_NAME_OFFSET..VK_NAME_OFFSET + name.len()].copy_from_slice(&name);
+    Ok(offset)
+}
+
+/// Names that fit into Latin-1 are stored with one byte per character
+fn encode_name(name: &str, max_len: usize) -> io::Result<(Vec<u8>, bool)> {
+    let words: Vec<u16> = name.encode_utf16().collect();
+    if words.len() > max_len || (max_len == MAX_KEY_NAME_LEN && words.is_empty()) {
+        return werr!(Foundation::ERROR_INVALID_PARAMETER);
+    }
+    if words.iter().all(|w| *w <= 0xFF) {
+        Ok((words.iter().map(|w| *w as u8).collect(), true))
+    } else {
+        Ok((v16_to_v8(&words), false))
+    }
+}
+
+/// Self-relative security descriptor shared by all keys:
+/// owned by Administrators, full access for Everyone.
+/// Access to an application hive is controlled by the ACL of its file anyway.
+fn default_security_descriptor() -> Vec<u8> {
+    fn sid(authority: u8, sub_authorities: &[u32]) -> Vec<u8> {
+        let mut sid = vec![1, sub_authorities.len() as u8, 0, 0, 0, 0, 0, authority];
+        for s in sub_authorities {
+            sid.extend_from_slice(&s.to_le_bytes());
+        }
+        sid
+    }
+    let everyone = sid(1, &[0]);
+    let admins = sid(5, &[32, 544]);
+    let system = sid(5, &[18]);
+
+    let mut ace = vec![0u8, 0x02]; // ACCESS_ALLOWED_ACE_TYPE, CONTAINER_INHERIT_ACE
+    ace.extend_from_slice(&((8 + everyone.len()) as u16).to_le_bytes());
+    ace.extend_from_slice(&KEY_ALL_ACCESS.to_le_bytes());
+    ace.extend_from_slice(&everyone);
+    let mut acl = vec![2u8, 0];
+    acl.extend_from_slice(&((8 + ace.len()) as u16).to_le_bytes());
+    acl.extend_from_slice(&1u16.to_le_bytes());
+    acl.extend_from_slice(&[0, 0]);
+    acl.extend_from_slice(&ace);
+
+    let dacl = 20;
+    let owner = dacl + acl.len();
+    let group = owner + admins.len();
+    let mut sd = vec![1u8, 0];
+    sd.extend_from_slice(&0x8004u16.to_le_bytes()); // SE_SELF_RELATIVE | SE_DACL_PRESENT
+    sd.extend_from_slice(&(owner as u32).to_le_bytes());
+    sd.extend_from_slice(&(group as u32).to_le_bytes());
+    sd.extend_from_slice(&0u32.to_le_bytes());
+    sd.extend_from_slice(&(dacl as u32).to_le_bytes());
+    sd.extend_from_slice(&acl);
+    sd.extend_from_slice(&admins);
+    sd.extend_from_slice(&system);
+    sd
+}
+
+fn put_u16(data: &mut [u8], off: usize, v: u16) {
+    data[off..off + 2].copy_from_slice(&v.to_le_bytes());
+}
+
+fn put_u32(data: &mut [u8], off: usize, v: u32) {
+    data[off..off + 4].copy_from_slice(&v.to_le_bytes());
+}
+
+fn put_u64(data: &mut [u8], off: usize, v: u64) {
+    data[off..off + 8].copy_from_slice(&v.to_le_bytes());
+}
+
+/// Cell allocator that never lets a cell cross a hive bin boundary
+struct Bins {
+    data: Vec<u8>,
+    bin_start: usize,
+    timestamp: u64,
+    first_key: u32,
+    last_key: u32,
+}
+
+impl Bins {
+    fn new(timestamp: u64) -> Bins {
+        let mut bins = Bins {
+            data: Vec::new(),
+            bin_start: 0,
+            timestamp,
+            first_key: NO_CELL,
+            last_key: NO_CELL,
+        };
+        bins.start_bin(0);
+        bins
+    }
+
+    fn start_bin(&mut self, min_cell: usize) {
+        self.bin_start = self.data.len();
+        let size = align(HBIN_HEADER_SIZE + min_cell, HBIN_ALIGNMENT);
+        let mut header = [0u8; HBIN_HEADER_SIZE];
+        header[..4].copy_from_slice(b"hbin");
+        put_u32(&mut header, 0x04, self.bin_start as u32);
+        put_u32(&mut header, 0x08, size as u32);
+        if self.bin_start == 0 {
+            put_u64(&mut header, 0x14, self.timestamp);
+        }
+        self.data.extend_from_slice(&header);
+    }
+
+    fn bin_end(&self) -> usize {
+        self.bin_start + read_u32(&self.data, self.bin_start + 8).unwrap_or_default() as usize
+    }
+
+    /// Close the current bin by marking the rest of it as a free cell
+    fn close_bin(&mut self) {
+        let end = self.bin_end();
+        let free = end - self.data.len();
+        if free > 0 {
+            let offset = self.data.len();
+            self.data.resize(end, 0);
+            put_u32(&mut self.data, offset, free as u32);
+        }
+    }
+
+    /// Allocate a cell for `size` bytes of data, returns its offset
+    fn alloc(&mut self, size: usize) -> u32 {
+        let size = align(size + 4, CELL_ALIGNMENT);
+        if self.data.len() + size > self.bin_end() {
+            self.close_bin();
+            self.start_bin(size);
+        }
+        let offset = self.data.len();
+        self.data.resize(offset + size, 0);
+        put_u32(&mut self.data, offset, (-(size as i32)) as u32);
+        offset as u32
+    }
+
+    fn cell_mut(&mut self, offset: u32) -> &mut [u8] {
+        let offset = offset as usize;
+        let size =
+            (read_u32(&self.data, offset).unwrap_or_default() as i32).unsigned_abs() as usize;
+        &mut self.data[offset + 4..offset + size]
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.close_bin();
+        self.data
+    }
+}
+
+fn align(v: usize, alignment: usize) -> usize {
+    (v + alignment - 1) & !(alignment - 1)
+}
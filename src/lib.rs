@@ -6,6 +6,13 @@
 
 //! Crate for accessing MS Windows registry
 //!
+//! Registry access itself (`RegKey`, iterators, transactions and serialization)
+//! is only available on Windows. The value layer (`RegValue`, `RegType`,
+//! `FromRegValue`/`ToRegValue` conversions and `RegKeyMetadata`) compiles
+//! on every platform, so registry data exported from Windows can be processed elsewhere.
+//! So do the offline hive reader in [`hive`] and the file formats in [`regfile`],
+//! [`policy`] and [`wine`].
+//!
 //!## Usage
 //!
 //!### Basic usage
@@ -19,9 +26,12 @@
 //!```no_run
 //!use std::io;
 //!use std::path::Path;
+//!# #[cfg(windows)]
 //!use winreg::enums::*;
+//!# #[cfg(windows)]
 //!use winreg::{HKCU, HKLM};
 //!
+//!# #[cfg(windows)]
 //!fn main() -> io::Result<()> {
 //!    println!("Reading some system info...");
 //!    let cur_ver = HKLM.open_subkey("SOFTWARE\\Microsoft\\Windows\\CurrentVersion")?;
@@ -80,14 +90,18 @@
 //!    });
 //!    Ok(())
 //!}
+//!# #[cfg(not(windows))]
+//!# fn main() {}
 //!```
 //!
 //!### Iterators
 //!
 //!```no_run
 //!use std::io;
+//!# #[cfg(windows)]
 //!use winreg::{HKCR, HKLM};
 //!
+//!# #[cfg(windows)]
 //!fn main() -> io::Result<()> {
 //!    println!("File extensions, registered in system:");
 //!    for i in HKCR.enum_keys().map(|x| x.unwrap())
@@ -103,35 +117,55 @@
 //!
 //!    Ok(())
 //!}
+//!# #[cfg(not(windows))]
+//!# fn main() {}
 //!```
 //!
+pub use crate::reg_key_metadata::RegKeyMetadata;
+pub use crate::reg_value::RegValue;
+
+pub mod backend;
+mod common;
+#[cfg(feature = "serialization-serde")]
+pub mod decoder;
+pub mod diff;
+#[cfg(feature = "serialization-serde")]
+pub mod encoder;
+pub mod enum_keys;
+pub mod enum_keys_os_string;
+pub mod enum_values;
+pub mod enum_values_os_string;
+pub mod enums;
+pub mod expand;
+pub mod hive;
+pub mod mem;
+pub mod mock;
+pub mod policy;
+pub mod reg_key_metadata;
+pub mod reg_value;
+pub mod regfile;
+pub mod resource;
+#[cfg(feature = "serialization-serde")]
+pub mod serde_with;
+#[cfg(feature = "serialization-serde")]
+pub mod snapshot;
+pub mod types;
+pub mod value;
+pub mod wine;
+
+pub use crate::backend::{RegistryBackend, RegistryEnum};
+pub use crate::enum_keys::EnumKeys;
+pub use crate::enum_keys_os_string::EnumKeysOsString;
+pub use crate::enum_values::EnumValues;
+pub use crate::enum_values_os_string::EnumValuesOsString;
+pub use crate::value::Value;
+
 cfg_if::cfg_if! {
-    if #[cfg(not(windows))] {
-        compile_error!("OS not supported. if your application is multi-platform, use `[target.'cfg(windows)'.dependencies] winreg = \"...\"`");
-    } else {
+    if #[cfg(windows)] {
         pub use crate::reg_key::{RegKey, HKEY, HKCC, HKCR, HKCU, HKLM, HKU};
-        pub use crate::enum_keys::EnumKeys;
-        pub use crate::enum_keys_os_string::EnumKeysOsString;
-        pub use crate::enum_values::EnumValues;
-        pub use crate::enum_values_os_string::EnumValuesOsString;
-        pub use crate::reg_key_metadata::RegKeyMetadata;
-        pub use crate::reg_value::RegValue;
 
-        mod common;
-        #[cfg(feature = "serialization-serde")]
-        pub mod decoder;
-        #[cfg(feature = "serialization-serde")]
-        pub mod encoder;
-        pub mod enums;
         pub mod reg_key;
-        pub mod reg_key_metadata;
-        pub mod reg_value;
-        pub mod enum_keys;
-        pub mod enum_keys_os_string;
-        pub mod enum_values;
-        pub mod enum_values_os_string;
         #[cfg(feature = "transactions")]
         pub mod transaction;
-        pub mod types;
     }
 }
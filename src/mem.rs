@@ -0,0 +1,459 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! In-memory registry that follows the rules of the Windows one,
+//! so code generic over `RegistryBackend` can be tested on every platform.
+//!
+//! * key and value names are case insensitive, but the case of new names is preserved
+//! * an empty value name is the `Default` value
+//! * subkeys are enumerated in the registry order, values in the order they were created
+//! * errors carry the Windows error codes `RegKey` returns: `ERROR_FILE_NOT_FOUND`
+//!   for missing keys and values, `ERROR_ACCESS_DENIED` when the handle lacks
+//!   the permissions or a key to delete still has subkeys, `ERROR_KEY_DELETED`
+//!   for handles of deleted keys, `ERROR_BAD_FILE_TYPE` from value conversions
+//! * every change updates the last write time of the key
+//!
+//!```
+//!use std::io;
+//!use winreg::enums::*;
+//!use winreg::mem::MemRegistry;
+//!use winreg::RegistryBackend;
+//!
+//!fn main() -> io::Result<()> {
+//!    let reg = MemRegistry::new();
+//!    let hkcu = reg.predef(HKEY_CURRENT_USER);
+//!    let (settings, disp) = hkcu.create_subkey("Software\\MyProduct\\Settings")?;
+//!    assert_eq!(disp, REG_CREATED_NEW_KEY);
+//!    settings.set_value("Port", &8080u32)?;
+//!
+//!    let settings = hkcu.open_subkey("SOFTWARE\\myproduct\\settings")?;
+//!    let port: u32 = settings.get_value("port")?;
+//!    assert_eq!(port, 8080);
+//!    Ok(())
+//!}
+//!```
+use crate::backend::{RegistryBackend, RegistryEnum};
+use crate::common::*;
+use crate::enums::*;
+use crate::reg_key_metadata::{FileTime, RegKeyMetadata};
+use crate::reg_value::RegValue;
+use crate::regfile::to_static;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use windows_sys::Win32::Foundation;
+use windows_sys::Win32::System::Registry::HKEY;
+
+/// Longest key name in UTF-16 code units
+const MAX_KEY_NAME_LEN: usize = 255;
+/// Longest value name in UTF-16 code units
+const MAX_VALUE_NAME_LEN: usize = 16383;
+/// `FILETIME` of the Unix epoch
+const UNIX_EPOCH_FILETIME: u64 = 116_444_736_000_000_000;
+
+#[derive(Debug)]
+struct Node {
+    name: String,
+    parent: Option<usize>,
+    subkeys: Vec<usize>,
+    values: Vec<(String, RegValue<'static>)>,
+    last_write_time: u64,
+    deleted: bool,
+}
+
+#[derive(Debug, Default)]
+struct Store {
+    nodes: Vec<Node>,
+    predefs: Vec<(usize, usize)>,
+    time: Option<u64>,
+}
+
+impl Store {
+    fn now(&self) -> u64 {
+        match self.time {
+            Some(time) => time,
+            None => {
+                let since = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                UNIX_EPOCH_FILETIME
+                    + since.as_secs() * 10_000_000
+                    + u64::from(since.subsec_nanos()) / 100
+            }
+        }
+    }
+
+    fn add_node(&mut self, name: &str, parent: Option<usize>) -> usize {
+        let now = self.now();
+        self.nodes.push(Node {
+            name: name.to_owned(),
+            parent,
+            subkeys: Vec::new(),
+            values: Vec::new(),
+            last_write_time: now,
+            deleted: false,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn node(&self, id: usize) -> io::Result<&Node> {
+        let node = &self.nodes[id];
+        if node.deleted {
+            return werr!(Foundation::ERROR_KEY_DELETED);
+        }
+        Ok(node)
+    }
+
+    fn node_mut(&mut self, id: usize) -> io::Result<&mut Node> {
+        self.node(id)?;
+        Ok(&mut self.nodes[id])
+    }
+
+    fn touch(&mut self, id: usize) {
+        self.nodes[id].last_write_time = self.now();
+    }
+
+    /// Position of the subkey `name` in the sorted subkey list of `id` or where to insert it
+    fn subkey_pos(&self, id: usize, name: &str) -> Result<usize, usize> {
+        self.nodes[id]
+            .subkeys
+            .binary_search_by(|&sub| compare_names(&self.nodes[sub].name, name))
+    }
+
+    fn find(&self, id: usize, names: &[String]) -> io::Result<usize> {
+        let mut id = id;
+        self.node(id)?;
+        for name in names {
+            id = match self.subkey_pos(id, name) {
+                Ok(pos) => self.nodes[id].subkeys[pos],
+                Err(_) => return werr!(Foundation::ERROR_FILE_NOT_FOUND),
+            };
+        }
+        Ok(id)
+    }
+
+    fn unlink(&mut self, id: usize) {
+        if let Some(parent) = self.nodes[id].parent {
+            self.nodes[parent].subkeys.retain(|&sub| sub != id);
+            self.touch(parent);
+        }
+    }
+
+    fn delete_tree(&mut self, id: usize) {
+        let subkeys = std::mem::take(&mut self.nodes[id].subkeys);
+        for sub in subkeys {
+            self.delete_tree(sub);
+        }
+        self.nodes[id].values.clear();
+        self.nodes[id].deleted = true;
+    }
+}
+
+/// Split a key path into names.
+/// The registry rejects paths starting with a backslash and names longer than 255 characters.
+fn split_path(path: &OsStr) -> io::Result<Vec<String>> {
+    let path = path.to_string_lossy();
+    if path.starts_with('\\') {
+        return werr!(Foundation::ERROR_BAD_PATHNAME);
+    }
+    let mut names = Vec::new();
+    for name in path.split('\\').filter(|n| !n.is_empty()) {
+        if name.encode_utf16().count() > MAX_KEY_NAME_LEN {
+            return werr!(Foundation::ERROR_INVALID_PARAMETER);
+        }
+        names.push(name.to_owned());
+    }
+    Ok(names)
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+/// Registry living in memory. Clones share the same keys and values.
+#[derive(Debug, Clone, Default)]
+pub struct MemRegistry {
+    store: Arc<Mutex<Store>>,
+}
+
+impl MemRegistry {
+    /// Create an empty registry
+    pub fn new() -> MemRegistry {
+        MemRegistry::default()
+    }
+
+    /// Open one of the predefined keys, e.g. `HKEY_CURRENT_USER`, with `KEY_ALL_ACCESS`
+    /// permissions. Predefined keys are empty until something is created in them
+    /// and cannot be deleted or renamed.
+    pub fn predef(&self, hkey: HKEY) -> MemKey {
+        let mut store = self.lock();
+        let hkey = hkey as usize;
+        let id = match store.predefs.iter().find(|(h, _)| *h == hkey) {
+            Some(&(_, id)) => id,
+            None => {
+                let id = store.add_node("", None);
+                store.predefs.push((hkey, id));
+                id
+            }
+        };
+        MemKey {
+            registry: self.clone(),
+            id,
+            perms: KEY_ALL_ACCESS,
+        }
+    }
+
+    /// Use `time` as the last write time of all changes from now on instead of the system clock,
+    /// so the results are reproducible.
+    pub fn set_time(&self, time: FileTime) {
+        self.lock().time = Some(time.as_u64());
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Store> {
+        self.store.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Handle of a key in a `MemRegistry`.
+/// Like a `RegKey` it is opened with some permissions and becomes unusable
+/// (`ERROR_KEY_DELETED`) when the key is deleted.
+#[derive(Debug, Clone)]
+pub struct MemKey {
+    registry: MemRegistry,
+    id: usize,
+    perms: u32,
+}
+
+impl MemKey {
+    /// Registry the key belongs to
+    pub fn registry(&self) -> &MemRegistry {
+        &self.registry
+    }
+
+    fn check_perms(&self, perms: u32) -> io::Result<()> {
+        if self.perms & perms != perms {
+            return werr!(Foundation::ERROR_ACCESS_DENIED);
+        }
+        Ok(())
+    }
+
+    fn open(&self, id: usize, perms: u32) -> MemKey {
+        MemKey {
+            registry: self.registry.clone(),
+            id,
+            perms,
+        }
+    }
+}
+
+impl RegistryEnum for MemKey {
+    fn enum_key(&self, index: u32) -> Option<io::Result<OsString>> {
+        if let Err(e) = self.check_perms(KEY_ENUMERATE_SUB_KEYS) {
+            return Some(Err(e));
+        }
+        let store = self.registry.lock();
+        match store.node(self.id) {
+            Ok(node) => node
+                .subkeys
+                .get(index as usize)
+                .map(|&sub| Ok(store.nodes[sub].name.clone().into())),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    fn enum_value(&self, index: u32) -> Option<io::Result<(OsString, RegValue<'static>)>> {
+        if let Err(e) = self.check_perms(KEY_QUERY_VALUE) {
+            return Some(Err(e));
+        }
+        let store = self.registry.lock();
+        match store.node(self.id) {
+            Ok(node) => node
+                .values
+                .get(index as usize)
+                .map(|(name, value)| Ok((name.clone().into(), to_static(value)))),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl RegistryBackend for MemKey {
+    fn open_subkey_with_flags<P: AsRef<OsStr>>(&self, path: P, perms: u32) -> io::Result<Self> {
+        let names = split_path(path.as_ref())?;
+        let id = self.registry.lock().find(self.id, &names)?;
+        Ok(self.open(id, perms))
+    }
+
+    fn create_subkey_with_flags<P: AsRef<OsStr>>(
+        &self,
+        path: P,
+        perms: u32,
+    ) -> io::Result<(Self, RegDisposition)> {
+        let names = split_path(path.as_ref())?;
+        let mut store = self.registry.lock();
+        let mut id = self.id;
+        let mut disp = REG_OPENED_EXISTING_KEY;
+        store.node(id)?;
+        for name in &names {
+            id = match store.subkey_pos(id, name) {
+                Ok(pos) => store.nodes[id].subkeys[pos],
+                Err(pos) => {
+                    self.check_perms(KEY_CREATE_SUB_KEY)?;
+                    let sub = store.add_node(name, Some(id));
+                    store.nodes[id].subkeys.insert(pos, sub);
+                    store.touch(id);
+                    disp = REG_CREATED_NEW_KEY;
+                    sub
+                }
+            };
+        }
+        Ok((self.open(id, perms), disp))
+    }
+
+    fn rename_subkey<ON: AsRef<OsStr>, NN: AsRef<OsStr>>(
+        &self,
+        old_name: ON,
+        new_name: NN,
+    ) -> io::Result<()> {
+        let names = split_path(old_name.as_ref())?;
+        let new_name = new_name.as_ref().to_string_lossy();
+        if new_name.is_empty()
+            || new_name.contains('\\')
+            || utf16_len(&new_name) as usize > MAX_KEY_NAME_LEN
+        {
+            return werr!(Foundation::ERROR_INVALID_PARAMETER);
+        }
+        let mut store = self.registry.lock();
+        let id = store.find(self.id, &names)?;
+        let parent = match store.nodes[id].parent {
+            Some(parent) => parent,
+            None => return werr!(Foundation::ERROR_ACCESS_DENIED),
+        };
+        if let Ok(pos) = store.subkey_pos(parent, &new_name) {
+            if store.nodes[parent].subkeys[pos] != id {
+                return werr!(Foundation::ERROR_ACCESS_DENIED);
+            }
+        }
+        store.nodes[parent].subkeys.retain(|&sub| sub != id);
+        store.nodes[id].name = new_name.into_owned();
+        let pos = match store.subkey_pos(parent, &store.nodes[id].name) {
+            Ok(pos) | Err(pos) => pos,
+        };
+        store.nodes[parent].subkeys.insert(pos, id);
+        store.touch(parent);
+        store.touch(id);
+        Ok(())
+    }
+
+    fn query_info(&self) -> io::Result<RegKeyMetadata> {
+        self.check_perms(KEY_QUERY_VALUE)?;
+        let store = self.registry.lock();
+        let node = store.node(self.id)?;
+        Ok(RegKeyMetadata {
+            sub_keys: node.subkeys.len() as u32,
+            max_sub_key_len: node
+                .subkeys
+                .iter()
+                .map(|&sub| utf16_len(&store.nodes[sub].name))
+                .max()
+                .unwrap_or(0),
+            max_class_len: 0,
+            values: node.values.len() as u32,
+            max_value_name_len: node
+                .values
+                .iter()
+                .map(|(name, _)| utf16_len(name))
+                .max()
+                .unwrap_or(0),
+            max_value_len: node
+                .values
+                .iter()
+                .map(|(_, value)| value.bytes.len() as u32)
+                .max()
+                .unwrap_or(0),
+            last_write_time: FileTime::from_u64(node.last_write_time),
+        })
+    }
+
+    fn delete_subkey<P: AsRef<OsStr>>(&self, path: P) -> io::Result<()> {
+        let names = split_path(path.as_ref())?;
+        let mut store = self.registry.lock();
+        let id = store.find(self.id, &names)?;
+        let node = &store.nodes[id];
+        if node.parent.is_none() || !node.subkeys.is_empty() {
+            return werr!(Foundation::ERROR_ACCESS_DENIED);
+        }
+        store.unlink(id);
+        store.delete_tree(id);
+        Ok(())
+    }
+
+    fn delete_subkey_all<P: AsRef<OsStr>>(&self, path: P) -> io::Result<()> {
+        self.check_perms(DELETE | KEY_ENUMERATE_SUB_KEYS | KEY_QUERY_VALUE)?;
+        let names = split_path(path.as_ref())?;
+        let mut store = self.registry.lock();
+        let id = store.find(self.id, &names)?;
+        if names.is_empty() {
+            let subkeys = std::mem::take(&mut store.nodes[id].subkeys);
+            for sub in subkeys {
+                store.delete_tree(sub);
+            }
+            store.nodes[id].values.clear();
+            store.touch(id);
+        } else {
+            store.unlink(id);
+            store.delete_tree(id);
+        }
+        Ok(())
+    }
+
+    fn get_raw_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<RegValue<'static>> {
+        self.check_perms(KEY_QUERY_VALUE)?;
+        let name = name.as_ref().to_string_lossy();
+        let store = self.registry.lock();
+        match store
+            .node(self.id)?
+            .values
+            .iter()
+            .find(|(n, _)| names_eq(n, &name))
+        {
+            Some((_, value)) => Ok(to_static(value)),
+            None => werr!(Foundation::ERROR_FILE_NOT_FOUND),
+        }
+    }
+
+    fn set_raw_value<N: AsRef<OsStr>>(&self, name: N, value: &RegValue) -> io::Result<()> {
+        self.check_perms(KEY_SET_VALUE)?;
+        let name = name.as_ref().to_string_lossy();
+        if utf16_len(&name) as usize > MAX_VALUE_NAME_LEN {
+            return werr!(Foundation::ERROR_INVALID_PARAMETER);
+        }
+        let mut store = self.registry.lock();
+        let node = store.node_mut(self.id)?;
+        let value = to_static(value);
+        match node.values.iter_mut().find(|(n, _)| names_eq(n, &name)) {
+            Some(v) => v.1 = value,
+            None => node.values.push((name.into_owned(), value)),
+        }
+        store.touch(self.id);
+        Ok(())
+    }
+
+    fn delete_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<()> {
+        self.check_perms(KEY_SET_VALUE)?;
+        let name = name.as_ref().to_string_lossy();
+        let mut store = self.registry.lock();
+        let node = store.node_mut(self.id)?;
+        match node.values.iter().position(|(n, _)| names_eq(n, &name)) {
+            Some(pos) => {
+                node.values.remove(pos);
+                store.touch(self.id);
+                Ok(())
+            }
+            None => werr!(Foundation::ERROR_FILE_NOT_FOUND),
+        }
+    }
+}
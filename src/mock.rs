@@ -0,0 +1,336 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Registry that fails on demand, for testing error handling.
+//!
+//! `MockRegistry` wraps keys of another `RegistryBackend` (a `MemRegistry` by default),
+//! records every call made through them and fails the calls that match
+//! an injected `Fault` with a Windows error code, the way `RegKey` would.
+//! Paths are relative to the wrapped key and compared ignoring case.
+//! An `ERROR_NO_MORE_ITEMS` fault ends the enumeration of keys or values early.
+//!
+//!```
+//!use windows_sys::Win32::Foundation;
+//!use winreg::enums::*;
+//!use winreg::mock::{Fault, MockRegistry, Op};
+//!use winreg::RegistryBackend;
+//!
+//!let mock = MockRegistry::new();
+//!let hkcu = mock.predef(HKEY_CURRENT_USER);
+//!let (key, _) = hkcu.create_subkey("Software\\X").unwrap();
+//!for i in 0..5u32 {
+//!    key.set_value(format!("v{}", i), &i).unwrap();
+//!}
+//!
+//!// the third `enum_value` on `Software\X` returns ERROR_NO_MORE_ITEMS
+//!mock.inject(Fault::new(Op::EnumValue, "Software\\X", Foundation::ERROR_NO_MORE_ITEMS).nth(3));
+//!assert_eq!(key.enum_values().count(), 2);
+//!assert_eq!(key.enum_values().count(), 5);
+//!```
+use crate::backend::{RegistryBackend, RegistryEnum};
+use crate::common::*;
+use crate::enums::*;
+use crate::mem::{MemKey, MemRegistry};
+use crate::reg_key_metadata::RegKeyMetadata;
+use crate::reg_value::RegValue;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use windows_sys::Win32::Foundation;
+use windows_sys::Win32::System::Registry::HKEY;
+
+/// Operations of `RegistryBackend` that can be recorded and failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    OpenKey,
+    CreateKey,
+    RenameKey,
+    QueryInfo,
+    EnumKey,
+    EnumValue,
+    DeleteKey,
+    DeleteTree,
+    GetValue,
+    SetValue,
+    DeleteValue,
+}
+
+/// A recorded call.
+/// `path` is the key the operation works on: the opened, created, renamed or
+/// deleted subkey, or the key itself for value operations, `query_info` and enumeration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub op: Op,
+    pub path: String,
+    /// Value name, or new key name for `RenameKey`
+    pub name: Option<String>,
+    /// Enumeration index
+    pub index: Option<u32>,
+    /// Error code if the call failed with an injected fault
+    pub fault: Option<u32>,
+}
+
+/// Scripted failure of the calls of `op` on `path`
+#[derive(Debug, Clone)]
+pub struct Fault {
+    op: Op,
+    path: String,
+    error: u32,
+    nth: Option<usize>,
+    seen: usize,
+}
+
+impl Fault {
+    /// Fail every matching call with the Windows error code `error`
+    pub fn new<P: Into<String>>(op: Op, path: P, error: u32) -> Fault {
+        Fault {
+            op,
+            path: path.into(),
+            error,
+            nth: None,
+            seen: 0,
+        }
+    }
+
+    /// Fail only the `n`-th matching call, counting from 1
+    pub fn nth(mut self, n: usize) -> Fault {
+        self.nth = Some(n);
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    faults: Vec<Fault>,
+    calls: Vec<Call>,
+}
+
+/// Shared state of mocked keys: injected faults and recorded calls.
+/// Clones share the state.
+#[derive(Debug, Clone, Default)]
+pub struct MockRegistry {
+    registry: MemRegistry,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockRegistry {
+    /// Mock backed by an empty `MemRegistry`
+    pub fn new() -> MockRegistry {
+        MockRegistry::default()
+    }
+
+    /// Mock backed by `registry`, e.g. one filled with test data
+    pub fn from_registry(registry: MemRegistry) -> MockRegistry {
+        MockRegistry {
+            registry,
+            state: Arc::default(),
+        }
+    }
+
+    /// The `MemRegistry` behind `predef()`, changes made through it are not recorded
+    pub fn registry(&self) -> &MemRegistry {
+        &self.registry
+    }
+
+    /// Predefined key of the backing `MemRegistry`, paths of calls are relative to it
+    pub fn predef(&self, hkey: HKEY) -> MockKey<MemKey> {
+        self.wrap(self.registry.predef(hkey))
+    }
+
+    /// Mock any key, e.g. a `RegKey`. Paths of calls are relative to it.
+    pub fn wrap<K: RegistryBackend>(&self, key: K) -> MockKey<K> {
+        MockKey {
+            inner: key,
+            path: String::new(),
+            state: self.state.clone(),
+        }
+    }
+
+    /// Add a fault. When several faults match a call, the first one injected wins.
+    pub fn inject(&self, fault: Fault) {
+        self.lock().faults.push(fault);
+    }
+
+    /// Remove all faults
+    pub fn clear_faults(&self) {
+        self.lock().faults.clear();
+    }
+
+    /// Calls made so far, in order
+    pub fn calls(&self) -> Vec<Call> {
+        self.lock().calls.clone()
+    }
+
+    /// Calls of `op` made so far
+    pub fn calls_of(&self, op: Op) -> Vec<Call> {
+        self.lock()
+            .calls
+            .iter()
+            .filter(|c| c.op == op)
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear_calls(&self) {
+        self.lock().calls.clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Key that records its calls and fails them as scripted
+#[derive(Debug)]
+pub struct MockKey<K> {
+    inner: K,
+    path: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl<K> MockKey<K> {
+    /// The wrapped key
+    pub fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    /// Path of the key relative to the wrapped one
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn join(&self, sub: &OsStr) -> String {
+        let sub = sub.to_string_lossy();
+        let sub = sub.trim_matches('\\');
+        match (self.path.is_empty(), sub.is_empty()) {
+            (_, true) => self.path.clone(),
+            (true, false) => sub.to_owned(),
+            (false, false) => format!("{}\\{}", self.path, sub),
+        }
+    }
+
+    fn child(&self, inner: K, path: String) -> MockKey<K> {
+        MockKey {
+            inner,
+            path,
+            state: self.state.clone(),
+        }
+    }
+
+    /// Record the call and return the error of the first fault that fires
+    fn call(&self, op: Op, path: &str, name: Option<&OsStr>, index: Option<u32>) -> Option<u32> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut error = None;
+        for fault in state.faults.iter_mut() {
+            if fault.op != op || !names_eq(fault.path.trim_matches('\\'), path) {
+                continue;
+            }
+            fault.seen += 1;
+            if error.is_none() && fault.nth.map_or(true, |n| n == fault.seen) {
+                error = Some(fault.error);
+            }
+        }
+        state.calls.push(Call {
+            op,
+            path: path.to_owned(),
+            name: name.map(|n| n.to_string_lossy().into_owned()),
+            index,
+            fault: error,
+        });
+        error
+    }
+
+    fn check(&self, op: Op, path: &str, name: Option<&OsStr>) -> io::Result<()> {
+        match self.call(op, path, name, None) {
+            Some(error) => werr!(error),
+            None => Ok(()),
+        }
+    }
+
+    fn check_enum(&self, op: Op, index: u32) -> Option<io::Result<()>> {
+        match self.call(op, &self.path, None, Some(index)) {
+            Some(Foundation::ERROR_NO_MORE_ITEMS) => None,
+            Some(error) => Some(werr!(error)),
+            None => Some(Ok(())),
+        }
+    }
+}
+
+impl<K: RegistryBackend> RegistryEnum for MockKey<K> {
+    fn enum_key(&self, index: u32) -> Option<io::Result<OsString>> {
+        match self.check_enum(Op::EnumKey, index)? {
+            Ok(()) => self.inner.enum_key(index),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    fn enum_value(&self, index: u32) -> Option<io::Result<(OsString, RegValue<'static>)>> {
+        match self.check_enum(Op::EnumValue, index)? {
+            Ok(()) => self.inner.enum_value(index),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl<K: RegistryBackend> RegistryBackend for MockKey<K> {
+    fn open_subkey_with_flags<P: AsRef<OsStr>>(&self, path: P, perms: u32) -> io::Result<Self> {
+        let full = self.join(path.as_ref());
+        self.check(Op::OpenKey, &full, None)?;
+        let inner = self.inner.open_subkey_with_flags(path, perms)?;
+        Ok(self.child(inner, full))
+    }
+
+    fn create_subkey_with_flags<P: AsRef<OsStr>>(
+        &self,
+        path: P,
+        perms: u32,
+    ) -> io::Result<(Self, RegDisposition)> {
+        let full = self.join(path.as_ref());
+        self.check(Op::CreateKey, &full, None)?;
+        let (inner, disp) = self.inner.create_subkey_with_flags(path, perms)?;
+        Ok((self.child(inner, full), disp))
+    }
+
+    fn rename_subkey<ON: AsRef<OsStr>, NN: AsRef<OsStr>>(
+        &self,
+        old_name: ON,
+        new_name: NN,
+    ) -> io::Result<()> {
+        let full = self.join(old_name.as_ref());
+        self.check(Op::RenameKey, &full, Some(new_name.as_ref()))?;
+        self.inner.rename_subkey(old_name, new_name)
+    }
+
+    fn query_info(&self) -> io::Result<RegKeyMetadata> {
+        self.check(Op::QueryInfo, &self.path, None)?;
+        self.inner.query_info()
+    }
+
+    fn delete_subkey<P: AsRef<OsStr>>(&self, path: P) -> io::Result<()> {
+        self.check(Op::DeleteKey, &self.join(path.as_ref()), None)?;
+        self.inner.delete_subkey(path)
+    }
+
+    fn delete_subkey_all<P: AsRef<OsStr>>(&self, path: P) -> io::Result<()> {
+        self.check(Op::DeleteTree, &self.join(path.as_ref()), None)?;
+        self.inner.delete_subkey_all(path)
+    }
+
+    fn get_raw_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<RegValue<'static>> {
+        self.check(Op::GetValue, &self.path, Some(name.as_ref()))?;
+        self.inner.get_raw_value(name)
+    }
+
+    fn set_raw_value<N: AsRef<OsStr>>(&self, name: N, value: &RegValue) -> io::Result<()> {
+        self.check(Op::SetValue, &self.path, Some(name.as_ref()))?;
+        self.inner.set_raw_value(name, value)
+    }
+
+    fn delete_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<()> {
+        self.check(Op::DeleteValue, &self.path, Some(name.as_ref()))?;
+        self.inner.delete_value(name)
+    }
+}
@@ -0,0 +1,416 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parser and writer for Group Policy `Registry.pol` files (`PReg` format).
+//!
+//! Key paths in a policy file are relative to the hive the file is meant for:
+//! `HKEY_LOCAL_MACHINE` for `Machine\Registry.pol` and `HKEY_CURRENT_USER`
+//! for `User\Registry.pol`. Value names starting with `**` are directives:
+//!
+//! * `**del.Name` deletes the value `Name`
+//! * `**delvals.` deletes all values of the key
+//! * `**DeleteValues` deletes values listed in its data, separated by `;`
+//! * `**DeleteKeys` deletes subkeys listed in its data, separated by `;`
+//!
+//! Other directives (`**SecureKey`, `**soft.`) are kept in the file but ignored when applied.
+//!
+//!```no_run
+//!use std::io;
+//!use winreg::policy::PolFile;
+//!
+//!fn main() -> io::Result<()> {
+//!    let file = PolFile::open("Registry.pol")?;
+//!    for entry in &file.entries {
+//!        println!("{}\\{} = {:?}", entry.key, entry.name, entry.value);
+//!    }
+//!    Ok(())
+//!}
+//!```
+use crate::common::*;
+use crate::enums::*;
+use crate::reg_value::RegValue;
+use crate::regfile::{to_static, ApplyResult, ApplyTarget, Change};
+use crate::types::ToRegValue;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const SIGNATURE: &[u8; 4] = b"PReg";
+const VERSION: u32 = 1;
+
+const DELETE_VALUE: &str = "**del.";
+const DELETE_ALL_VALUES: &str = "**delvals.";
+const DELETE_VALUES: &str = "**DeleteValues";
+const DELETE_KEYS: &str = "**DeleteKeys";
+
+/// Content of a `Registry.pol` file
+#[derive(Debug, Default)]
+pub struct PolFile {
+    pub entries: Vec<PolEntry>,
+}
+
+/// A single `[key;value;type;size;data]` record, directives included
+#[derive(Debug)]
+pub struct PolEntry {
+    /// Path relative to the hive root
+    pub key: String,
+    /// Value name or directive, empty for the `Default` value
+    pub name: String,
+    pub value: RegValue<'static>,
+}
+
+fn parse_error<T>(offset: usize, msg: &str) -> io::Result<T> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("offset {}: {}", offset, msg),
+    ))
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn u16(&mut self) -> io::Result<u16> {
+        match self.data.get(self.pos..self.pos + 2) {
+            Some(b) => {
+                self.pos += 2;
+                Ok(u16::from_le_bytes([b[0], b[1]]))
+            }
+            None => parse_error(self.pos, "unexpected end of file"),
+        }
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        match self.data.get(self.pos..self.pos + 4) {
+            Some(b) => {
+                self.pos += 4;
+                Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            }
+            None => parse_error(self.pos, "unexpected end of file"),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> io::Result<()> {
+        let pos = self.pos;
+        if self.u16()? != c as u16 {
+            return parse_error(pos, &format!("expected '{}'", c));
+        }
+        Ok(())
+    }
+
+    /// Null terminated UTF-16 string
+    fn string(&mut self) -> io::Result<String> {
+        let pos = self.pos;
+        let mut chars = Vec::new();
+        loop {
+            match self.u16()? {
+                0 => break,
+                c => chars.push(c),
+            }
+        }
+        String::from_utf16(&chars).or_else(|_| parse_error(pos, "invalid UTF-16"))
+    }
+}
+
+fn put_string(out: &mut Vec<u8>, s: &str) {
+    for c in s.encode_utf16().chain(Some(0)) {
+        out.extend_from_slice(&c.to_le_bytes());
+    }
+}
+
+fn put_char(out: &mut Vec<u8>, c: char) {
+    out.extend_from_slice(&(c as u16).to_le_bytes());
+}
+
+/// `;` separated list stored in a `REG_SZ` value
+fn split_list(value: &RegValue) -> Vec<String> {
+    let chars = v8_to_v16(&value.bytes);
+    let len = chars.iter().position(|c| *c == 0).unwrap_or(chars.len());
+    String::from_utf16_lossy(&chars[..len])
+        .split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    match s.get(..prefix.len()) {
+        Some(head) if head.eq_ignore_ascii_case(prefix) => Some(&s[prefix.len()..]),
+        _ => None,
+    }
+}
+
+/// Data Group Policy editors store in deletion directives
+fn directive_value() -> RegValue<'static> {
+    RegValue {
+        bytes: v16_to_v8(&[' ' as u16, 0]).into(),
+        vtype: REG_SZ,
+    }
+}
+
+impl PolFile {
+    pub fn new() -> PolFile {
+        PolFile::default()
+    }
+
+    /// Read and parse a `Registry.pol` file
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<PolFile> {
+        PolFile::parse(&fs::read(path)?)
+    }
+
+    /// Parse raw content of a `Registry.pol` file.
+    /// Fails with `ErrorKind::InvalidData` and a byte offset on format errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// use winreg::policy::PolFile;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut file = PolFile::new();
+    /// file.set_value("Software\\Policies\\MyProduct", "Enabled", &1u32);
+    /// let parsed = PolFile::parse(&file.to_bytes())?;
+    /// assert_eq!(parsed.entries[0].name, "Enabled");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(data: &[u8]) -> io::Result<PolFile> {
+        if data.len() < 8 || &data[..4] != SIGNATURE {
+            return parse_error(0, "missing PReg signature");
+        }
+        let mut r = Reader { data, pos: 4 };
+        if r.u32()? != VERSION {
+            return parse_error(4, "unsupported version");
+        }
+        let mut file = PolFile::new();
+        while r.pos < data.len() {
+            r.expect('[')?;
+            let key = r.string()?;
+            r.expect(';')?;
+            let name = r.string()?;
+            r.expect(';')?;
+            let vtype = RegType::from_raw(r.u32()?);
+            r.expect(';')?;
+            let size = r.u32()? as usize;
+            r.expect(';')?;
+            let bytes = match r.pos.checked_add(size).and_then(|end| data.get(r.pos..end)) {
+                Some(bytes) => bytes.to_vec(),
+                None => return parse_error(r.pos, "data is out of bounds"),
+            };
+            r.pos += size;
+            r.expect(']')?;
+            file.entries.push(PolEntry {
+                key,
+                name,
+                value: RegValue {
+                    bytes: bytes.into(),
+                    vtype,
+                },
+            });
+        }
+        Ok(file)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = SIGNATURE.to_vec();
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        for entry in &self.entries {
+            put_char(&mut out, '[');
+            put_string(&mut out, &entry.key);
+            put_char(&mut out, ';');
+            put_string(&mut out, &entry.name);
+            put_char(&mut out, ';');
+            out.extend_from_slice(&entry.value.vtype.raw().to_le_bytes());
+            put_char(&mut out, ';');
+            out.extend_from_slice(&(entry.value.bytes.len() as u32).to_le_bytes());
+            put_char(&mut out, ';');
+            out.extend_from_slice(&entry.value.bytes);
+            put_char(&mut out, ']');
+        }
+        out
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    fn push<K: AsRef<OsStr>>(&mut self, key: K, name: String, value: RegValue<'static>) {
+        self.entries.push(PolEntry {
+            key: key.as_ref().to_string_lossy().into_owned(),
+            name,
+            value,
+        })
+    }
+
+    /// Convert a value from a rust type and add a record setting it.
+    /// Will set the `Default` value if `name` is an empty string.
+    pub fn set_value<T: ToRegValue, K: AsRef<OsStr>, N: AsRef<OsStr>>(
+        &mut self,
+        key: K,
+        name: N,
+        value: &T,
+    ) {
+        self.set_raw_value(key, name, &value.to_reg_value())
+    }
+
+    pub fn set_raw_value<K: AsRef<OsStr>, N: AsRef<OsStr>>(
+        &mut self,
+        key: K,
+        name: N,
+        value: &RegValue,
+    ) {
+        let name = name.as_ref().to_string_lossy().into_owned();
+        self.push(key, name, to_static(value))
+    }
+
+    /// Add a `**del.` record
+    pub fn delete_value<K: AsRef<OsStr>, N: AsRef<OsStr>>(&mut self, key: K, name: N) {
+        let name = format!("{}{}", DELETE_VALUE, name.as_ref().to_string_lossy());
+        self.push(key, name, directive_value())
+    }
+
+    /// Add a `**delvals.` record
+    pub fn delete_all_values<K: AsRef<OsStr>>(&mut self, key: K) {
+        self.push(key, DELETE_ALL_VALUES.to_owned(), directive_value())
+    }
+
+    /// Changes the file makes, in file order. `root` is the hive the file is meant for,
+    /// e.g. `HKEY_LOCAL_MACHINE`, and is prepended to every key path.
+    /// A key is created before the first value set in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winreg::policy::PolFile;
+    /// use winreg::regfile::Change;
+    ///
+    /// let mut file = PolFile::new();
+    /// file.delete_value("Software\\Policies\\MyProduct", "Proxy");
+    /// assert_eq!(
+    ///     file.changes("HKEY_CURRENT_USER"),
+    ///     vec![Change::DeleteValue(
+    ///         "HKEY_CURRENT_USER\\Software\\Policies\\MyProduct".to_owned(),
+    ///         "Proxy".to_owned()
+    ///     )]
+    /// );
+    /// ```
+    pub fn changes(&self, root: &str) -> Vec<Change> {
+        let root = root.trim_end_matches('\\');
+        let mut changes = Vec::new();
+        let mut created: Option<&str> = None;
+        for entry in &self.entries {
+            let path = if root.is_empty() {
+                entry.key.clone()
+            } else if entry.key.is_empty() {
+                root.to_owned()
+            } else {
+                format!("{}\\{}", root, entry.key)
+            };
+            let name = entry.name.as_str();
+            if let Some(value) = strip_prefix_ignore_case(name, DELETE_VALUE) {
+                changes.push(Change::DeleteValue(path, value.to_owned()));
+            } else if name.eq_ignore_ascii_case(DELETE_ALL_VALUES) {
+                changes.push(Change::DeleteAllValues(path));
+            } else if name.eq_ignore_ascii_case(DELETE_VALUES) {
+                for value in split_list(&entry.value) {
+                    changes.push(Change::DeleteValue(path.clone(), value));
+                }
+            } else if name.eq_ignore_ascii_case(DELETE_KEYS) {
+                for key in split_list(&entry.value) {
+                    changes.push(Change::DeleteKey(format!("{}\\{}", path, key)));
+                }
+            } else if name.starts_with("**") {
+                continue;
+            } else {
+                let same_key = match created {
+                    Some(key) => names_eq(key, &entry.key),
+                    None => false,
+                };
+                if !same_key {
+                    changes.push(Change::CreateKey(path.clone()));
+                    created = Some(&entry.key);
+                }
+                if !name.is_empty()
+                    || entry.value.vtype != REG_NONE
+                    || !entry.value.bytes.is_empty()
+                {
+                    changes.push(Change::SetValue(
+                        path,
+                        name.to_owned(),
+                        to_static(&entry.value),
+                    ));
+                }
+                continue;
+            }
+            created = None;
+        }
+        changes
+    }
+
+    /// Apply the file to a target, see `changes` for the meaning of `root`.
+    /// Doesn't stop on errors, returns a result for every change.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use winreg::policy::PolFile;
+    /// use winreg::regfile::DryRun;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let file = PolFile::open("Machine\\Registry.pol")?;
+    /// let mut dry_run = DryRun::default();
+    /// file.apply("HKEY_LOCAL_MACHINE", &mut dry_run);
+    /// for change in &dry_run.changes {
+    ///     println!("{:?}", change);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn apply<T: ApplyTarget>(&self, root: &str, target: &mut T) -> Vec<ApplyResult> {
+        self.changes(root)
+            .into_iter()
+            .map(|change| {
+                let result = match change {
+                    Change::CreateKey(ref path) => target.create_key(path),
+                    Change::DeleteKey(ref path) => target.delete_key(path),
+                    Change::SetValue(ref path, ref name, ref value) => {
+                        target.set_value(path, name, value)
+                    }
+                    Change::DeleteValue(ref path, ref name) => target.delete_value(path, name),
+                    Change::DeleteAllValues(ref path) => target.delete_all_values(path),
+                };
+                ApplyResult { change, result }
+            })
+            .collect()
+    }
+
+    /// Apply the file to the tree under `key` instead of a hive root,
+    /// e.g. to a sandbox or to a loaded `NTUSER.DAT`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use winreg::policy::PolFile;
+    /// use winreg::HKCU;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let (sandbox, _) = HKCU.create_subkey("Software\\Sandbox")?;
+    /// PolFile::open("User\\Registry.pol")?.apply_to_key(&sandbox)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(windows)]
+    pub fn apply_to_key(&self, key: &crate::reg_key::RegKey) -> io::Result<Vec<ApplyResult>> {
+        const ROOT: &str = "HKEY_LOCAL_MACHINE";
+        let key = key.open_subkey_with_flags("", KEY_ALL_ACCESS)?;
+        let mut target = crate::regfile::RegistryTarget::new().with_root(ROOT, key)?;
+        Ok(self.apply(ROOT, &mut target))
+    }
+}
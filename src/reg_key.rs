@@ -3,20 +3,23 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+use crate::backend::{RegistryBackend, RegistryEnum};
 use crate::common::*;
 use crate::enum_keys::EnumKeys;
 use crate::enum_keys_os_string::EnumKeysOsString;
 use crate::enum_values::EnumValues;
 use crate::enum_values_os_string::EnumValuesOsString;
 use crate::enums::{self, *};
+use crate::expand::{expand_value, EnvSource};
 use crate::reg_key_metadata::RegKeyMetadata;
 use crate::reg_value::RegValue;
 #[cfg(feature = "transactions")]
 use crate::transaction::Transaction;
 use crate::types::{FromRegValue, ToRegValue};
+use crate::value::Value;
 use std::default::Default;
 use std::ffi::{OsStr, OsString};
-use std::io;
+use std::io::{self, Write};
 use std::mem::transmute;
 use std::os::windows::ffi::OsStringExt;
 use std::ptr;
@@ -419,6 +422,29 @@ impl RegKey {
         }
     }
 
+    /// Write the key with all its subkeys and values as a `.reg` file
+    /// in the `Windows Registry Editor Version 5.00` format, the same one `regedit` exports.
+    /// `path` is the full name of this key in the file, starting with a predefined key name.
+    /// Subkeys are sorted by name, the same way the registry sorts them,
+    /// values are written in enumeration order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use std::fs::File;
+    /// # use winreg::HKCU;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let key = HKCU.open_subkey("Software\\MyProduct")?;
+    /// let file = File::create("my_product.reg")?;
+    /// key.export_reg("HKEY_CURRENT_USER\\Software\\MyProduct", file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn export_reg<P: AsRef<OsStr>, W: Write>(&self, path: P, w: W) -> io::Result<()> {
+        crate::regfile::export(self, &path.as_ref().to_string_lossy())?.write(w)
+    }
+
     pub fn query_info(&self) -> io::Result<RegKeyMetadata> {
         let mut info: RegKeyMetadata = RegKeyMetadata::default();
         match unsafe {
@@ -455,7 +481,7 @@ impl RegKey {
     ///     println!("{}", i);
     /// }
     /// ```
-    pub const fn enum_keys(&self) -> EnumKeys<'_> {
+    pub const fn enum_keys(&self) -> EnumKeys<'_, RegKey> {
         EnumKeys {
             key: self,
             index: 0,
@@ -479,7 +505,7 @@ impl RegKey {
     /// # Ok(())
     /// # }
     /// ```
-    pub const fn enum_keys_os_string(&self) -> EnumKeysOsString<'_> {
+    pub const fn enum_keys_os_string(&self) -> EnumKeysOsString<'_, RegKey> {
         EnumKeysOsString {
             key: self,
             index: 0,
@@ -502,7 +528,7 @@ impl RegKey {
     /// # Ok(())
     /// # }
     /// ```
-    pub const fn enum_values(&self) -> EnumValues<'_> {
+    pub const fn enum_values(&self) -> EnumValues<'_, RegKey> {
         EnumValues {
             key: self,
             index: 0,
@@ -525,7 +551,7 @@ impl RegKey {
     /// # Ok(())
     /// # }
     /// ```
-    pub const fn enum_values_os_string(&self) -> EnumValuesOsString<'_> {
+    pub const fn enum_values_os_string(&self) -> EnumValuesOsString<'_, RegKey> {
         EnumValuesOsString {
             key: self,
             index: 0,
@@ -707,11 +733,7 @@ impl RegKey {
                     unsafe {
                         buf.set_len(buf_len as usize);
                     }
-                    // minimal check before transmute to RegType
-                    if buf_type > Registry::REG_QWORD {
-                        return werr!(Foundation::ERROR_BAD_FILE_TYPE);
-                    }
-                    let t: RegType = unsafe { transmute(buf_type as u8) };
+                    let t = RegType::from_raw(buf_type);
                     return Ok(RegValue {
                         bytes: buf.into(),
                         vtype: t,
@@ -745,6 +767,73 @@ impl RegKey {
         self.set_raw_value(name, &value.to_reg_value())
     }
 
+    /// Get a value decoded according to its type.
+    /// Fails with `ERROR_INVALID_DATA` if the value can't be decoded losslessly, see `Value`.
+    /// Will get the `Default` value if `name` is an empty string.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use winreg::{HKCU, Value};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let settings = HKCU.open_subkey("Software\\MyProduct\\Settings")?;
+    /// match settings.get_typed_value("path")? {
+    ///     Value::Sz(path) => println!("path = {}", path),
+    ///     Value::ExpandSz(path) => println!("path = {} (not expanded)", path),
+    ///     other => println!("unexpected value {:?}", other),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_typed_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<Value> {
+        self.get_value(name)
+    }
+
+    /// Write a typed value with its type.
+    /// Will set the `Default` value if `name` is an empty string.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use winreg::{HKCU, Value};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let (settings, disp) = HKCU.create_subkey("Software\\MyProduct\\Settings")?;
+    /// settings.set_typed_value("path", &Value::ExpandSz("%ProgramFiles%\\MyProduct".to_owned()))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_typed_value<N: AsRef<OsStr>>(&self, name: N, value: &Value) -> io::Result<()> {
+        self.set_value(name, value)
+    }
+
+    /// Get a `REG_SZ` or `REG_EXPAND_SZ` value as a `String`, expanding `%VAR%` references
+    /// in `REG_EXPAND_SZ` values with variables from `env`. Other value types fail
+    /// with `ERROR_BAD_FILE_TYPE`.
+    /// Will get the `Default` value if `name` is an empty string.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use winreg::expand::{ProcessEnv, RegistryEnv};
+    /// use winreg::HKCU;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let folders = HKCU.open_subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\User Shell Folders")?;
+    /// let documents = folders.get_expanded_value("Personal", ProcessEnv)?;
+    /// let path = HKCU.open_subkey("Environment")?.get_expanded_value("Path", RegistryEnv::system()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_expanded_value<N: AsRef<OsStr>, E: EnvSource>(
+        &self,
+        name: N,
+        env: E,
+    ) -> io::Result<String> {
+        expand_value(&self.get_raw_value(name)?, env)
+    }
+
     /// Write raw bytes from `RegValue` struct to a registry value.
     /// Will set the `Default` value if `name` is an empty string.
     ///
@@ -765,7 +854,7 @@ impl RegKey {
     /// ```
     pub fn set_raw_value<N: AsRef<OsStr>>(&self, name: N, value: &RegValue) -> io::Result<()> {
         let c_name = to_utf16(name);
-        let t = value.vtype.clone() as u32;
+        let t = value.vtype.raw();
         match unsafe {
             Registry::RegSetValueExW(
                 self.hkey,
@@ -1050,6 +1139,121 @@ impl RegKey {
         T::deserialize(&mut decoder)
     }
 
+    /// Save `Encodable` type to a registry key like `encode`,
+    /// with the number formats of `options`.
+    /// Part of `serialization-serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use serde_derive::Serialize;
+    /// use winreg::encoder::{EncoderOptions, FloatFormat, SignedFormat};
+    /// use winreg::HKCU;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Offset {
+    ///     dx: i32,
+    ///     scale: f64,
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let options = EncoderOptions::new()
+    ///     .signed(SignedFormat::TwosComplement)
+    ///     .floats(FloatFormat::Bits);
+    /// let key = HKCU.open_subkey("Software\\MyProduct\\Offset")?;
+    /// // `dx` becomes a REG_DWORD, `scale` a REG_QWORD
+    /// key.encode_with_options(&Offset { dx: -10, scale: 1.5 }, options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serialization-serde")]
+    pub fn encode_with_options<T: serde::Serialize>(
+        &self,
+        value: &T,
+        options: crate::encoder::EncoderOptions,
+    ) -> crate::encoder::EncodeResult<()> {
+        let mut encoder = crate::encoder::Encoder::from_key(self)?.with_options(options);
+        value.serialize(&mut encoder)?;
+        encoder.commit()
+    }
+
+    /// Load `Decodable` type from a registry key like `decode`,
+    /// but skip the subkeys and values that fail to decode and return their errors.
+    /// A failed map entry is left out of the map, a failed `Option` field or sequence element
+    /// reads as `None`, any other failure makes the key it is in fail too.
+    /// Errors carry the path of the subkey and the value name.
+    /// Part of `serialization-serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use serde_derive::Deserialize;
+    /// use std::collections::HashMap;
+    /// use winreg::HKLM;
+    ///
+    /// #[allow(non_snake_case)]
+    /// #[derive(Deserialize)]
+    /// struct InstalledApp {
+    ///     DisplayName: String,
+    ///     DisplayVersion: Option<String>,
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let uninstall = HKLM.open_subkey(r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall")?;
+    /// let (apps, errors): (HashMap<String, InstalledApp>, _) = uninstall.decode_lenient()?;
+    /// for err in errors {
+    ///     println!("skipped {}", err);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serialization-serde")]
+    pub fn decode_lenient<'de, T: serde::Deserialize<'de>>(
+        &self,
+    ) -> crate::decoder::DecodeResult<(T, Vec<crate::decoder::DecoderError>)> {
+        let mut decoder = crate::decoder::Decoder::from_key(self)?;
+        decoder.decode_lenient()
+    }
+
+    /// Load `Decodable` type from a registry key like `decode`,
+    /// reading `REG_DWORD` and `REG_QWORD` values of signed integers and floats
+    /// with the formats of `options`.
+    /// Part of `serialization-serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use serde_derive::Deserialize;
+    /// use winreg::decoder::{DecoderOptions, FloatFormat, SignedFormat};
+    /// use winreg::HKCU;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Offset {
+    ///     dx: i32,
+    ///     scale: f64,
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let options = DecoderOptions::new()
+    ///     .signed(SignedFormat::TwosComplement)
+    ///     .floats(FloatFormat::Bits);
+    /// let key = HKCU.open_subkey("Software\\MyProduct\\Offset")?;
+    /// let offset: Offset = key.decode_with_options(options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serialization-serde")]
+    pub fn decode_with_options<'de, T: serde::Deserialize<'de>>(
+        &self,
+        options: crate::decoder::DecoderOptions,
+    ) -> crate::decoder::DecodeResult<T> {
+        let mut decoder = crate::decoder::Decoder::from_key(self)?.with_options(options);
+        T::deserialize(&mut decoder)
+    }
+
     fn close_(&mut self) -> io::Result<()> {
         // don't try to close predefined keys
         // The root hkey overflows with windows-sys, where HKEY is an alias for isize.
@@ -1062,8 +1266,10 @@ impl RegKey {
             err => werr!(err),
         }
     }
+}
 
-    pub(crate) fn enum_key(&self, index: u32) -> Option<io::Result<OsString>> {
+impl RegistryEnum for RegKey {
+    fn enum_key(&self, index: u32) -> Option<io::Result<OsString>> {
         let mut name_len = 2048;
         #[allow(clippy::unnecessary_cast)]
         let mut name = [0 as u16; 2048];
@@ -1085,10 +1291,7 @@ impl RegKey {
         }
     }
 
-    pub(crate) fn enum_value(
-        &self,
-        index: u32,
-    ) -> Option<io::Result<(OsString, RegValue<'static>)>> {
+    fn enum_value(&self, index: u32) -> Option<io::Result<(OsString, RegValue<'static>)>> {
         let mut name_len = 2048;
         #[allow(clippy::unnecessary_cast)]
         let mut name = [0 as u16; 2048];
@@ -1114,11 +1317,7 @@ impl RegKey {
                     unsafe {
                         buf.set_len(buf_len as usize);
                     }
-                    // minimal check before transmute to RegType
-                    if buf_type > Registry::REG_QWORD {
-                        return Some(werr!(Foundation::ERROR_BAD_FILE_TYPE));
-                    }
-                    let t: RegType = unsafe { transmute(buf_type as u8) };
+                    let t = RegType::from_raw(buf_type);
                     let value = RegValue {
                         bytes: buf.into(),
                         vtype: t,
@@ -1136,6 +1335,78 @@ impl RegKey {
     }
 }
 
+impl RegistryBackend for RegKey {
+    fn open_subkey_with_flags<P: AsRef<OsStr>>(&self, path: P, perms: u32) -> io::Result<RegKey> {
+        RegKey::open_subkey_with_flags(self, path, perms)
+    }
+
+    fn create_subkey_with_flags<P: AsRef<OsStr>>(
+        &self,
+        path: P,
+        perms: u32,
+    ) -> io::Result<(RegKey, RegDisposition)> {
+        RegKey::create_subkey_with_flags(self, path, perms)
+    }
+
+    fn rename_subkey<ON: AsRef<OsStr>, NN: AsRef<OsStr>>(
+        &self,
+        old_name: ON,
+        new_name: NN,
+    ) -> io::Result<()> {
+        RegKey::rename_subkey(self, old_name, new_name)
+    }
+
+    fn query_info(&self) -> io::Result<RegKeyMetadata> {
+        RegKey::query_info(self)
+    }
+
+    fn delete_subkey<P: AsRef<OsStr>>(&self, path: P) -> io::Result<()> {
+        RegKey::delete_subkey(self, path)
+    }
+
+    fn delete_subkey_all<P: AsRef<OsStr>>(&self, path: P) -> io::Result<()> {
+        RegKey::delete_subkey_all(self, path)
+    }
+
+    fn get_raw_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<RegValue<'static>> {
+        RegKey::get_raw_value(self, name)
+    }
+
+    fn set_raw_value<N: AsRef<OsStr>>(&self, name: N, value: &RegValue) -> io::Result<()> {
+        RegKey::set_raw_value(self, name, value)
+    }
+
+    fn delete_value<N: AsRef<OsStr>>(&self, name: N) -> io::Result<()> {
+        RegKey::delete_value(self, name)
+    }
+
+    fn copy_tree<P: AsRef<OsStr>>(&self, path: P, dest: &RegKey) -> io::Result<()> {
+        RegKey::copy_tree(self, path, dest)
+    }
+
+    #[cfg(feature = "serialization-serde")]
+    fn encode<T: serde::Serialize>(&self, value: &T) -> crate::encoder::EncodeResult<()> {
+        RegKey::encode(self, value)
+    }
+
+    #[cfg(feature = "serialization-serde")]
+    fn encode_destructive<T: serde::Serialize>(
+        &self,
+        value: &T,
+    ) -> crate::encoder::EncodeResult<()> {
+        RegKey::encode_destructive(self, value)
+    }
+
+    #[cfg(feature = "serialization-serde")]
+    fn encode_with_options<T: serde::Serialize>(
+        &self,
+        value: &T,
+        options: crate::encoder::EncoderOptions,
+    ) -> crate::encoder::EncodeResult<()> {
+        RegKey::encode_with_options(self, value, options)
+    }
+}
+
 impl Drop for RegKey {
     fn drop(&mut self) {
         self.close_().unwrap_or(());
@@ -6,7 +6,9 @@
 use std::fmt;
 use std::ops::Deref;
 use windows_sys::Win32::Foundation::FILETIME;
+#[cfg(windows)]
 use windows_sys::Win32::Foundation::SYSTEMTIME;
+#[cfg(windows)]
 use windows_sys::Win32::System::Time::FileTimeToSystemTime;
 
 pub struct FileTime(pub(crate) FILETIME);
@@ -20,6 +22,21 @@ impl Default for FileTime {
     }
 }
 
+impl FileTime {
+    /// Create from the number of 100-nanosecond intervals since January 1, 1601 (UTC)
+    pub fn from_u64(v: u64) -> FileTime {
+        FileTime(FILETIME {
+            dwLowDateTime: v as u32,
+            dwHighDateTime: (v >> 32) as u32,
+        })
+    }
+
+    /// Number of 100-nanosecond intervals since January 1, 1601 (UTC)
+    pub fn as_u64(&self) -> u64 {
+        ((self.0.dwHighDateTime as u64) << 32) | self.0.dwLowDateTime as u64
+    }
+}
+
 impl fmt::Debug for FileTime {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("FILETIME")
@@ -52,6 +69,7 @@ pub struct RegKeyMetadata {
     pub last_write_time: FileTime,
 }
 
+#[cfg(windows)]
 impl RegKeyMetadata {
     /// Returns `last_write_time` field as `windows_sys::Win32::Foundation::SYSTEMTIME`
     pub fn get_last_write_time_system(&self) -> SYSTEMTIME {
@@ -4,6 +4,7 @@
 // may not be copied, modified, or distributed
 // except according to those terms.
 use crate::enums::*;
+use crate::resource::{FullResourceDescriptor, ResourceList, ResourceRequirementsList};
 use crate::types::FromRegValue;
 use std::borrow::Cow;
 use std::fmt;
@@ -24,13 +25,26 @@ macro_rules! format_reg_value {
     };
 }
 
+/// Resource descriptors that fail to parse are shown as bytes
+macro_rules! format_resource {
+    ($e:expr => $t:ident) => {
+        match $t::from_reg_value($e) {
+            Ok(val) => format!("{:?}", val),
+            Err(_) => format!("{:?}", $e.bytes),
+        }
+    };
+}
+
 impl fmt::Display for RegValue<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let f_val = match self.vtype {
             REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ => format_reg_value!(self => String),
             REG_DWORD => format_reg_value!(self => u32),
             REG_QWORD => format_reg_value!(self => u64),
-            _ => format!("{:?}", self.bytes), //TODO: implement more types
+            REG_RESOURCE_LIST => format_resource!(self => ResourceList),
+            REG_FULL_RESOURCE_DESCRIPTOR => format_resource!(self => FullResourceDescriptor),
+            REG_RESOURCE_REQUIREMENTS_LIST => format_resource!(self => ResourceRequirementsList),
+            _ => format!("{:?}", self.bytes),
         };
         write!(f, "{}", f_val)
     }
@@ -0,0 +1,376 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Applying parsed `.reg` files to the registry or to any other `ApplyTarget`
+use super::RegFile;
+use crate::reg_value::RegValue;
+use std::io;
+use windows_sys::Win32::Foundation;
+
+/// Something a `.reg` file can be applied to.
+/// Paths are full paths as written in the file, starting with a predefined key name.
+pub trait ApplyTarget {
+    /// Create the key and all missing parents, succeed if it exists
+    fn create_key(&mut self, path: &str) -> io::Result<()>;
+    /// Delete the key with all its subkeys and values, succeed if it doesn't exist
+    fn delete_key(&mut self, path: &str) -> io::Result<()>;
+    fn set_value(&mut self, path: &str, name: &str, value: &RegValue) -> io::Result<()>;
+    /// Delete the value, succeed if it doesn't exist
+    fn delete_value(&mut self, path: &str, name: &str) -> io::Result<()>;
+
+    /// Names of the values of the key, for the default `delete_all_values`.
+    /// Fails with `ERROR_CALL_NOT_IMPLEMENTED` by default.
+    fn value_names(&mut self, _path: &str) -> io::Result<Vec<String>> {
+        werr!(Foundation::ERROR_CALL_NOT_IMPLEMENTED)
+    }
+
+    /// Delete all values of the key, keep its subkeys. `.reg` files never do that,
+    /// only `Registry.pol` files applied with `policy::PolFile::apply`.
+    /// Deletes the values listed by `value_names` one by one by default.
+    fn delete_all_values(&mut self, path: &str) -> io::Result<()> {
+        for name in self.value_names(path)? {
+            self.delete_value(path, &name)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single change described by a `.reg` file
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    /// `[Key]`
+    CreateKey(String),
+    /// `[-Key]`
+    DeleteKey(String),
+    /// `"Name"=data`: path, name and value
+    SetValue(String, String, RegValue<'static>),
+    /// `"Name"=-`: path and name
+    DeleteValue(String, String),
+    /// All values of the key are deleted, not produced by `.reg` files
+    DeleteAllValues(String),
+}
+
+/// Outcome of a single change
+#[derive(Debug)]
+pub struct ApplyResult {
+    pub change: Change,
+    pub result: io::Result<()>,
+}
+
+/// Target that only records changes without applying them anywhere
+#[derive(Debug, Default)]
+pub struct DryRun {
+    pub changes: Vec<Change>,
+}
+
+impl ApplyTarget for DryRun {
+    fn create_key(&mut self, path: &str) -> io::Result<()> {
+        self.changes.push(Change::CreateKey(path.to_owned()));
+        Ok(())
+    }
+
+    fn delete_key(&mut self, path: &str) -> io::Result<()> {
+        self.changes.push(Change::DeleteKey(path.to_owned()));
+        Ok(())
+    }
+
+    fn set_value(&mut self, path: &str, name: &str, value: &RegValue) -> io::Result<()> {
+        self.changes.push(Change::SetValue(
+            path.to_owned(),
+            name.to_owned(),
+            super::to_static(value),
+        ));
+        Ok(())
+    }
+
+    fn delete_value(&mut self, path: &str, name: &str) -> io::Result<()> {
+        self.changes
+            .push(Change::DeleteValue(path.to_owned(), name.to_owned()));
+        Ok(())
+    }
+
+    fn delete_all_values(&mut self, path: &str) -> io::Result<()> {
+        self.changes.push(Change::DeleteAllValues(path.to_owned()));
+        Ok(())
+    }
+}
+
+impl RegFile {
+    /// Apply all sections in file order, the same way `reg import` does.
+    /// Doesn't stop on errors, returns a result for every change.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// use winreg::regfile::{Change, DryRun, RegFile};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let file = RegFile::parse_str(
+    ///     "REGEDIT4\r\n\r\n[-HKEY_CURRENT_USER\\Software\\MyProduct]\r\n",
+    /// )?;
+    /// let mut dry_run = DryRun::default();
+    /// let results = file.apply(&mut dry_run);
+    /// assert!(results.iter().all(|r| r.result.is_ok()));
+    /// assert_eq!(
+    ///     dry_run.changes,
+    ///     vec![Change::DeleteKey("HKEY_CURRENT_USER\\Software\\MyProduct".to_owned())]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn apply<T: ApplyTarget>(&self, target: &mut T) -> Vec<ApplyResult> {
+        let mut results = Vec::new();
+        for key in &self.keys {
+            if key.delete {
+                results.push(ApplyResult {
+                    result: target.delete_key(&key.path),
+                    change: Change::DeleteKey(key.path.clone()),
+                });
+                continue;
+            }
+            results.push(ApplyResult {
+                result: target.create_key(&key.path),
+                change: Change::CreateKey(key.path.clone()),
+            });
+            for value in &key.values {
+                let path = key.path.clone();
+                let name = value.name.clone();
+                results.push(match value.value {
+                    Some(ref v) => ApplyResult {
+                        result: target.set_value(&path, &name, v),
+                        change: Change::SetValue(path, name, super::to_static(v)),
+                    },
+                    None => ApplyResult {
+                        result: target.delete_value(&path, &name),
+                        change: Change::DeleteValue(path, name),
+                    },
+                });
+            }
+        }
+        results
+    }
+
+    /// Apply the file to the registry inside a new transaction.
+    /// The transaction is committed only if every change succeeds,
+    /// otherwise it's rolled back and nothing is changed.
+    /// Part of `transactions` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use winreg::regfile::RegFile;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let results = RegFile::open("settings.reg")?.apply_transacted()?;
+    /// for r in results.iter().filter(|r| r.result.is_err()) {
+    ///     eprintln!("{:?}: {:?}", r.change, r.result);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(windows, feature = "transactions"))]
+    pub fn apply_transacted(&self) -> io::Result<Vec<ApplyResult>> {
+        let t = crate::transaction::Transaction::new()?;
+        let results = self.apply(&mut RegistryTarget::new().transacted(&t));
+        if results.iter().all(|r| r.result.is_ok()) {
+            t.commit()?;
+        } else {
+            t.rollback()?;
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(windows)]
+pub use self::registry::RegistryTarget;
+
+#[cfg(windows)]
+mod registry {
+    use super::ApplyTarget;
+    use crate::common::*;
+    use crate::enums::*;
+    use crate::reg_key::RegKey;
+    use crate::reg_value::RegValue;
+    #[cfg(feature = "transactions")]
+    use crate::transaction::Transaction;
+    use std::io;
+    use std::marker::PhantomData;
+    use windows_sys::Win32::Foundation;
+    use windows_sys::Win32::System::Registry::HKEY;
+
+    const ROOTS: [(&str, &str, HKEY); 5] = [
+        ("HKEY_CLASSES_ROOT", "HKCR", HKEY_CLASSES_ROOT),
+        ("HKEY_CURRENT_USER", "HKCU", HKEY_CURRENT_USER),
+        ("HKEY_LOCAL_MACHINE", "HKLM", HKEY_LOCAL_MACHINE),
+        ("HKEY_USERS", "HKU", HKEY_USERS),
+        ("HKEY_CURRENT_CONFIG", "HKCC", HKEY_CURRENT_CONFIG),
+    ];
+
+    /// Applies `.reg` files to the live registry
+    pub struct RegistryTarget<'t> {
+        roots: Vec<(&'static str, RegKey)>,
+        #[cfg(feature = "transactions")]
+        transaction: Option<&'t Transaction>,
+        marker: PhantomData<&'t ()>,
+    }
+
+    impl Default for RegistryTarget<'_> {
+        fn default() -> Self {
+            RegistryTarget::new()
+        }
+    }
+
+    impl<'t> RegistryTarget<'t> {
+        /// Target that resolves paths against predefined keys
+        pub fn new() -> RegistryTarget<'t> {
+            RegistryTarget {
+                roots: Vec::new(),
+                #[cfg(feature = "transactions")]
+                transaction: None,
+                marker: PhantomData,
+            }
+        }
+
+        /// Redirect paths starting with a predefined key name (long or short form)
+        /// to another key, e.g. to apply a file to a sandbox.
+        /// Fails with `ERROR_BAD_PATHNAME` if `name` is not a predefined key name.
+        ///
+        /// # Examples
+        ///
+        /// ```no_run
+        /// # use std::error::Error;
+        /// use winreg::regfile::{RegFile, RegistryTarget};
+        /// use winreg::HKCU;
+        /// # fn main() -> Result<(), Box<dyn Error>> {
+        /// let (sandbox, _) = HKCU.create_subkey("Software\\Sandbox")?;
+        /// let mut target = RegistryTarget::new().with_root("HKEY_LOCAL_MACHINE", sandbox)?;
+        /// RegFile::open("machine.reg")?.apply(&mut target);
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub fn with_root(mut self, name: &str, key: RegKey) -> io::Result<RegistryTarget<'t>> {
+            match find_root(name) {
+                Some((long, _)) => {
+                    self.roots.retain(|(n, _)| *n != long);
+                    self.roots.push((long, key));
+                    Ok(self)
+                }
+                None => werr!(Foundation::ERROR_BAD_PATHNAME),
+            }
+        }
+
+        /// Make all changes inside a transaction.
+        /// Part of `transactions` feature.
+        #[cfg(feature = "transactions")]
+        pub fn transacted(mut self, t: &'t Transaction) -> RegistryTarget<'t> {
+            self.transaction = Some(t);
+            self
+        }
+
+        fn with_key<R, F>(&self, path: &str, f: F) -> io::Result<R>
+        where
+            F: FnOnce(&RegKey, &str) -> io::Result<R>,
+        {
+            let (root, sub) = match path.find('\\') {
+                Some(i) => (&path[..i], &path[i + 1..]),
+                None => (path, ""),
+            };
+            let (long, hkey) = match find_root(root) {
+                Some(root) => root,
+                None => return werr!(Foundation::ERROR_BAD_PATHNAME),
+            };
+            match self.roots.iter().find(|(n, _)| *n == long) {
+                Some((_, key)) => f(key, sub),
+                None => f(&RegKey::predef(hkey), sub),
+            }
+        }
+
+        fn open(&self, root: &RegKey, sub: &str, perms: u32) -> io::Result<RegKey> {
+            #[cfg(feature = "transactions")]
+            {
+                if let Some(t) = self.transaction {
+                    return root.open_subkey_transacted_with_flags(sub, t, perms);
+                }
+            }
+            root.open_subkey_with_flags(sub, perms)
+        }
+    }
+
+    fn find_root(name: &str) -> Option<(&'static str, HKEY)> {
+        ROOTS
+            .iter()
+            .find(|(long, short, _)| names_eq(long, name) || names_eq(short, name))
+            .map(|(long, _, hkey)| (*long, *hkey))
+    }
+
+    fn ignore_not_found(res: io::Result<()>) -> io::Result<()> {
+        match res {
+            Err(ref err) if err.raw_os_error() == Some(Foundation::ERROR_FILE_NOT_FOUND as i32) => {
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    impl ApplyTarget for RegistryTarget<'_> {
+        fn create_key(&mut self, path: &str) -> io::Result<()> {
+            self.with_key(path, |root, sub| {
+                #[cfg(feature = "transactions")]
+                {
+                    if let Some(t) = self.transaction {
+                        return root.create_subkey_transacted(sub, t).map(|_| ());
+                    }
+                }
+                root.create_subkey(sub).map(|_| ())
+            })
+        }
+
+        fn delete_key(&mut self, path: &str) -> io::Result<()> {
+            self.with_key(path, |root, sub| {
+                if sub.is_empty() {
+                    // never wipe a whole predefined key
+                    return werr!(Foundation::ERROR_INVALID_PARAMETER);
+                }
+                // if the parent is opened with a transaction RegDeleteTreeW happens inside it
+                let parent = self.open(root, "", KEY_ALL_ACCESS)?;
+                ignore_not_found(parent.delete_subkey_all(sub))
+            })
+        }
+
+        fn set_value(&mut self, path: &str, name: &str, value: &RegValue) -> io::Result<()> {
+            self.with_key(path, |root, sub| {
+                self.open(root, sub, KEY_SET_VALUE)?
+                    .set_raw_value(name, value)
+            })
+        }
+
+        fn delete_value(&mut self, path: &str, name: &str) -> io::Result<()> {
+            self.with_key(path, |root, sub| {
+                ignore_not_found(
+                    self.open(root, sub, KEY_SET_VALUE)
+                        .and_then(|key| key.delete_value(name)),
+                )
+            })
+        }
+
+        fn delete_all_values(&mut self, path: &str) -> io::Result<()> {
+            self.with_key(path, |root, sub| {
+                let key = match self.open(root, sub, KEY_QUERY_VALUE | KEY_SET_VALUE) {
+                    Ok(key) => key,
+                    Err(err) => return ignore_not_found(Err(err)),
+                };
+                let names = key
+                    .enum_values_os_string()
+                    .map(|v| v.map(|(name, _)| name))
+                    .collect::<io::Result<Vec<_>>>()?;
+                for name in names {
+                    ignore_not_found(key.delete_value(name))?;
+                }
+                Ok(())
+            })
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Export of key trees to `.reg` files
+use super::{RegFile, RegFileKey, RegFileValue, RegFileVersion};
+use crate::backend::RegistryBackend;
+use crate::common::*;
+use crate::hive::HiveKey;
+use crate::reg_value::RegValue;
+use std::ffi::{OsStr, OsString};
+use std::io;
+
+/// A key tree that can be read: `HiveKey` or any `RegistryBackend` like `RegKey`.
+/// Used by `.reg` export and `diff`.
+pub trait ExportSource: Sized {
+    /// Names of the direct subkeys, in any order
+    fn subkey_names(&self) -> io::Result<Vec<OsString>>;
+    /// Open a direct subkey for reading
+    fn open(&self, name: &OsStr) -> io::Result<Self>;
+    /// All values, in any order
+    fn values(&self) -> io::Result<Vec<(OsString, RegValue<'static>)>>;
+}
+
+impl ExportSource for HiveKey<'_> {
+    fn subkey_names(&self) -> io::Result<Vec<OsString>> {
+        self.enum_keys_os_string().collect()
+    }
+
+    fn open(&self, name: &OsStr) -> io::Result<Self> {
+        self.open_subkey(name)
+    }
+
+    fn values(&self) -> io::Result<Vec<(OsString, RegValue<'static>)>> {
+        self.enum_values_os_string().collect()
+    }
+}
+
+impl<K: RegistryBackend> ExportSource for K {
+    fn subkey_names(&self) -> io::Result<Vec<OsString>> {
+        self.enum_keys_os_string().collect()
+    }
+
+    fn open(&self, name: &OsStr) -> io::Result<Self> {
+        self.open_subkey_with_flags(name, crate::enums::KEY_READ)
+    }
+
+    fn values(&self) -> io::Result<Vec<(OsString, RegValue<'static>)>> {
+        self.enum_values_os_string().collect()
+    }
+}
+
+/// Build a `Regedit5` file with the whole tree, `path` is the name of `key` in the file.
+/// Subkeys are sorted the way the registry sorts key names,
+/// values are written in enumeration order like `regedit` does.
+pub(crate) fn export<S: ExportSource>(key: &S, path: &str) -> io::Result<RegFile> {
+    let mut file = RegFile::new(RegFileVersion::Regedit5);
+    export_key(&mut file, key, path.trim_end_matches('\\'))?;
+    Ok(file)
+}
+
+fn export_key<S: ExportSource>(file: &mut RegFile, key: &S, path: &str) -> io::Result<()> {
+    file.keys.push(RegFileKey {
+        path: path.to_owned(),
+        delete: false,
+        values: key
+            .values()?
+            .into_iter()
+            .map(|(name, value)| RegFileValue {
+                name: name.to_string_lossy().into_owned(),
+                value: Some(value),
+            })
+            .collect(),
+    });
+
+    let mut subkeys = key.subkey_names()?;
+    subkeys.sort_by(|a, b| compare_names(&a.to_string_lossy(), &b.to_string_lossy()));
+    for name in subkeys {
+        let sub = key.open(&name)?;
+        let sub_path = format!("{}\\{}", path, name.to_string_lossy());
+        export_key(file, &sub, &sub_path)?;
+    }
+    Ok(())
+}
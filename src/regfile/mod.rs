@@ -0,0 +1,542 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parser and writer for `.reg` files in both formats used by `regedit`:
+//! ANSI `REGEDIT4` and UTF-16LE `Windows Registry Editor Version 5.00`.
+//!
+//!```no_run
+//!use std::io;
+//!use winreg::regfile::RegFile;
+//!
+//!fn main() -> io::Result<()> {
+//!    let file = RegFile::open("settings.reg")?;
+//!    for key in &file.keys {
+//!        println!("[{}]", key.path);
+//!        for value in &key.values {
+//!            println!("{:?} = {:?}", value.name, value.value);
+//!        }
+//!    }
+//!    Ok(())
+//!}
+//!```
+use crate::common::*;
+use crate::enums::*;
+use crate::reg_value::RegValue;
+use crate::types::ToRegValue;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use windows_sys::Win32::Foundation;
+
+mod apply;
+mod export;
+
+#[cfg(windows)]
+pub use self::apply::RegistryTarget;
+pub use self::apply::{ApplyResult, ApplyTarget, Change, DryRun};
+pub(crate) use self::export::export;
+pub use self::export::ExportSource;
+
+const HEADER_REGEDIT4: &str = "REGEDIT4";
+const HEADER_REGEDIT5: &str = "Windows Registry Editor Version 5.00";
+/// `regedit` wraps long hex data when a line reaches this many characters
+const HEX_LINE_WIDTH: usize = 77;
+
+/// Format of a `.reg` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegFileVersion {
+    /// `REGEDIT4`: ANSI encoding, strings in `hex(2)`/`hex(7)` data are single-byte.
+    /// Only characters up to `U+00FF` (Latin-1) are supported.
+    Regedit4,
+    /// `Windows Registry Editor Version 5.00`: UTF-16LE encoding
+    Regedit5,
+}
+
+/// Content of a `.reg` file: a list of key sections in file order
+#[derive(Debug)]
+pub struct RegFile {
+    pub version: RegFileVersion,
+    pub keys: Vec<RegFileKey>,
+}
+
+/// A `[Key]` or `[-Key]` section
+#[derive(Debug)]
+pub struct RegFileKey {
+    /// Full path, starting with a predefined key name, e.g. `HKEY_CURRENT_USER\Software`
+    pub path: String,
+    /// `[-Key]`: the key is deleted with all its subkeys
+    pub delete: bool,
+    pub values: Vec<RegFileValue>,
+}
+
+/// A `"Name"=data` line
+#[derive(Debug)]
+pub struct RegFileValue {
+    /// Empty string for the default value (`@`)
+    pub name: String,
+    /// `None` for `"Name"=-`: the value is deleted
+    pub value: Option<RegValue<'static>>,
+}
+
+fn parse_error<T>(line: usize, msg: &str) -> io::Result<T> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, msg),
+    ))
+}
+
+pub(crate) fn to_static(value: &RegValue) -> RegValue<'static> {
+    RegValue {
+        bytes: value.bytes.to_vec().into(),
+        vtype: value.vtype.clone(),
+    }
+}
+
+impl RegFile {
+    pub fn new(version: RegFileVersion) -> RegFile {
+        RegFile {
+            version,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Read and parse a `.reg` file
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<RegFile> {
+        RegFile::parse(&fs::read(path)?)
+    }
+
+    /// Parse raw content of a `.reg` file. The encoding is detected by
+    /// the byte order mark: UTF-16LE or UTF-8 if present. Without one,
+    /// the content is read as UTF-8 if it's valid, and as Latin-1 otherwise.
+    /// Fails with `ErrorKind::InvalidData` and a line number on syntax errors.
+    pub fn parse(data: &[u8]) -> io::Result<RegFile> {
+        let text = if let Some(rest) = data.strip_prefix(&[0xFF, 0xFE]) {
+            match String::from_utf16(&v8_to_v16(rest)) {
+                Ok(text) => text,
+                Err(_) => return parse_error(1, "invalid UTF-16"),
+            }
+        } else {
+            let rest = data.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(data);
+            match std::str::from_utf8(rest) {
+                Ok(text) => text.to_owned(),
+                Err(_) => rest.iter().map(|b| *b as char).collect(),
+            }
+        };
+        RegFile::parse_str(&text)
+    }
+
+    /// Parse decoded content of a `.reg` file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// use winreg::enums::*;
+    /// use winreg::regfile::RegFile;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let file = RegFile::parse_str(
+    ///     "Windows Registry Editor Version 5.00\r\n\
+    ///      \r\n\
+    ///      [HKEY_CURRENT_USER\\Software\\MyProduct]\r\n\
+    ///      \"Port\"=dword:00001f90\r\n",
+    /// )?;
+    /// let port = file.keys[0].values[0].value.as_ref().unwrap();
+    /// assert_eq!(port.vtype, REG_DWORD);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_str(text: &str) -> io::Result<RegFile> {
+        let mut lines = logical_lines(text)
+            .into_iter()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let version = match lines.next() {
+            Some((_, ref header)) if header.trim() == HEADER_REGEDIT5 => RegFileVersion::Regedit5,
+            Some((_, ref header)) if header.trim() == HEADER_REGEDIT4 => RegFileVersion::Regedit4,
+            Some((n, _)) => return parse_error(n, "unknown header"),
+            None => return parse_error(1, "missing header"),
+        };
+        let mut file = RegFile::new(version);
+        for (n, line) in lines {
+            let line = line.trim();
+            if line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') {
+                if !line.ends_with(']') {
+                    return parse_error(n, "unterminated key path");
+                }
+                let path = &line[1..line.len() - 1];
+                let (path, delete) = match path.strip_prefix('-') {
+                    Some(path) => (path, true),
+                    None => (path, false),
+                };
+                file.keys.push(RegFileKey {
+                    path: path.to_owned(),
+                    delete,
+                    values: Vec::new(),
+                });
+                continue;
+            }
+            let key = match file.keys.last_mut() {
+                Some(key) if !key.delete => key,
+                Some(_) => return parse_error(n, "value in a deleted key section"),
+                None => return parse_error(n, "value outside of a key section"),
+            };
+            match parse_value(line, version) {
+                Ok(value) => key.values.push(value),
+                Err(msg) => return parse_error(n, msg),
+            }
+        }
+        Ok(file)
+    }
+
+    /// Get a key section to add values to. Reuses the last section
+    /// with the same path (case insensitive) unless a deletion of that key follows it.
+    pub fn add_key<P: AsRef<OsStr>>(&mut self, path: P) -> &mut RegFileKey {
+        let path = path.as_ref().to_string_lossy();
+        let pos = self.keys.iter().rposition(|k| names_eq(&k.path, &path));
+        match pos {
+            Some(pos) if !self.keys[pos].delete => &mut self.keys[pos],
+            _ => {
+                self.keys.push(RegFileKey {
+                    path: path.into_owned(),
+                    delete: false,
+                    values: Vec::new(),
+                });
+                self.keys.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Add a `[-Key]` section
+    pub fn delete_key<P: AsRef<OsStr>>(&mut self, path: P) {
+        self.keys.push(RegFileKey {
+            path: path.as_ref().to_string_lossy().into_owned(),
+            delete: true,
+            values: Vec::new(),
+        });
+    }
+
+    /// Format the file the way `regedit` does.
+    /// Values that can't be written as a plain string or `dword:`
+    /// are written in the `hex(N):` form, so no data is lost.
+    /// Fails with `ERROR_NO_UNICODE_TRANSLATION` if some string data can't be
+    /// represented in the `REGEDIT4` format.
+    pub fn to_text(&self) -> io::Result<String> {
+        let mut out = String::new();
+        out.push_str(match self.version {
+            RegFileVersion::Regedit4 => HEADER_REGEDIT4,
+            RegFileVersion::Regedit5 => HEADER_REGEDIT5,
+        });
+        out.push_str("\r\n\r\n");
+        for key in &self.keys {
+            out.push('[');
+            if key.delete {
+                out.push('-');
+            }
+            out.push_str(&key.path);
+            out.push_str("]\r\n");
+            if !key.delete {
+                for value in &key.values {
+                    write_value(&mut out, value, self.version)?;
+                    out.push_str("\r\n");
+                }
+            }
+            out.push_str("\r\n");
+        }
+        Ok(out)
+    }
+
+    /// Encode the file: UTF-16LE with a byte order mark for `Regedit5`,
+    /// single-byte Latin-1 for `Regedit4`
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let text = self.to_text()?;
+        match self.version {
+            RegFileVersion::Regedit5 => {
+                let mut res = vec![0xFF, 0xFE];
+                res.extend(text.encode_utf16().flat_map(|w| w.to_le_bytes()));
+                Ok(res)
+            }
+            RegFileVersion::Regedit4 => text
+                .chars()
+                .map(|c| match c as u32 {
+                    c @ 0..=0xFF => Ok(c as u8),
+                    _ => werr!(Foundation::ERROR_NO_UNICODE_TRANSLATION),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn write<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&self.to_bytes()?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_bytes()?)
+    }
+}
+
+impl RegFileKey {
+    /// Convert a value from a rust type and set it.
+    /// Will set the `Default` value if `name` is an empty string.
+    pub fn set_value<T: ToRegValue, N: AsRef<OsStr>>(&mut self, name: N, value: &T) {
+        self.set_raw_value(name, &value.to_reg_value())
+    }
+
+    /// Set a value, replacing an existing line with the same name (case insensitive).
+    /// Will set the `Default` value if `name` is an empty string.
+    pub fn set_raw_value<N: AsRef<OsStr>>(&mut self, name: N, value: &RegValue) {
+        self.put(name, Some(to_static(value)))
+    }
+
+    /// Add a `"Name"=-` line
+    pub fn delete_value<N: AsRef<OsStr>>(&mut self, name: N) {
+        self.put(name, None)
+    }
+
+    fn put<N: AsRef<OsStr>>(&mut self, name: N, value: Option<RegValue<'static>>) {
+        let name = name.as_ref().to_string_lossy();
+        match self.values.iter_mut().find(|v| names_eq(&v.name, &name)) {
+            Some(v) => v.value = value,
+            None => self.values.push(RegFileValue {
+                name: name.into_owned(),
+                value,
+            }),
+        }
+    }
+}
+
+/// Split text into lines, joining hex data continued with a trailing `\`.
+/// Returns lines with their numbers.
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut res = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let (n, mut acc) = match current.take() {
+            Some((n, mut acc)) => {
+                acc.push_str(line.trim_start());
+                (n, acc)
+            }
+            None => (i + 1, line.to_owned()),
+        };
+        let trimmed = acc.trim_end();
+        let continued = trimmed
+            .strip_suffix('\\')
+            .map(|rest| rest.trim_end())
+            .filter(|rest| rest.ends_with(',') || rest.ends_with(':'))
+            .map(|rest| rest.len());
+        match continued {
+            Some(len) if !acc.trim_start().starts_with(';') => {
+                acc.truncate(len);
+                current = Some((n, acc));
+            }
+            _ => res.push((n, acc)),
+        }
+    }
+    res.extend(current);
+    res
+}
+
+/// Parse a string in double quotes with `\"` and `\\` escapes,
+/// returns it and the rest of the input
+fn parse_quoted(s: &str) -> Result<(String, &str), &'static str> {
+    let mut res = String::new();
+    let mut chars = s.char_indices().skip(1);
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '"' => {
+                let end = chars.next().map(|(i, _)| i).unwrap_or(s.len());
+                return Ok((res, &s[end..]));
+            }
+            '\\' => match chars.next() {
+                Some((_, c @ '\\')) | Some((_, c @ '"')) => res.push(c),
+                Some((_, c)) => {
+                    res.push('\\');
+                    res.push(c);
+                }
+                None => break,
+            },
+            c => res.push(c),
+        }
+    }
+    Err("unterminated string")
+}
+
+fn strip_prefix_ci<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    match s.get(..prefix.len()) {
+        Some(head) if head.eq_ignore_ascii_case(prefix) => Some(&s[prefix.len()..]),
+        _ => None,
+    }
+}
+
+fn parse_value(line: &str, version: RegFileVersion) -> Result<RegFileValue, &'static str> {
+    let (name, rest) = if let Some(rest) = line.strip_prefix('@') {
+        (String::new(), rest)
+    } else if line.starts_with('"') {
+        parse_quoted(line)?
+    } else {
+        return Err("expected a value name");
+    };
+    let data = match rest.trim_start().strip_prefix('=') {
+        Some(data) => data.trim(),
+        None => return Err("expected '='"),
+    };
+    if data == "-" {
+        return Ok(RegFileValue { name, value: None });
+    }
+    let value = if data.starts_with('"') {
+        let (s, rest) = parse_quoted(data)?;
+        if !rest.trim().is_empty() {
+            return Err("unexpected data after a string");
+        }
+        to_static(&s.to_reg_value())
+    } else if let Some(hex) = strip_prefix_ci(data, "dword:") {
+        let hex = hex.trim();
+        if hex.is_empty() || hex.len() > 8 {
+            return Err("invalid dword");
+        }
+        match u32::from_str_radix(hex, 16) {
+            Ok(v) => to_static(&v.to_reg_value()),
+            Err(_) => return Err("invalid dword"),
+        }
+    } else if let Some(rest) = strip_prefix_ci(data, "hex") {
+        let (raw_type, hex) = if let Some(hex) = rest.strip_prefix(':') {
+            (REG_BINARY.raw(), hex)
+        } else if let Some(rest) = rest.strip_prefix('(') {
+            let close = rest.find(')').ok_or("invalid value type")?;
+            let raw_type =
+                u32::from_str_radix(&rest[..close], 16).map_err(|_| "invalid value type")?;
+            let hex = rest[close + 1..]
+                .strip_prefix(':')
+                .ok_or("expected ':' after a value type")?;
+            (raw_type, hex)
+        } else {
+            return Err("unrecognized value data");
+        };
+        let vtype = RegType::from_raw(raw_type);
+        let mut bytes = Vec::new();
+        let hex = hex.trim();
+        if !hex.is_empty() {
+            for b in hex.split(',').map(|b| b.trim()) {
+                if b.is_empty() || b.len() > 2 {
+                    return Err("invalid hex data");
+                }
+                bytes.push(u8::from_str_radix(b, 16).map_err(|_| "invalid hex data")?);
+            }
+        }
+        if version == RegFileVersion::Regedit4 && is_string_type(&vtype) {
+            bytes = bytes.iter().flat_map(|b| [*b, 0]).collect();
+        }
+        RegValue {
+            bytes: bytes.into(),
+            vtype,
+        }
+    } else {
+        return Err("unrecognized value data");
+    };
+    Ok(RegFileValue {
+        name,
+        value: Some(value),
+    })
+}
+
+fn is_string_type(vtype: &RegType) -> bool {
+    matches!(vtype, REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// `REG_SZ` data that can be written in double quotes
+fn plain_string(bytes: &[u8], version: RegFileVersion) -> Option<String> {
+    if bytes.len() % 2 != 0 || !bytes.ends_with(&[0, 0]) {
+        return None;
+    }
+    let words = v8_to_v16(&bytes[..bytes.len() - 2]);
+    if words.contains(&0)
+        || (version == RegFileVersion::Regedit4 && words.iter().any(|w| *w > 0xFF))
+    {
+        return None;
+    }
+    String::from_utf16(&words)
+        .ok()
+        .filter(|s| !s.contains(['\r', '\n']))
+}
+
+fn write_value(out: &mut String, value: &RegFileValue, version: RegFileVersion) -> io::Result<()> {
+    let line_start = out.len();
+    if value.name.is_empty() {
+        out.push('@');
+    } else {
+        out.push('"');
+        out.push_str(&escape(&value.name));
+        out.push('"');
+    }
+    out.push('=');
+    let value = match value.value {
+        Some(ref value) => value,
+        None => {
+            out.push('-');
+            return Ok(());
+        }
+    };
+    if value.vtype == REG_SZ {
+        if let Some(s) = plain_string(&value.bytes, version) {
+            out.push('"');
+            out.push_str(&escape(&s));
+            out.push('"');
+            return Ok(());
+        }
+    }
+    if value.vtype == REG_DWORD && value.bytes.len() == 4 {
+        let v = u32::from_le_bytes([
+            value.bytes[0],
+            value.bytes[1],
+            value.bytes[2],
+            value.bytes[3],
+        ]);
+        out.push_str(&format!("dword:{:08x}", v));
+        return Ok(());
+    }
+    let narrowed;
+    let bytes: &[u8] = if version == RegFileVersion::Regedit4 && is_string_type(&value.vtype) {
+        if value.bytes.len() % 2 != 0 {
+            return werr!(Foundation::ERROR_NO_UNICODE_TRANSLATION);
+        }
+        narrowed = v8_to_v16(&value.bytes)
+            .into_iter()
+            .map(|w| match w {
+                0..=0xFF => Ok(w as u8),
+                _ => werr!(Foundation::ERROR_NO_UNICODE_TRANSLATION),
+            })
+            .collect::<io::Result<Vec<u8>>>()?;
+        &narrowed
+    } else {
+        &value.bytes
+    };
+    if value.vtype == REG_BINARY {
+        out.push_str("hex:");
+    } else {
+        out.push_str(&format!("hex({:x}):", value.vtype.raw()));
+    }
+    // `regedit` counts UTF-16 characters
+    let mut line_len = out[line_start..].encode_utf16().count();
+    for (i, b) in bytes.iter().enumerate() {
+        out.push_str(&format!("{:02x}", b));
+        line_len += 2;
+        if i + 1 < bytes.len() {
+            out.push(',');
+            line_len += 1;
+            if line_len >= HEX_LINE_WIDTH {
+                out.push_str("\\\r\n  ");
+                line_len = 2;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,714 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Hardware resource descriptors stored in `REG_RESOURCE_LIST`,
+//! `REG_FULL_RESOURCE_DESCRIPTOR` and `REG_RESOURCE_REQUIREMENTS_LIST` values,
+//! e.g. under `HARDWARE\RESOURCEMAP` and `SYSTEM\CurrentControlSet\Enum\...\Control`.
+//!
+//! The structures are parsed and written in pure Rust. Their layout depends on the pointer
+//! size of the system that wrote them (see `Layout`): `from_bytes()`/`to_bytes()` and
+//! `get_value()`/`set_value()` use the one of the target the crate is built for,
+//! `from_bytes_with_layout()`/`to_bytes_with_layout()` take it explicitly, e.g. for hives
+//! of another system. Resources of other types are kept as raw bytes; bytes a known type
+//! doesn't use are written as zeros.
+//!
+//!```no_run
+//!use std::io;
+//!use winreg::resource::{Resource, ResourceList};
+//!use winreg::RegistryBackend;
+//!
+//!fn print_ports<K: RegistryBackend>(resource_map: &K, name: &str) -> io::Result<()> {
+//!    let list: ResourceList = resource_map.get_value(name)?;
+//!    for full in &list.lists {
+//!        for partial in &full.descriptors {
+//!            if let Resource::Port { start, length } = partial.resource {
+//!                println!("ports {:#x}..{:#x}", start, start + length as u64);
+//!            }
+//!        }
+//!    }
+//!    Ok(())
+//!}
+//!```
+use crate::enums::*;
+use crate::reg_value::RegValue;
+use crate::types::{FromRegValue, ToRegValue};
+use std::io;
+use windows_sys::Win32::Foundation;
+
+const CM_RESOURCE_TYPE_PORT: u8 = 1;
+const CM_RESOURCE_TYPE_INTERRUPT: u8 = 2;
+const CM_RESOURCE_TYPE_MEMORY: u8 = 3;
+const CM_RESOURCE_TYPE_DMA: u8 = 4;
+const CM_RESOURCE_TYPE_DEVICE_SPECIFIC: u8 = 5;
+const CM_RESOURCE_TYPE_BUS_NUMBER: u8 = 6;
+
+/// Layout of the structures, it depends on the pointer size of the system that wrote them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// 32-bit Windows: `CM_PARTIAL_RESOURCE_DESCRIPTOR` is 16 bytes long,
+    /// interrupt affinities are 32-bit and their upper half is not written
+    Win32,
+    /// 64-bit Windows: `CM_PARTIAL_RESOURCE_DESCRIPTOR` is 20 bytes long
+    Win64,
+}
+
+impl Layout {
+    /// Layout of the target the crate is built for
+    #[cfg(target_pointer_width = "64")]
+    pub const NATIVE: Layout = Layout::Win64;
+    /// Layout of the target the crate is built for
+    #[cfg(not(target_pointer_width = "64"))]
+    pub const NATIVE: Layout = Layout::Win32;
+
+    /// Size of the union of `CM_PARTIAL_RESOURCE_DESCRIPTOR`
+    fn partial_union_size(self) -> usize {
+        match self {
+            Layout::Win32 => 12,
+            Layout::Win64 => 16,
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout::NATIVE
+    }
+}
+
+/// `CM_RESOURCE_LIST`, data of `REG_RESOURCE_LIST` values
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceList {
+    pub lists: Vec<FullResourceDescriptor>,
+}
+
+/// `CM_FULL_RESOURCE_DESCRIPTOR`, data of `REG_FULL_RESOURCE_DESCRIPTOR` values:
+/// resources of one device on a bus
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FullResourceDescriptor {
+    /// `INTERFACE_TYPE` of the bus, e.g. `5` for `PCIBus`
+    pub interface_type: i32,
+    pub bus_number: u32,
+    pub version: u16,
+    pub revision: u16,
+    pub descriptors: Vec<PartialResourceDescriptor>,
+}
+
+/// `CM_PARTIAL_RESOURCE_DESCRIPTOR`: a single resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialResourceDescriptor {
+    /// `CM_RESOURCE_SHARE_DISPOSITION`
+    pub share_disposition: u8,
+    /// `CM_RESOURCE_*` flags of the resource type
+    pub flags: u16,
+    pub resource: Resource,
+}
+
+/// Resource of a `PartialResourceDescriptor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resource {
+    /// `CmResourceTypePort`: a range of I/O ports
+    Port { start: u64, length: u32 },
+    /// `CmResourceTypeInterrupt`
+    Interrupt {
+        level: u16,
+        group: u16,
+        vector: u32,
+        affinity: u64,
+    },
+    /// `CmResourceTypeMemory`: a range of physical memory
+    Memory { start: u64, length: u32 },
+    /// `CmResourceTypeDma`
+    Dma { channel: u32, port: u32 },
+    /// `CmResourceTypeDeviceSpecific`: data that follows the descriptor
+    DeviceSpecific(Vec<u8>),
+    /// `CmResourceTypeBusNumber`: a range of bus numbers
+    BusNumber { start: u32, length: u32 },
+    /// Any other resource type with the raw bytes of its union,
+    /// only the first 12 of them are used in the 32-bit layout
+    Other { resource_type: u8, data: [u8; 16] },
+}
+
+/// `IO_RESOURCE_REQUIREMENTS_LIST`, data of `REG_RESOURCE_REQUIREMENTS_LIST` values:
+/// alternative sets of resources a device can work with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceRequirementsList {
+    /// `INTERFACE_TYPE` of the bus, e.g. `5` for `PCIBus`
+    pub interface_type: i32,
+    pub bus_number: u32,
+    pub slot_number: u32,
+    pub alternatives: Vec<IoResourceList>,
+}
+
+/// `IO_RESOURCE_LIST`: one alternative set of resources
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IoResourceList {
+    pub version: u16,
+    pub revision: u16,
+    pub descriptors: Vec<IoResourceDescriptor>,
+}
+
+/// `IO_RESOURCE_DESCRIPTOR`: a range a resource can be assigned from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoResourceDescriptor {
+    /// `IO_RESOURCE_PREFERRED`, `IO_RESOURCE_DEFAULT` or `IO_RESOURCE_ALTERNATIVE`
+    pub option: u8,
+    pub share_disposition: u8,
+    pub flags: u16,
+    pub requirement: Requirement,
+}
+
+/// Requirement of an `IoResourceDescriptor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Requirement {
+    Port {
+        length: u32,
+        alignment: u32,
+        minimum: u64,
+        maximum: u64,
+    },
+    Interrupt {
+        minimum_vector: u32,
+        maximum_vector: u32,
+        affinity_policy: u16,
+        group: u16,
+        priority_policy: u32,
+        targeted_processors: u64,
+    },
+    Memory {
+        length: u32,
+        alignment: u32,
+        minimum: u64,
+        maximum: u64,
+    },
+    Dma {
+        minimum_channel: u32,
+        maximum_channel: u32,
+    },
+    BusNumber {
+        length: u32,
+        minimum: u32,
+        maximum: u32,
+    },
+    /// Any other resource type with the raw bytes of its union
+    Other { resource_type: u8, data: [u8; 24] },
+}
+
+fn invalid_data<T>() -> io::Result<T> {
+    werr!(Foundation::ERROR_INVALID_DATA)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        match self.data.get(self.pos..self.pos.saturating_add(len)) {
+            Some(b) => {
+                self.pos += len;
+                Ok(b)
+            }
+            None => invalid_data(),
+        }
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        let lo = self.u32()? as u64;
+        let hi = self.u32()? as u64;
+        Ok((hi << 32) | lo)
+    }
+
+    /// `KAFFINITY`, pointer sized
+    fn affinity(&mut self, layout: Layout) -> io::Result<u64> {
+        match layout {
+            Layout::Win32 => Ok(self.u32()? as u64),
+            Layout::Win64 => self.u64(),
+        }
+    }
+
+    /// Union of a descriptor: `len` bytes starting at the current position
+    fn union(&mut self, len: usize) -> io::Result<Reader<'a>> {
+        Ok(Reader::new(self.bytes(len)?))
+    }
+
+    /// Number of items, each at least `min_size` bytes long
+    fn count(&mut self, min_size: usize) -> io::Result<usize> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(min_size) > self.data.len() - self.pos {
+            return invalid_data();
+        }
+        Ok(count)
+    }
+
+    fn finish<T>(&self, value: T) -> io::Result<T> {
+        if self.pos != self.data.len() {
+            return invalid_data();
+        }
+        Ok(value)
+    }
+}
+
+/// Write `bytes` padded with zeros or truncated to `len`
+fn put_padded(out: &mut Vec<u8>, bytes: &[u8], len: usize) {
+    let bytes = &bytes[..bytes.len().min(len)];
+    out.extend_from_slice(bytes);
+    out.resize(out.len() + len - bytes.len(), 0);
+}
+
+fn put_affinity(out: &mut Vec<u8>, affinity: u64, layout: Layout) {
+    match layout {
+        Layout::Win32 => out.extend_from_slice(&(affinity as u32).to_le_bytes()),
+        Layout::Win64 => out.extend_from_slice(&affinity.to_le_bytes()),
+    }
+}
+
+impl ResourceList {
+    pub fn from_bytes(data: &[u8]) -> io::Result<ResourceList> {
+        ResourceList::from_bytes_with_layout(data, Layout::NATIVE)
+    }
+
+    pub fn from_bytes_with_layout(data: &[u8], layout: Layout) -> io::Result<ResourceList> {
+        let mut r = Reader::new(data);
+        let count = r.count(16)?;
+        let mut lists = Vec::with_capacity(count);
+        for _ in 0..count {
+            lists.push(FullResourceDescriptor::read(&mut r, layout)?);
+        }
+        r.finish(ResourceList { lists })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_layout(Layout::NATIVE)
+    }
+
+    pub fn to_bytes_with_layout(&self, layout: Layout) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&(self.lists.len() as u32).to_le_bytes());
+        for full in &self.lists {
+            full.write(&mut out, layout);
+        }
+        out
+    }
+}
+
+impl FullResourceDescriptor {
+    pub fn from_bytes(data: &[u8]) -> io::Result<FullResourceDescriptor> {
+        FullResourceDescriptor::from_bytes_with_layout(data, Layout::NATIVE)
+    }
+
+    pub fn from_bytes_with_layout(
+        data: &[u8],
+        layout: Layout,
+    ) -> io::Result<FullResourceDescriptor> {
+        let mut r = Reader::new(data);
+        let full = FullResourceDescriptor::read(&mut r, layout)?;
+        r.finish(full)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_layout(Layout::NATIVE)
+    }
+
+    pub fn to_bytes_with_layout(&self, layout: Layout) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write(&mut out, layout);
+        out
+    }
+
+    fn read(r: &mut Reader, layout: Layout) -> io::Result<FullResourceDescriptor> {
+        let interface_type = r.u32()? as i32;
+        let bus_number = r.u32()?;
+        let version = r.u16()?;
+        let revision = r.u16()?;
+        let count = r.count(4 + layout.partial_union_size())?;
+        let mut descriptors = Vec::with_capacity(count);
+        for _ in 0..count {
+            descriptors.push(PartialResourceDescriptor::read(r, layout)?);
+        }
+        Ok(FullResourceDescriptor {
+            interface_type,
+            bus_number,
+            version,
+            revision,
+            descriptors,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>, layout: Layout) {
+        out.extend_from_slice(&self.interface_type.to_le_bytes());
+        out.extend_from_slice(&self.bus_number.to_le_bytes());
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&self.revision.to_le_bytes());
+        out.extend_from_slice(&(self.descriptors.len() as u32).to_le_bytes());
+        for partial in &self.descriptors {
+            partial.write(out, layout);
+        }
+    }
+}
+
+impl PartialResourceDescriptor {
+    fn read(r: &mut Reader, layout: Layout) -> io::Result<PartialResourceDescriptor> {
+        let resource_type = r.u8()?;
+        let share_disposition = r.u8()?;
+        let flags = r.u16()?;
+        let mut u = r.union(layout.partial_union_size())?;
+        let resource = match resource_type {
+            CM_RESOURCE_TYPE_PORT => Resource::Port {
+                start: u.u64()?,
+                length: u.u32()?,
+            },
+            CM_RESOURCE_TYPE_INTERRUPT => Resource::Interrupt {
+                level: u.u16()?,
+                group: u.u16()?,
+                vector: u.u32()?,
+                affinity: u.affinity(layout)?,
+            },
+            CM_RESOURCE_TYPE_MEMORY => Resource::Memory {
+                start: u.u64()?,
+                length: u.u32()?,
+            },
+            CM_RESOURCE_TYPE_DMA => Resource::Dma {
+                channel: u.u32()?,
+                port: u.u32()?,
+            },
+            CM_RESOURCE_TYPE_DEVICE_SPECIFIC => {
+                let size = u.u32()? as usize;
+                Resource::DeviceSpecific(r.bytes(size)?.to_vec())
+            }
+            CM_RESOURCE_TYPE_BUS_NUMBER => Resource::BusNumber {
+                start: u.u32()?,
+                length: u.u32()?,
+            },
+            _ => {
+                let mut data = [0; 16];
+                data[..u.data.len()].copy_from_slice(u.data);
+                Resource::Other {
+                    resource_type,
+                    data,
+                }
+            }
+        };
+        Ok(PartialResourceDescriptor {
+            share_disposition,
+            flags,
+            resource,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>, layout: Layout) {
+        let (resource_type, u) = match &self.resource {
+            Resource::Port { start, length } | Resource::Memory { start, length } => {
+                let mut u = start.to_le_bytes().to_vec();
+                u.extend_from_slice(&length.to_le_bytes());
+                let resource_type = match self.resource {
+                    Resource::Port { .. } => CM_RESOURCE_TYPE_PORT,
+                    _ => CM_RESOURCE_TYPE_MEMORY,
+                };
+                (resource_type, u)
+            }
+            Resource::Interrupt {
+                level,
+                group,
+                vector,
+                affinity,
+            } => {
+                let mut u = level.to_le_bytes().to_vec();
+                u.extend_from_slice(&group.to_le_bytes());
+                u.extend_from_slice(&vector.to_le_bytes());
+                put_affinity(&mut u, *affinity, layout);
+                (CM_RESOURCE_TYPE_INTERRUPT, u)
+            }
+            Resource::Dma { channel, port } => {
+                let mut u = channel.to_le_bytes().to_vec();
+                u.extend_from_slice(&port.to_le_bytes());
+                (CM_RESOURCE_TYPE_DMA, u)
+            }
+            Resource::DeviceSpecific(data) => (
+                CM_RESOURCE_TYPE_DEVICE_SPECIFIC,
+                (data.len() as u32).to_le_bytes().to_vec(),
+            ),
+            Resource::BusNumber { start, length } => {
+                let mut u = start.to_le_bytes().to_vec();
+                u.extend_from_slice(&length.to_le_bytes());
+                (CM_RESOURCE_TYPE_BUS_NUMBER, u)
+            }
+            Resource::Other {
+                resource_type,
+                data,
+            } => (*resource_type, data.to_vec()),
+        };
+        out.push(resource_type);
+        out.push(self.share_disposition);
+        out.extend_from_slice(&self.flags.to_le_bytes());
+        put_padded(out, &u, layout.partial_union_size());
+        if let Resource::DeviceSpecific(data) = &self.resource {
+            out.extend_from_slice(data);
+        }
+    }
+}
+
+impl ResourceRequirementsList {
+    pub fn from_bytes(data: &[u8]) -> io::Result<ResourceRequirementsList> {
+        ResourceRequirementsList::from_bytes_with_layout(data, Layout::NATIVE)
+    }
+
+    pub fn from_bytes_with_layout(
+        data: &[u8],
+        layout: Layout,
+    ) -> io::Result<ResourceRequirementsList> {
+        let mut r = Reader::new(data);
+        let list_size = r.u32()? as usize;
+        if list_size != data.len() {
+            return invalid_data();
+        }
+        let interface_type = r.u32()? as i32;
+        let bus_number = r.u32()?;
+        let slot_number = r.u32()?;
+        r.bytes(12)?; // Reserved
+        let count = r.count(8)?;
+        let mut alternatives = Vec::with_capacity(count);
+        for _ in 0..count {
+            alternatives.push(IoResourceList::read(&mut r, layout)?);
+        }
+        r.finish(ResourceRequirementsList {
+            interface_type,
+            bus_number,
+            slot_number,
+            alternatives,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_layout(Layout::NATIVE)
+    }
+
+    pub fn to_bytes_with_layout(&self, layout: Layout) -> Vec<u8> {
+        let mut out = vec![0; 4]; // ListSize
+        out.extend_from_slice(&self.interface_type.to_le_bytes());
+        out.extend_from_slice(&self.bus_number.to_le_bytes());
+        out.extend_from_slice(&self.slot_number.to_le_bytes());
+        out.extend_from_slice(&[0; 12]);
+        out.extend_from_slice(&(self.alternatives.len() as u32).to_le_bytes());
+        for list in &self.alternatives {
+            out.extend_from_slice(&list.version.to_le_bytes());
+            out.extend_from_slice(&list.revision.to_le_bytes());
+            out.extend_from_slice(&(list.descriptors.len() as u32).to_le_bytes());
+            for desc in &list.descriptors {
+                desc.write(&mut out, layout);
+            }
+        }
+        let size = out.len() as u32;
+        out[..4].copy_from_slice(&size.to_le_bytes());
+        out
+    }
+}
+
+impl IoResourceList {
+    fn read(r: &mut Reader, layout: Layout) -> io::Result<IoResourceList> {
+        let version = r.u16()?;
+        let revision = r.u16()?;
+        let count = r.count(32)?;
+        let mut descriptors = Vec::with_capacity(count);
+        for _ in 0..count {
+            descriptors.push(IoResourceDescriptor::read(r, layout)?);
+        }
+        Ok(IoResourceList {
+            version,
+            revision,
+            descriptors,
+        })
+    }
+}
+
+impl IoResourceDescriptor {
+    fn read(r: &mut Reader, layout: Layout) -> io::Result<IoResourceDescriptor> {
+        let option = r.u8()?;
+        let resource_type = r.u8()?;
+        let share_disposition = r.u8()?;
+        r.u8()?; // Spare1
+        let flags = r.u16()?;
+        r.u16()?; // Spare2
+        let mut u = r.union(24)?;
+        let requirement = match resource_type {
+            CM_RESOURCE_TYPE_PORT | CM_RESOURCE_TYPE_MEMORY => {
+                let length = u.u32()?;
+                let alignment = u.u32()?;
+                let minimum = u.u64()?;
+                let maximum = u.u64()?;
+                if resource_type == CM_RESOURCE_TYPE_PORT {
+                    Requirement::Port {
+                        length,
+                        alignment,
+                        minimum,
+                        maximum,
+                    }
+                } else {
+                    Requirement::Memory {
+                        length,
+                        alignment,
+                        minimum,
+                        maximum,
+                    }
+                }
+            }
+            CM_RESOURCE_TYPE_INTERRUPT => {
+                let minimum_vector = u.u32()?;
+                let maximum_vector = u.u32()?;
+                let affinity_policy = u.u16()?;
+                let group = u.u16()?;
+                let priority_policy = u.u32()?;
+                Requirement::Interrupt {
+                    minimum_vector,
+                    maximum_vector,
+                    affinity_policy,
+                    group,
+                    priority_policy,
+                    targeted_processors: u.affinity(layout)?,
+                }
+            }
+            CM_RESOURCE_TYPE_DMA => Requirement::Dma {
+                minimum_channel: u.u32()?,
+                maximum_channel: u.u32()?,
+            },
+            CM_RESOURCE_TYPE_BUS_NUMBER => Requirement::BusNumber {
+                length: u.u32()?,
+                minimum: u.u32()?,
+                maximum: u.u32()?,
+            },
+            _ => {
+                let mut data = [0; 24];
+                data.copy_from_slice(u.data);
+                Requirement::Other {
+                    resource_type,
+                    data,
+                }
+            }
+        };
+        Ok(IoResourceDescriptor {
+            option,
+            share_disposition,
+            flags,
+            requirement,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>, layout: Layout) {
+        let mut u = Vec::with_capacity(24);
+        let resource_type = match &self.requirement {
+            Requirement::Port {
+                length,
+                alignment,
+                minimum,
+                maximum,
+            }
+            | Requirement::Memory {
+                length,
+                alignment,
+                minimum,
+                maximum,
+            } => {
+                u.extend_from_slice(&length.to_le_bytes());
+                u.extend_from_slice(&alignment.to_le_bytes());
+                u.extend_from_slice(&minimum.to_le_bytes());
+                u.extend_from_slice(&maximum.to_le_bytes());
+                match self.requirement {
+                    Requirement::Port { .. } => CM_RESOURCE_TYPE_PORT,
+                    _ => CM_RESOURCE_TYPE_MEMORY,
+                }
+            }
+            Requirement::Interrupt {
+                minimum_vector,
+                maximum_vector,
+                affinity_policy,
+                group,
+                priority_policy,
+                targeted_processors,
+            } => {
+                u.extend_from_slice(&minimum_vector.to_le_bytes());
+                u.extend_from_slice(&maximum_vector.to_le_bytes());
+                u.extend_from_slice(&affinity_policy.to_le_bytes());
+                u.extend_from_slice(&group.to_le_bytes());
+                u.extend_from_slice(&priority_policy.to_le_bytes());
+                put_affinity(&mut u, *targeted_processors, layout);
+                CM_RESOURCE_TYPE_INTERRUPT
+            }
+            Requirement::Dma {
+                minimum_channel,
+                maximum_channel,
+            } => {
+                u.extend_from_slice(&minimum_channel.to_le_bytes());
+                u.extend_from_slice(&maximum_channel.to_le_bytes());
+                CM_RESOURCE_TYPE_DMA
+            }
+            Requirement::BusNumber {
+                length,
+                minimum,
+                maximum,
+            } => {
+                u.extend_from_slice(&length.to_le_bytes());
+                u.extend_from_slice(&minimum.to_le_bytes());
+                u.extend_from_slice(&maximum.to_le_bytes());
+                CM_RESOURCE_TYPE_BUS_NUMBER
+            }
+            Requirement::Other {
+                resource_type,
+                data,
+            } => {
+                u.extend_from_slice(data);
+                *resource_type
+            }
+        };
+        out.extend_from_slice(&[self.option, resource_type, self.share_disposition, 0]);
+        out.extend_from_slice(&self.flags.to_le_bytes());
+        out.extend_from_slice(&[0; 2]);
+        put_padded(out, &u, 24);
+    }
+}
+
+macro_rules! resource_reg_value {
+    ($t:ident, $vtype:ident) => {
+        impl FromRegValue for $t {
+            fn from_reg_value(val: &RegValue) -> io::Result<$t> {
+                match val.vtype {
+                    $vtype => $t::from_bytes(&val.bytes),
+                    _ => werr!(Foundation::ERROR_BAD_FILE_TYPE),
+                }
+            }
+        }
+
+        impl ToRegValue for $t {
+            fn to_reg_value(&self) -> RegValue<'_> {
+                RegValue {
+                    bytes: self.to_bytes().into(),
+                    vtype: $vtype,
+                }
+            }
+        }
+    };
+}
+
+resource_reg_value!(ResourceList, REG_RESOURCE_LIST);
+resource_reg_value!(FullResourceDescriptor, REG_FULL_RESOURCE_DESCRIPTOR);
+resource_reg_value!(ResourceRequirementsList, REG_RESOURCE_REQUIREMENTS_LIST);
@@ -0,0 +1,247 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Modules for `#[serde(with = "...")]` that store a field as a value of the exact `RegType`
+//! instead of the one the `Encoder` picks from the serde data model.
+//!
+//! * `expand_sz`: strings as `REG_EXPAND_SZ`
+//! * `big_endian`: `u32` as `REG_DWORD_BIG_ENDIAN`
+//! * `binary`: bytes as `REG_BINARY`
+//! * `none`: bytes as `REG_NONE`
+//! * `link`: strings as `REG_LINK`
+//! * `raw`: a `RegValue` as it is
+//!
+//! The value is passed to the `Encoder` and `Decoder` as a newtype struct with a reserved name,
+//! other serializers see the value type and data as bytes.
+//! Decoding a value of another type fails.
+//!
+//! Fields of internally or adjacently tagged enums and of `#[serde(flatten)]` structures are
+//! buffered by serde, the `Decoder` passes their values the same way unless the type has a match
+//! in the serde data model: `REG_SZ`, `REG_DWORD`, `REG_QWORD`, `REG_MULTI_SZ` and `REG_NONE`.
+//! So there `raw` fields can't read `REG_DWORD`, `REG_QWORD`, `REG_MULTI_SZ` and `REG_NONE`
+//! values, `none` fields can't be read at all, and plain fields can only read these five types.
+//!
+//!```
+//!# use std::error::Error;
+//!use serde_derive::{Deserialize, Serialize};
+//!use winreg::enums::*;
+//!use winreg::mem::MemRegistry;
+//!use winreg::RegistryBackend;
+//!
+//!#[derive(Debug, PartialEq, Serialize, Deserialize)]
+//!struct Settings {
+//!    #[serde(with = "winreg::serde_with::expand_sz")]
+//!    data_dir: String,
+//!    #[serde(with = "winreg::serde_with::big_endian")]
+//!    port: u32,
+//!}
+//!
+//!# fn main() -> Result<(), Box<dyn Error>> {
+//!let hkcu = MemRegistry::new().predef(HKEY_CURRENT_USER);
+//!let (key, _disp) = hkcu.create_subkey("Software\\MyProduct")?;
+//!let settings = Settings {
+//!    data_dir: "%APPDATA%\\MyProduct".to_owned(),
+//!    port: 8080,
+//!};
+//!key.encode(&settings)?;
+//!assert_eq!(key.get_raw_value("data_dir")?.vtype, REG_EXPAND_SZ);
+//!assert_eq!(key.get_raw_value("port")?.vtype, REG_DWORD_BIG_ENDIAN);
+//!assert_eq!(key.decode::<Settings>()?, settings);
+//!# Ok(())
+//!# }
+//!```
+use crate::enums::RegType;
+use crate::types::{FromRegValue, ToRegValue};
+use crate::value::Value;
+use crate::RegValue;
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+
+/// Name of the newtype struct that carries a raw value:
+/// little-endian type followed by the data
+pub(crate) const RAW_VALUE: &str = "$winreg::RegValue";
+
+/// Value type and data as they are passed under `RAW_VALUE`
+pub(crate) fn raw_to_bytes(value: &RegValue) -> Vec<u8> {
+    let mut raw = value.vtype.raw().to_le_bytes().to_vec();
+    raw.extend_from_slice(&value.bytes);
+    raw
+}
+
+/// The opposite of `raw_to_bytes`
+pub(crate) fn raw_from_bytes(mut raw: Vec<u8>) -> Option<RegValue<'static>> {
+    if raw.len() < 4 {
+        return None;
+    }
+    let bytes = raw.split_off(4);
+    let vtype = RegType::from_raw(u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]));
+    Some(RegValue {
+        bytes: bytes.into(),
+        vtype,
+    })
+}
+
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+fn serialize_value<S: Serializer>(value: &RegValue, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(RAW_VALUE, &Bytes(&raw_to_bytes(value)))
+}
+
+struct RawVisitor;
+
+impl<'de> Visitor<'de> for RawVisitor {
+    type Value = RegValue<'static>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("registry value")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        d.deserialize_byte_buf(self)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        self.visit_byte_buf(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        let len = v.len();
+        raw_from_bytes(v).ok_or_else(|| E::invalid_length(len, &self))
+    }
+
+    // a buffered `REG_SZ` value
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Value::Sz(v.to_owned()).into())
+    }
+}
+
+fn deserialize_value<'de, D: Deserializer<'de>>(d: D) -> Result<RegValue<'static>, D::Error> {
+    d.deserialize_newtype_struct(RAW_VALUE, RawVisitor)
+}
+
+fn deserialize_as<'de, T: FromRegValue, D: Deserializer<'de>>(d: D) -> Result<T, D::Error> {
+    T::from_reg_value(&deserialize_value(d)?).map_err(de::Error::custom)
+}
+
+/// Strings as `REG_EXPAND_SZ`, see `types::ExpandSz`
+pub mod expand_sz {
+    use super::*;
+    use crate::types::ExpandSz;
+    use std::ffi::OsStr;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<OsStr> + ?Sized,
+        S: Serializer,
+    {
+        serialize_value(&ExpandSz(value).to_reg_value(), serializer)
+    }
+
+    pub fn deserialize<'de, T: FromRegValue, D: Deserializer<'de>>(d: D) -> Result<T, D::Error> {
+        deserialize_as::<ExpandSz<T>, D>(d).map(|v| v.0)
+    }
+}
+
+/// `u32` as `REG_DWORD_BIG_ENDIAN`, see `types::BigEndian`
+pub mod big_endian {
+    use super::*;
+    use crate::types::BigEndian;
+
+    pub fn serialize<S: Serializer>(value: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_value(&BigEndian(*value).to_reg_value(), serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+        deserialize_as::<BigEndian<u32>, D>(d).map(|v| v.0)
+    }
+}
+
+/// Bytes as `REG_BINARY`, see `types::Binary`
+pub mod binary {
+    use super::*;
+    use crate::types::Binary;
+
+    pub fn serialize<B, S>(value: &B, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        B: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        serialize_value(&Binary(value).to_reg_value(), serializer)
+    }
+
+    pub fn deserialize<'de, B, D>(d: D) -> Result<B, D::Error>
+    where
+        B: From<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        deserialize_as::<Binary<B>, D>(d).map(|v| v.0)
+    }
+}
+
+/// Bytes as `REG_NONE`, see `types::NoneValue`
+pub mod none {
+    use super::*;
+    use crate::types::NoneValue;
+
+    pub fn serialize<B, S>(value: &B, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        B: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        serialize_value(&NoneValue(value).to_reg_value(), serializer)
+    }
+
+    pub fn deserialize<'de, B, D>(d: D) -> Result<B, D::Error>
+    where
+        B: From<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        deserialize_as::<NoneValue<B>, D>(d).map(|v| v.0)
+    }
+}
+
+/// Strings as `REG_LINK`, see `types::Link`
+pub mod link {
+    use super::*;
+    use crate::types::Link;
+    use std::ffi::OsStr;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<OsStr> + ?Sized,
+        S: Serializer,
+    {
+        serialize_value(&Link(value).to_reg_value(), serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(d: D) -> Result<T, D::Error>
+    where
+        Link<T>: FromRegValue,
+        D: Deserializer<'de>,
+    {
+        deserialize_as::<Link<T>, D>(d).map(|v| v.0)
+    }
+}
+
+/// A `RegValue` with its type and data kept as they are
+pub mod raw {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &RegValue, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_value(value, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<RegValue<'static>, D::Error> {
+        deserialize_value(d)
+    }
+}
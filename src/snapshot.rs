@@ -0,0 +1,429 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Lossless snapshots of key trees for storing as JSON, YAML
+//! or any other serde format, e.g. registry baselines kept in git.
+//! Part of `serialization-serde` feature.
+//!
+//! Unlike `decoder::Decoder`, every value keeps its `RegType` and exact bytes,
+//! so exporting a tree and importing it back gives the same keys and values.
+//! Keys and values are sorted the way the registry sorts names, so snapshots
+//! of the same tree are identical.
+//!
+//! A key is an object with its `name`, a list of `values` and a list of subkeys
+//! in `keys`; empty lists are omitted. A value is an object with its `name`
+//! (empty for the default value), `type` (e.g. `"REG_SZ"`, or a hex number
+//! like `"0xffff0012"` for types without a name) and either `data` or `hex`:
+//!
+//! * `REG_SZ`, `REG_EXPAND_SZ` and `REG_LINK`: `data` is a string
+//! * `REG_MULTI_SZ`: `data` is a list of strings
+//! * `REG_DWORD`, `REG_DWORD_BIG_ENDIAN` and `REG_QWORD`: `data` is a number
+//! * everything else, and values of the types above that don't have the canonical form
+//!   (no terminating `NULL`, wrong size, invalid UTF-16):
+//!   `hex` is a string with the raw bytes as hex digits
+//!
+//!```json
+//!{
+//!  "name": "MyProduct",
+//!  "values": [
+//!    { "name": "", "type": "REG_SZ", "data": "default" },
+//!    { "name": "Path", "type": "REG_EXPAND_SZ", "data": "%ProgramFiles%\\MyProduct" },
+//!    { "name": "Port", "type": "REG_DWORD", "data": 8080 },
+//!    { "name": "Servers", "type": "REG_MULTI_SZ", "data": ["a", "b"] },
+//!    { "name": "Token", "type": "REG_BINARY", "hex": "deadbeef" }
+//!  ],
+//!  "keys": [
+//!    { "name": "Plugins" }
+//!  ]
+//!}
+//!```
+//!
+//!```no_run
+//!use std::error::Error;
+//!use std::fs;
+//!use winreg::snapshot::KeySnapshot;
+//!use winreg::RegistryBackend;
+//!
+//!fn save_baseline<K: RegistryBackend>(key: &K) -> Result<(), Box<dyn Error>> {
+//!    let snapshot = KeySnapshot::from_key(key, "MyProduct")?;
+//!    fs::write("baseline.json", serde_json::to_string_pretty(&snapshot)?)?;
+//!    Ok(())
+//!}
+//!
+//!fn restore_baseline<K: RegistryBackend>(key: &K) -> Result<(), Box<dyn Error>> {
+//!    let baseline: KeySnapshot = serde_json::from_str(&fs::read_to_string("baseline.json")?)?;
+//!    baseline.restore(key)?;
+//!    Ok(())
+//!}
+//!```
+use crate::backend::RegistryBackend;
+use crate::common::*;
+use crate::enums::*;
+use crate::reg_value::RegValue;
+use crate::regfile::ExportSource;
+use crate::value::Value;
+use serde::de::{self, Error as _, IgnoredAny, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt::{self, Write as _};
+use std::io;
+
+const TYPES: [RegType; 12] = [
+    REG_NONE,
+    REG_SZ,
+    REG_EXPAND_SZ,
+    REG_BINARY,
+    REG_DWORD,
+    REG_DWORD_BIG_ENDIAN,
+    REG_LINK,
+    REG_MULTI_SZ,
+    REG_RESOURCE_LIST,
+    REG_FULL_RESOURCE_DESCRIPTOR,
+    REG_RESOURCE_REQUIREMENTS_LIST,
+    REG_QWORD,
+];
+
+/// A key with all its values and subkeys.
+/// Empty `values` and `keys` are not serialized.
+#[derive(Debug, Default, PartialEq)]
+pub struct KeySnapshot {
+    pub name: String,
+    pub values: Vec<ValueSnapshot>,
+    pub keys: Vec<KeySnapshot>,
+}
+
+/// A named value, see the module documentation for its serialized form
+#[derive(Debug, PartialEq)]
+pub struct ValueSnapshot {
+    pub name: String,
+    pub value: RegValue<'static>,
+}
+
+impl KeySnapshot {
+    /// Read the whole tree under `key`: a `RegKey`, a `HiveKey` or any other `RegistryBackend`.
+    /// `name` becomes the name of the top key in the snapshot.
+    pub fn from_key<S: ExportSource>(key: &S, name: &str) -> io::Result<KeySnapshot> {
+        let mut values: Vec<ValueSnapshot> = key
+            .values()?
+            .into_iter()
+            .map(|(name, value)| ValueSnapshot {
+                name: name.to_string_lossy().into_owned(),
+                value,
+            })
+            .collect();
+        values.sort_by(|a, b| compare_names(&a.name, &b.name));
+        let mut keys = Vec::new();
+        for sub_name in key.subkey_names()? {
+            let sub = key.open(&sub_name)?;
+            keys.push(KeySnapshot::from_key(&sub, &sub_name.to_string_lossy())?);
+        }
+        keys.sort_by(|a, b| compare_names(&a.name, &b.name));
+        Ok(KeySnapshot {
+            name: name.to_owned(),
+            values,
+            keys,
+        })
+    }
+
+    /// Find a subkey. Key names are not case sensitive.
+    pub fn subkey(&self, path: &str) -> Option<&KeySnapshot> {
+        let mut key = self;
+        for name in path.split('\\').filter(|n| !n.is_empty()) {
+            key = key.keys.iter().find(|k| names_eq(&k.name, name))?;
+        }
+        Some(key)
+    }
+
+    pub fn get_raw_value(&self, name: &str) -> Option<&RegValue<'static>> {
+        self.values
+            .iter()
+            .find(|v| names_eq(&v.name, name))
+            .map(|v| &v.value)
+    }
+
+    /// Write the values and subkeys into `key`, keeping whatever else it contains.
+    /// The name of the top key is not used.
+    pub fn write_to<K: RegistryBackend>(&self, key: &K) -> io::Result<()> {
+        for value in &self.values {
+            key.set_raw_value(&value.name, &value.value)?;
+        }
+        for sub in &self.keys {
+            let (sub_key, _) = key.create_subkey_with_flags(&sub.name, KEY_ALL_ACCESS)?;
+            sub.write_to(&sub_key)?;
+        }
+        Ok(())
+    }
+
+    /// Make `key` an exact copy of the snapshot: delete all its values and subkeys
+    /// and write the snapshot into it.
+    /// `key` needs `DELETE`, `KEY_ENUMERATE_SUB_KEYS`, `KEY_QUERY_VALUE`,
+    /// `KEY_SET_VALUE` and `KEY_CREATE_SUB_KEY` permissions.
+    pub fn restore<K: RegistryBackend>(&self, key: &K) -> io::Result<()> {
+        key.delete_subkey_all("")?;
+        self.write_to(key)
+    }
+}
+
+/// Typed data of a value: a number, a string or a list of strings
+enum Data {
+    Number(u64),
+    Text(String),
+    Lines(Vec<String>),
+}
+
+fn type_name(vtype: &RegType) -> String {
+    match vtype {
+        Other(t) => format!("{:#010x}", t),
+        _ => format!("{:?}", vtype),
+    }
+}
+
+fn type_from_name(name: &str) -> Option<RegType> {
+    match name.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().map(RegType::from_raw),
+        None => TYPES.iter().find(|t| type_name(t) == name).cloned(),
+    }
+}
+
+fn encode_data(vtype: &RegType, data: Data) -> Option<Vec<u8>> {
+    let value = match (vtype, data) {
+        (REG_SZ, Data::Text(s)) => Value::Sz(s),
+        (REG_EXPAND_SZ, Data::Text(s)) => Value::ExpandSz(s),
+        (REG_LINK, Data::Text(s)) => Value::Link(s),
+        (REG_MULTI_SZ, Data::Lines(lines)) => Value::MultiSz(lines),
+        (REG_DWORD, Data::Number(n)) => Value::Dword(u32::try_from(n).ok()?),
+        (REG_DWORD_BIG_ENDIAN, Data::Number(n)) => Value::DwordBigEndian(u32::try_from(n).ok()?),
+        (REG_QWORD, Data::Number(n)) => Value::Qword(n),
+        _ => return None,
+    };
+    Some(value.to_bytes())
+}
+
+/// Typed form of the value, only if it gives back exactly the same bytes
+fn decode_data(value: &RegValue) -> Option<Data> {
+    Some(match Value::try_from(value).ok()? {
+        Value::Sz(s) | Value::ExpandSz(s) | Value::Link(s) => Data::Text(s),
+        Value::MultiSz(lines) => Data::Lines(lines),
+        Value::Dword(n) | Value::DwordBigEndian(n) => Data::Number(n.into()),
+        Value::Qword(n) => Data::Number(n),
+        _ => return None,
+    })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        let _ = write!(s, "{:02x}", b);
+    }
+    s
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+const KEY_FIELDS: &[&str] = &["name", "values", "keys"];
+const VALUE_FIELDS: &[&str] = &["name", "type", "data", "hex"];
+
+/// Read the value of a field that may appear only once
+fn next_field<'de, A, T>(
+    map: &mut A,
+    slot: &mut Option<T>,
+    field: &'static str,
+) -> Result<(), A::Error>
+where
+    A: MapAccess<'de>,
+    T: Deserialize<'de>,
+{
+    if slot.is_some() {
+        return Err(A::Error::duplicate_field(field));
+    }
+    *slot = Some(map.next_value()?);
+    Ok(())
+}
+
+impl Serialize for KeySnapshot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let len = 1 + usize::from(!self.values.is_empty()) + usize::from(!self.keys.is_empty());
+        let mut s = serializer.serialize_struct("KeySnapshot", len)?;
+        s.serialize_field("name", &self.name)?;
+        if self.values.is_empty() {
+            s.skip_field("values")?;
+        } else {
+            s.serialize_field("values", &self.values)?;
+        }
+        if self.keys.is_empty() {
+            s.skip_field("keys")?;
+        } else {
+            s.serialize_field("keys", &self.keys)?;
+        }
+        s.end()
+    }
+}
+
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = KeySnapshot;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("key snapshot")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<KeySnapshot, A::Error> {
+        let (mut name, mut values, mut keys) = (None, None, None);
+        while let Some(field) = map.next_key::<String>()? {
+            match field.as_str() {
+                "name" => next_field(&mut map, &mut name, "name")?,
+                "values" => next_field(&mut map, &mut values, "values")?,
+                "keys" => next_field(&mut map, &mut keys, "keys")?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(KeySnapshot {
+            name: name.ok_or_else(|| A::Error::missing_field("name"))?,
+            values: values.unwrap_or_default(),
+            keys: keys.unwrap_or_default(),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for KeySnapshot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("KeySnapshot", KEY_FIELDS, KeyVisitor)
+    }
+}
+
+impl Serialize for Data {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Data::Number(n) => serializer.serialize_u64(*n),
+            Data::Text(s) => serializer.serialize_str(s),
+            Data::Lines(lines) => lines.serialize(serializer),
+        }
+    }
+}
+
+struct DataVisitor;
+
+impl<'de> Visitor<'de> for DataVisitor {
+    type Value = Data;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number, a string or a list of strings")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Data, E> {
+        Ok(Data::Number(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Data, E> {
+        u64::try_from(v)
+            .map(Data::Number)
+            .map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Data, E> {
+        Ok(Data::Text(v.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Data, E> {
+        Ok(Data::Text(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Data, A::Error> {
+        let mut lines = Vec::new();
+        while let Some(line) = seq.next_element()? {
+            lines.push(line);
+        }
+        Ok(Data::Lines(lines))
+    }
+}
+
+impl<'de> Deserialize<'de> for Data {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DataVisitor)
+    }
+}
+
+impl Serialize for ValueSnapshot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("ValueSnapshot", 3)?;
+        s.serialize_field("name", &self.name)?;
+        s.serialize_field("type", &type_name(&self.value.vtype))?;
+        match decode_data(&self.value) {
+            Some(data) => {
+                s.serialize_field("data", &data)?;
+                s.skip_field("hex")?;
+            }
+            None => {
+                s.skip_field("data")?;
+                s.serialize_field("hex", &to_hex(&self.value.bytes))?;
+            }
+        }
+        s.end()
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = ValueSnapshot;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("value snapshot")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ValueSnapshot, A::Error> {
+        let (mut name, mut vtype, mut data) = (None, None, None);
+        let mut hex: Option<String> = None;
+        while let Some(field) = map.next_key::<String>()? {
+            match field.as_str() {
+                "name" => next_field(&mut map, &mut name, "name")?,
+                "type" => next_field(&mut map, &mut vtype, "type")?,
+                "data" => next_field(&mut map, &mut data, "data")?,
+                "hex" => next_field(&mut map, &mut hex, "hex")?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        let name: String = name.ok_or_else(|| A::Error::missing_field("name"))?;
+        let type_str: String = vtype.ok_or_else(|| A::Error::missing_field("type"))?;
+        let vtype = type_from_name(&type_str)
+            .ok_or_else(|| A::Error::custom(format!("unknown value type {:?}", type_str)))?;
+        let bytes = match (data, hex) {
+            (Some(data), None) => encode_data(&vtype, data)
+                .ok_or_else(|| A::Error::custom(format!("data doesn't match type {}", type_str)))?,
+            (None, Some(hex)) => {
+                from_hex(&hex).ok_or_else(|| A::Error::custom("invalid hex data"))?
+            }
+            _ => return Err(A::Error::custom("expected either `data` or `hex`")),
+        };
+        Ok(ValueSnapshot {
+            name,
+            value: RegValue {
+                bytes: bytes.into(),
+                vtype,
+            },
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ValueSnapshot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("ValueSnapshot", VALUE_FIELDS, ValueVisitor)
+    }
+}
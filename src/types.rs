@@ -11,8 +11,6 @@ use crate::RegValue;
 use std::convert::TryInto;
 use std::ffi::{OsStr, OsString};
 use std::io;
-use std::os::windows::ffi::OsStringExt;
-use std::slice;
 use windows_sys::Win32::Foundation;
 
 /// A trait for types that can be loaded from registry values.
@@ -31,11 +29,8 @@ impl FromRegValue for String {
     fn from_reg_value(val: &RegValue) -> io::Result<String> {
         match val.vtype {
             REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ => {
-                let words = unsafe {
-                    #[allow(clippy::cast_ptr_alignment)]
-                    slice::from_raw_parts(val.bytes.as_ptr() as *const u16, val.bytes.len() / 2)
-                };
-                let mut s = String::from_utf16_lossy(words);
+                let words = v8_to_v16(&val.bytes);
+                let mut s = String::from_utf16_lossy(&words);
                 while s.ends_with('\u{0}') {
                     s.pop();
                 }
@@ -53,10 +48,8 @@ impl FromRegValue for Vec<String> {
     fn from_reg_value(val: &RegValue) -> io::Result<Vec<String>> {
         match val.vtype {
             REG_MULTI_SZ => {
-                let words = unsafe {
-                    slice::from_raw_parts(val.bytes.as_ptr() as *const u16, val.bytes.len() / 2)
-                };
-                let mut s = String::from_utf16_lossy(words);
+                let words = v8_to_v16(&val.bytes);
+                let mut s = String::from_utf16_lossy(&words);
                 while s.ends_with('\u{0}') {
                     s.pop();
                 }
@@ -72,14 +65,11 @@ impl FromRegValue for OsString {
     fn from_reg_value(val: &RegValue) -> io::Result<OsString> {
         match val.vtype {
             REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ => {
-                let mut words = unsafe {
-                    #[allow(clippy::cast_ptr_alignment)]
-                    slice::from_raw_parts(val.bytes.as_ptr() as *const u16, val.bytes.len() / 2)
-                };
+                let mut words = v8_to_v16(&val.bytes);
                 while let Some(0) = words.last() {
-                    words = &words[0..words.len() - 1];
+                    words.pop();
                 }
-                let s = OsString::from_wide(words);
+                let s = utf16_to_os_string(&words);
                 Ok(s)
             }
             _ => werr!(Foundation::ERROR_BAD_FILE_TYPE),
@@ -91,15 +81,13 @@ impl FromRegValue for Vec<OsString> {
     fn from_reg_value(val: &RegValue) -> io::Result<Vec<OsString>> {
         match val.vtype {
             REG_MULTI_SZ => {
-                let mut words = unsafe {
-                    slice::from_raw_parts(val.bytes.as_ptr() as *const u16, val.bytes.len() / 2)
-                };
+                let mut words = v8_to_v16(&val.bytes);
                 while let Some(0) = words.last() {
-                    words = &words[0..words.len() - 1];
+                    words.pop();
                 }
                 let v: Vec<OsString> = words
                     .split(|ch| *ch == 0u16)
-                    .map(OsString::from_wide)
+                    .map(utf16_to_os_string)
                     .collect();
                 Ok(v)
             }
@@ -121,7 +109,7 @@ macro_rules! try_from_reg_value_int {
 impl FromRegValue for u32 {
     fn from_reg_value(val: &RegValue) -> io::Result<u32> {
         match val.vtype {
-            REG_DWORD => try_from_reg_value_int!(val, u32::from_ne_bytes),
+            REG_DWORD => try_from_reg_value_int!(val, u32::from_le_bytes),
             REG_DWORD_BIG_ENDIAN => try_from_reg_value_int!(val, u32::from_be_bytes),
             _ => werr!(Foundation::ERROR_BAD_FILE_TYPE),
         }
@@ -131,7 +119,7 @@ impl FromRegValue for u32 {
 impl FromRegValue for u64 {
     fn from_reg_value(val: &RegValue) -> io::Result<u64> {
         match val.vtype {
-            REG_QWORD => try_from_reg_value_int!(val, u64::from_ne_bytes),
+            REG_QWORD => try_from_reg_value_int!(val, u64::from_le_bytes),
             _ => werr!(Foundation::ERROR_BAD_FILE_TYPE),
         }
     }
@@ -140,6 +128,17 @@ impl FromRegValue for u64 {
 /// A trait for types that can be written into registry values.
 ///
 /// **NOTE:** Adds trailing `NULL` character to `str`, `String`, `OsStr` and `OsString` values
+///
+/// Strings are written as `REG_SZ` and `u32` as `REG_DWORD`, wrap them in `ExpandSz`,
+/// `BigEndian`, `Binary`, `Link` or `NoneValue` to write the other types.
+///
+/// ```
+/// use winreg::enums::*;
+/// use winreg::types::{BigEndian, ExpandSz, ToRegValue};
+///
+/// assert_eq!(ExpandSz("%TEMP%").to_reg_value().vtype, REG_EXPAND_SZ);
+/// assert_eq!(BigEndian(1u32).to_reg_value().bytes[..], [0, 0, 0, 1]);
+/// ```
 pub trait ToRegValue {
     fn to_reg_value(&self) -> RegValue<'_>;
 }
@@ -188,8 +187,7 @@ to_reg_value_multi_sz!(&'a OsStr, 'a);
 
 impl ToRegValue for u32 {
     fn to_reg_value(&self) -> RegValue<'_> {
-        let bytes: Vec<u8> =
-            unsafe { slice::from_raw_parts((self as *const u32) as *const u8, 4).to_vec() };
+        let bytes: Vec<u8> = self.to_le_bytes().to_vec();
         RegValue {
             bytes: bytes.into(),
             vtype: REG_DWORD,
@@ -199,11 +197,138 @@ impl ToRegValue for u32 {
 
 impl ToRegValue for u64 {
     fn to_reg_value(&self) -> RegValue<'_> {
-        let bytes: Vec<u8> =
-            unsafe { slice::from_raw_parts((self as *const u64) as *const u8, 8).to_vec() };
+        let bytes: Vec<u8> = self.to_le_bytes().to_vec();
         RegValue {
             bytes: bytes.into(),
             vtype: REG_QWORD,
         }
     }
 }
+
+/// `REG_EXPAND_SZ` string: `ExpandSz("%SystemRoot%\\System32")`.
+/// Reading requires the value to be `REG_EXPAND_SZ`, `%VAR%` references are not expanded,
+/// see `expand` module for that.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandSz<T>(pub T);
+
+impl<T: AsRef<OsStr>> ToRegValue for ExpandSz<T> {
+    fn to_reg_value(&self) -> RegValue<'_> {
+        RegValue {
+            bytes: v16_to_v8(&to_utf16(&self.0)).into(),
+            vtype: REG_EXPAND_SZ,
+        }
+    }
+}
+
+impl<T: FromRegValue> FromRegValue for ExpandSz<T> {
+    fn from_reg_value(val: &RegValue) -> io::Result<ExpandSz<T>> {
+        match val.vtype {
+            REG_EXPAND_SZ => T::from_reg_value(val).map(ExpandSz),
+            _ => werr!(Foundation::ERROR_BAD_FILE_TYPE),
+        }
+    }
+}
+
+/// `REG_DWORD_BIG_ENDIAN` number. Reading requires the value to be `REG_DWORD_BIG_ENDIAN`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BigEndian<T>(pub T);
+
+impl ToRegValue for BigEndian<u32> {
+    fn to_reg_value(&self) -> RegValue<'_> {
+        let bytes: Vec<u8> = self.0.to_be_bytes().to_vec();
+        RegValue {
+            bytes: bytes.into(),
+            vtype: REG_DWORD_BIG_ENDIAN,
+        }
+    }
+}
+
+impl FromRegValue for BigEndian<u32> {
+    fn from_reg_value(val: &RegValue) -> io::Result<BigEndian<u32>> {
+        match val.vtype {
+            REG_DWORD_BIG_ENDIAN => {
+                try_from_reg_value_int!(val, |b| BigEndian(u32::from_be_bytes(b)))
+            }
+            _ => werr!(Foundation::ERROR_BAD_FILE_TYPE),
+        }
+    }
+}
+
+macro_rules! reg_value_bytes {
+    ($t:ident, $vtype:ident, $doc:expr) => {
+        #[doc=$doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $t<B>(pub B);
+
+        impl<B: AsRef<[u8]>> ToRegValue for $t<B> {
+            fn to_reg_value(&self) -> RegValue<'_> {
+                RegValue {
+                    bytes: self.0.as_ref().into(),
+                    vtype: $vtype,
+                }
+            }
+        }
+
+        impl<B: From<Vec<u8>>> FromRegValue for $t<B> {
+            fn from_reg_value(val: &RegValue) -> io::Result<$t<B>> {
+                match val.vtype {
+                    $vtype => Ok($t(val.bytes.to_vec().into())),
+                    _ => werr!(Foundation::ERROR_BAD_FILE_TYPE),
+                }
+            }
+        }
+    };
+}
+
+reg_value_bytes!(
+    Binary,
+    REG_BINARY,
+    "`REG_BINARY` data: `Binary(vec![1, 2, 3])` or `Binary(&bytes[..])`. \
+     Reading requires the value to be `REG_BINARY`."
+);
+reg_value_bytes!(
+    NoneValue,
+    REG_NONE,
+    "`REG_NONE` data. Reading requires the value to be `REG_NONE`."
+);
+
+/// `REG_LINK`: target path of a symbolic link, e.g. `\Registry\Machine\Software\Target`.
+/// Stored as UTF-16 without a terminating `NULL`. Reading requires the value to be `REG_LINK`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link<T>(pub T);
+
+impl<T: AsRef<OsStr>> ToRegValue for Link<T> {
+    fn to_reg_value(&self) -> RegValue<'_> {
+        let mut words = to_utf16(&self.0);
+        words.pop();
+        RegValue {
+            bytes: v16_to_v8(&words).into(),
+            vtype: REG_LINK,
+        }
+    }
+}
+
+fn link_words(val: &RegValue) -> io::Result<Vec<u16>> {
+    match val.vtype {
+        REG_LINK => {
+            let mut words = v8_to_v16(&val.bytes);
+            while let Some(0) = words.last() {
+                words.pop();
+            }
+            Ok(words)
+        }
+        _ => werr!(Foundation::ERROR_BAD_FILE_TYPE),
+    }
+}
+
+impl FromRegValue for Link<String> {
+    fn from_reg_value(val: &RegValue) -> io::Result<Link<String>> {
+        Ok(Link(String::from_utf16_lossy(&link_words(val)?)))
+    }
+}
+
+impl FromRegValue for Link<OsString> {
+    fn from_reg_value(val: &RegValue) -> io::Result<Link<OsString>> {
+        Ok(Link(utf16_to_os_string(&link_words(val)?)))
+    }
+}
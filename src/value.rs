@@ -0,0 +1,221 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Typed registry values
+use crate::common::*;
+use crate::enums::*;
+use crate::reg_value::RegValue;
+use crate::types::{FromRegValue, ToRegValue};
+use std::convert::TryFrom;
+use std::io;
+use windows_sys::Win32::Foundation;
+
+/// Registry value decoded according to its type.
+///
+/// Conversion from `RegValue` is lossless: `Value::try_from(&raw)` converted back
+/// gives exactly the same type and bytes. Values that don't have the canonical form
+/// of their type can't be decoded without losing data and fail with `ERROR_INVALID_DATA`:
+/// strings without a terminating `NULL` or with invalid UTF-16, numbers of a wrong size.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use winreg::enums::*;
+/// use winreg::{RegValue, Value};
+///
+/// let raw: RegValue = Value::ExpandSz("%SystemRoot%".to_owned()).into();
+/// assert_eq!(raw.vtype, REG_EXPAND_SZ);
+/// assert_eq!(Value::try_from(&raw).unwrap(), Value::ExpandSz("%SystemRoot%".to_owned()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// `REG_SZ`, stored with a terminating `NULL`
+    Sz(String),
+    /// `REG_EXPAND_SZ`, stored with a terminating `NULL`
+    ExpandSz(String),
+    /// `REG_MULTI_SZ`, every string is stored with a terminating `NULL`
+    /// and the list ends with an extra `NULL`
+    MultiSz(Vec<String>),
+    /// `REG_DWORD`, little-endian
+    Dword(u32),
+    /// `REG_DWORD_BIG_ENDIAN`
+    DwordBigEndian(u32),
+    /// `REG_QWORD`, little-endian
+    Qword(u64),
+    /// `REG_BINARY`
+    Binary(Vec<u8>),
+    /// `REG_LINK`: target path of a symbolic link, stored without a terminating `NULL`
+    Link(String),
+    /// `REG_RESOURCE_LIST`
+    ResourceList(Vec<u8>),
+    /// `REG_FULL_RESOURCE_DESCRIPTOR`
+    FullResourceDescriptor(Vec<u8>),
+    /// `REG_RESOURCE_REQUIREMENTS_LIST`
+    ResourceRequirementsList(Vec<u8>),
+    /// `REG_NONE`
+    None(Vec<u8>),
+    /// Any other raw value type, see `RegType::Other`
+    Unknown(u32, Vec<u8>),
+}
+
+fn invalid_data<T>() -> io::Result<T> {
+    werr!(Foundation::ERROR_INVALID_DATA)
+}
+
+fn decode_str(bytes: &[u8]) -> io::Result<String> {
+    if bytes.len() % 2 != 0 {
+        return invalid_data();
+    }
+    match String::from_utf16(&v8_to_v16(bytes)) {
+        Ok(s) => Ok(s),
+        Err(_) => invalid_data(),
+    }
+}
+
+fn decode_sz(bytes: &[u8]) -> io::Result<String> {
+    match decode_str(bytes)?.strip_suffix('\0') {
+        Some(s) => Ok(s.to_owned()),
+        None => invalid_data(),
+    }
+}
+
+fn decode_multi_sz(bytes: &[u8]) -> io::Result<Vec<String>> {
+    let s = decode_str(bytes)?;
+    // an empty list is a single `NULL`
+    if s == "\0" {
+        return Ok(Vec::new());
+    }
+    match s.strip_suffix("\0\0") {
+        Some(s) => Ok(s.split('\0').map(str::to_owned).collect()),
+        None => invalid_data(),
+    }
+}
+
+fn encode_str(s: &str, terminated: bool) -> Vec<u8> {
+    let mut v: Vec<u16> = s.encode_utf16().collect();
+    if terminated {
+        v.push(0);
+    }
+    v16_to_v8(&v)
+}
+
+impl Value {
+    /// Raw `REG_*` value type
+    pub fn raw_type(&self) -> u32 {
+        use windows_sys::Win32::System::Registry as R;
+        match self {
+            Value::Sz(_) => R::REG_SZ,
+            Value::ExpandSz(_) => R::REG_EXPAND_SZ,
+            Value::MultiSz(_) => R::REG_MULTI_SZ,
+            Value::Dword(_) => R::REG_DWORD,
+            Value::DwordBigEndian(_) => R::REG_DWORD_BIG_ENDIAN,
+            Value::Qword(_) => R::REG_QWORD,
+            Value::Binary(_) => R::REG_BINARY,
+            Value::Link(_) => R::REG_LINK,
+            Value::ResourceList(_) => R::REG_RESOURCE_LIST,
+            Value::FullResourceDescriptor(_) => R::REG_FULL_RESOURCE_DESCRIPTOR,
+            Value::ResourceRequirementsList(_) => R::REG_RESOURCE_REQUIREMENTS_LIST,
+            Value::None(_) => R::REG_NONE,
+            Value::Unknown(t, _) => *t,
+        }
+    }
+
+    /// Data as it is stored in the registry
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Value::Sz(s) | Value::ExpandSz(s) => encode_str(s, true),
+            Value::Link(s) => encode_str(s, false),
+            Value::MultiSz(list) => {
+                let mut bytes = Vec::new();
+                for s in list {
+                    bytes.extend(encode_str(s, true));
+                }
+                bytes.extend_from_slice(&[0, 0]);
+                bytes
+            }
+            Value::Dword(v) => v.to_le_bytes().to_vec(),
+            Value::DwordBigEndian(v) => v.to_be_bytes().to_vec(),
+            Value::Qword(v) => v.to_le_bytes().to_vec(),
+            Value::Binary(b)
+            | Value::ResourceList(b)
+            | Value::FullResourceDescriptor(b)
+            | Value::ResourceRequirementsList(b)
+            | Value::None(b)
+            | Value::Unknown(_, b) => b.clone(),
+        }
+    }
+}
+
+impl TryFrom<&RegValue<'_>> for Value {
+    type Error = io::Error;
+
+    fn try_from(val: &RegValue) -> io::Result<Value> {
+        let bytes = &val.bytes[..];
+        Ok(match val.vtype {
+            REG_SZ => Value::Sz(decode_sz(bytes)?),
+            REG_EXPAND_SZ => Value::ExpandSz(decode_sz(bytes)?),
+            REG_MULTI_SZ => Value::MultiSz(decode_multi_sz(bytes)?),
+            REG_LINK => Value::Link(decode_str(bytes)?),
+            REG_DWORD | REG_DWORD_BIG_ENDIAN => {
+                let b: [u8; 4] = match bytes.try_into() {
+                    Ok(b) => b,
+                    Err(_) => return invalid_data(),
+                };
+                if val.vtype == REG_DWORD {
+                    Value::Dword(u32::from_le_bytes(b))
+                } else {
+                    Value::DwordBigEndian(u32::from_be_bytes(b))
+                }
+            }
+            REG_QWORD => match bytes.try_into() {
+                Ok(b) => Value::Qword(u64::from_le_bytes(b)),
+                Err(_) => return invalid_data(),
+            },
+            REG_BINARY => Value::Binary(bytes.to_vec()),
+            REG_RESOURCE_LIST => Value::ResourceList(bytes.to_vec()),
+            REG_FULL_RESOURCE_DESCRIPTOR => Value::FullResourceDescriptor(bytes.to_vec()),
+            REG_RESOURCE_REQUIREMENTS_LIST => Value::ResourceRequirementsList(bytes.to_vec()),
+            REG_NONE => Value::None(bytes.to_vec()),
+            Other(t) => Value::Unknown(t, bytes.to_vec()),
+        })
+    }
+}
+
+impl TryFrom<RegValue<'_>> for Value {
+    type Error = io::Error;
+
+    fn try_from(val: RegValue) -> io::Result<Value> {
+        Value::try_from(&val)
+    }
+}
+
+impl From<&Value> for RegValue<'static> {
+    fn from(val: &Value) -> RegValue<'static> {
+        RegValue {
+            bytes: val.to_bytes().into(),
+            vtype: RegType::from_raw(val.raw_type()),
+        }
+    }
+}
+
+impl From<Value> for RegValue<'static> {
+    fn from(val: Value) -> RegValue<'static> {
+        RegValue::from(&val)
+    }
+}
+
+impl FromRegValue for Value {
+    fn from_reg_value(val: &RegValue) -> io::Result<Value> {
+        Value::try_from(val)
+    }
+}
+
+impl ToRegValue for Value {
+    fn to_reg_value(&self) -> RegValue<'_> {
+        self.into()
+    }
+}
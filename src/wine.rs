@@ -0,0 +1,598 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parser and writer for registry files of Wine prefixes:
+//! `system.reg`, `user.reg` and `userdef.reg`.
+//!
+//!```no_run
+//!use std::io;
+//!use winreg::wine::WineRegistry;
+//!
+//!fn main() -> io::Result<()> {
+//!    let mut reg = WineRegistry::open("/home/user/.wine/user.reg")?;
+//!    let key = reg.add_key("Software\\Wine\\Direct3D");
+//!    key.set_value("renderer", &"vulkan");
+//!    reg.save("/home/user/.wine/user.reg")
+//!}
+//!```
+use crate::common::*;
+use crate::enums::*;
+use crate::reg_key_metadata::FileTime;
+use crate::reg_value::RegValue;
+use crate::regfile::to_static;
+use crate::types::{FromRegValue, ToRegValue};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use windows_sys::Win32::Foundation;
+
+const HEADER: &str = "WINE REGISTRY Version 2";
+const RELATIVE_TO: &str = ";; All keys relative to ";
+/// Wine wraps hex data once a line gets longer than that
+const HEX_LINE_WIDTH: usize = 76;
+/// Seconds between January 1, 1601 and January 1, 1970
+const EPOCH_DIFF: u64 = 11_644_473_600;
+const TICKS_PER_SEC: u64 = 10_000_000;
+
+/// Content of a Wine registry file
+#[derive(Debug)]
+pub struct WineRegistry {
+    /// Key all paths in the file are relative to, e.g. `\Machine`
+    /// or `\User\S-1-5-21-0-0-0-1000`
+    pub relative_to: String,
+    /// `#arch=` option, `win32` or `win64`
+    pub arch: Option<String>,
+    pub keys: Vec<WineKey>,
+}
+
+/// `[Key]` section
+#[derive(Debug)]
+pub struct WineKey {
+    pub path: String,
+    pub last_write_time: Option<FileTime>,
+    /// `#class=` option
+    pub class: Option<String>,
+    /// `#link` option: the key is a symbolic link,
+    /// the target is in the `SymbolicLinkValue` value
+    pub link: bool,
+    pub values: Vec<WineValue>,
+}
+
+#[derive(Debug)]
+pub struct WineValue {
+    /// Empty for the `Default` value
+    pub name: String,
+    pub value: RegValue<'static>,
+}
+
+fn parse_error<T>(line: usize, msg: &str) -> io::Result<T> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, msg),
+    ))
+}
+
+/// Join lines ending with a backslash, returns them with 1-based line numbers
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut res = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let (n, mut acc) = match current.take() {
+            Some((n, mut acc)) => {
+                acc.push_str(line.trim_start());
+                (n, acc)
+            }
+            None => (i + 1, line.to_owned()),
+        };
+        let len = acc.trim_end().len();
+        if acc[..len].ends_with('\\') && !acc.starts_with(';') {
+            acc.truncate(len - 1);
+            current = Some((n, acc));
+        } else {
+            res.push((n, acc));
+        }
+    }
+    res.extend(current);
+    res
+}
+
+/// Parse a string escaped the way Wine's `dump_strW` does it, up to the unescaped `end`
+/// or up to the end of input if `end` is `None`.
+/// Returns UTF-16 data and the rest of the input after `end`.
+fn parse_escaped(s: &str, end: Option<char>) -> Result<(Vec<u16>, &str), &'static str> {
+    let mut res = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if Some(c) == end {
+            return Ok((res, &s[i + c.len_utf8()..]));
+        }
+        if c != '\\' {
+            let mut buf = [0; 2];
+            res.extend_from_slice(c.encode_utf16(&mut buf));
+            continue;
+        }
+        let c = match chars.next() {
+            Some((_, c)) => c,
+            None => break,
+        };
+        let w = match c {
+            'a' => 7,
+            'b' => 8,
+            't' => 9,
+            'n' => 10,
+            'v' => 11,
+            'f' => 12,
+            'r' => 13,
+            'e' => 27,
+            'x' => {
+                let mut w = 0u16;
+                let mut digits = 0;
+                while let Some(d) = chars.peek().and_then(|(_, c)| c.to_digit(16)) {
+                    if digits == 4 {
+                        break;
+                    }
+                    w = w * 16 + d as u16;
+                    digits += 1;
+                    chars.next();
+                }
+                if digits == 0 {
+                    return Err("invalid hex escape");
+                }
+                w
+            }
+            '0'..='7' => {
+                let mut w = c.to_digit(8).unwrap_or(0) as u16;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|(_, c)| c.to_digit(8)) {
+                        Some(d) => {
+                            w = w * 8 + d as u16;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                w
+            }
+            c => {
+                let mut buf = [0; 2];
+                res.extend_from_slice(c.encode_utf16(&mut buf));
+                continue;
+            }
+        };
+        res.push(w);
+    }
+    match end {
+        Some(_) => Err("unterminated string"),
+        None => Ok((res, "")),
+    }
+}
+
+/// Escape UTF-16 data the way Wine's `dump_strW` does it, `delims` are escaped with a backslash
+fn escape(s: &[u16], delims: [char; 2]) -> String {
+    const ESCAPES: &[u8; 32] = b".......abtnvfr.............e....";
+    let mut out = String::new();
+    for (i, &w) in s.iter().enumerate() {
+        let next = s.get(i + 1).map(|&n| n as u32);
+        if w > 127 {
+            let next_is_hex = next
+                .and_then(char::from_u32)
+                .map(|c| c.is_ascii_hexdigit())
+                .unwrap_or(false);
+            if next_is_hex {
+                out.push_str(&format!("\\x{:04x}", w));
+            } else {
+                out.push_str(&format!("\\x{:x}", w));
+            }
+        } else if w < 32 {
+            let esc = ESCAPES[w as usize];
+            if esc != b'.' {
+                out.push('\\');
+                out.push(esc as char);
+            } else if matches!(next, Some(n) if (b'0' as u32..=b'7' as u32).contains(&n)) {
+                out.push_str(&format!("\\{:03o}", w));
+            } else {
+                out.push_str(&format!("\\{:o}", w));
+            }
+        } else {
+            let c = w as u8 as char;
+            if c == '\\' || c == delims[0] || c == delims[1] {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn escape_str(s: &str, delims: [char; 2]) -> String {
+    escape(&s.encode_utf16().collect::<Vec<_>>(), delims)
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, &'static str> {
+    let hex = hex.trim();
+    if hex.is_empty() {
+        return Ok(Vec::new());
+    }
+    hex.split(',')
+        .map(|b| b.trim())
+        .map(|b| match b.len() {
+            1 | 2 => u8::from_str_radix(b, 16).map_err(|_| "invalid hex data"),
+            _ => Err("invalid hex data"),
+        })
+        .collect()
+}
+
+/// Value type in `str(N):` and `hex(N):` prefixes
+fn parse_type(rest: &str) -> Result<(RegType, &str), &'static str> {
+    let close = rest.find("):").ok_or("invalid value type")?;
+    let raw_type = u32::from_str_radix(&rest[..close], 16).map_err(|_| "invalid value type")?;
+    let vtype = RegType::from_raw(raw_type);
+    Ok((vtype, &rest[close + 2..]))
+}
+
+fn parse_value(line: &str) -> Result<WineValue, &'static str> {
+    let (name, rest) = if let Some(rest) = line.strip_prefix('@') {
+        (Vec::new(), rest)
+    } else if let Some(rest) = line.strip_prefix('"') {
+        parse_escaped(rest, Some('"'))?
+    } else {
+        return Err("expected a value name");
+    };
+    let data = match rest.trim_start().strip_prefix('=') {
+        Some(data) => data.trim_start(),
+        None => return Err("expected '='"),
+    };
+    let string = |vtype: RegType, s: &str| match s.strip_prefix('"') {
+        Some(s) => {
+            let (mut words, _) = parse_escaped(s, Some('"'))?;
+            words.push(0);
+            Ok(RegValue {
+                bytes: v16_to_v8(&words).into(),
+                vtype,
+            })
+        }
+        None => Err("expected a string"),
+    };
+    let value = if data.starts_with('"') {
+        string(REG_SZ, data)?
+    } else if let Some(rest) = data.strip_prefix("str(") {
+        let (vtype, s) = parse_type(rest)?;
+        string(vtype, s)?
+    } else if let Some(hex) = data.strip_prefix("dword:") {
+        let hex = hex.trim();
+        if hex.is_empty() || hex.len() > 8 {
+            return Err("invalid dword");
+        }
+        let v = u32::from_str_radix(hex, 16).map_err(|_| "invalid dword")?;
+        to_static(&v.to_reg_value())
+    } else if let Some(hex) = data.strip_prefix("hex:") {
+        RegValue {
+            bytes: parse_hex(hex)?.into(),
+            vtype: REG_BINARY,
+        }
+    } else if let Some(rest) = data.strip_prefix("hex(") {
+        let (vtype, hex) = parse_type(rest)?;
+        RegValue {
+            bytes: parse_hex(hex)?.into(),
+            vtype,
+        }
+    } else {
+        return Err("unrecognized value data");
+    };
+    Ok(WineValue {
+        name: String::from_utf16_lossy(&name),
+        value,
+    })
+}
+
+fn write_value(out: &mut String, value: &WineValue) {
+    let line_start = out.len();
+    if value.name.is_empty() {
+        out.push_str("@=");
+    } else {
+        out.push('"');
+        out.push_str(&escape_str(&value.name, ['"', '"']));
+        out.push_str("\"=");
+    }
+    let value = &value.value;
+    let bytes = &value.bytes;
+    match value.vtype {
+        REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ
+            if bytes.len() >= 2 && bytes.len() % 2 == 0 && bytes.ends_with(&[0, 0]) =>
+        {
+            if value.vtype != REG_SZ {
+                out.push_str(&format!("str({:x}):", value.vtype.raw()));
+            }
+            let words = v8_to_v16(&bytes[..bytes.len() - 2]);
+            out.push('"');
+            out.push_str(&escape(&words, ['"', '"']));
+            out.push('"');
+            return;
+        }
+        REG_DWORD if bytes.len() == 4 => {
+            let v = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            out.push_str(&format!("dword:{:08x}", v));
+            return;
+        }
+        REG_BINARY => out.push_str("hex:"),
+        _ => out.push_str(&format!("hex({:x}):", value.vtype.raw())),
+    }
+    let mut line_len = out.len() - line_start;
+    for (i, b) in bytes.iter().enumerate() {
+        out.push_str(&format!("{:02x}", b));
+        line_len += 2;
+        if i + 1 < bytes.len() {
+            out.push(',');
+            line_len += 1;
+            if line_len > HEX_LINE_WIDTH {
+                out.push_str("\\\n  ");
+                line_len = 2;
+            }
+        }
+    }
+}
+
+impl WineRegistry {
+    /// Empty file with keys relative to `relative_to`, e.g. `\Machine`
+    pub fn new<P: AsRef<OsStr>>(relative_to: P) -> WineRegistry {
+        WineRegistry {
+            relative_to: relative_to.as_ref().to_string_lossy().into_owned(),
+            arch: None,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Read and parse a Wine registry file
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<WineRegistry> {
+        WineRegistry::parse(&fs::read(path)?)
+    }
+
+    /// Parse raw content of a Wine registry file. It's read as UTF-8 if it's valid,
+    /// and as Latin-1 otherwise. Wine itself escapes everything outside of ASCII.
+    /// Fails with `ErrorKind::InvalidData` and a line number on syntax errors.
+    pub fn parse(data: &[u8]) -> io::Result<WineRegistry> {
+        match std::str::from_utf8(data) {
+            Ok(text) => WineRegistry::parse_str(text),
+            Err(_) => WineRegistry::parse_str(&data.iter().map(|b| *b as char).collect::<String>()),
+        }
+    }
+
+    /// Parse decoded content of a Wine registry file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// use winreg::enums::*;
+    /// use winreg::wine::WineRegistry;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let reg = WineRegistry::parse_str(
+    ///     "WINE REGISTRY Version 2\n\
+    ///      ;; All keys relative to \\\\Machine\n\
+    ///      \n\
+    ///      [Software\\\\Wine] 1700000000\n\
+    ///      \"Version\"=str(2):\"%SystemRoot%\\\\system32\"\n",
+    /// )?;
+    /// let key = reg.key("Software\\Wine").unwrap();
+    /// assert_eq!(key.get_raw_value("Version").unwrap().vtype, REG_EXPAND_SZ);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_str(text: &str) -> io::Result<WineRegistry> {
+        let mut lines = logical_lines(text)
+            .into_iter()
+            .filter(|(_, line)| !line.trim().is_empty());
+        match lines.next() {
+            Some((_, ref header)) if header.trim() == HEADER => {}
+            Some((n, _)) => return parse_error(n, "unknown header"),
+            None => return parse_error(1, "missing header"),
+        }
+        let mut reg = WineRegistry::new("");
+        for (n, line) in lines {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix(RELATIVE_TO) {
+                let (path, _) = parse_escaped(rest, None).or_else(|msg| parse_error(n, msg))?;
+                reg.relative_to = String::from_utf16_lossy(&path);
+                continue;
+            }
+            if line.starts_with(';') {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix('[') {
+                let (path, rest) =
+                    parse_escaped(rest, Some(']')).or_else(|msg| parse_error(n, msg))?;
+                let rest = rest.trim();
+                let last_write_time = if rest.is_empty() {
+                    None
+                } else {
+                    let ticks = rest.parse::<u64>().ok().and_then(|secs| {
+                        secs.checked_add(EPOCH_DIFF)
+                            .and_then(|s| s.checked_mul(TICKS_PER_SEC))
+                    });
+                    match ticks {
+                        Some(ticks) => Some(FileTime::from_u64(ticks)),
+                        None => return parse_error(n, "invalid timestamp"),
+                    }
+                };
+                reg.keys.push(WineKey {
+                    path: String::from_utf16_lossy(&path),
+                    last_write_time,
+                    class: None,
+                    link: false,
+                    values: Vec::new(),
+                });
+                continue;
+            }
+            if let Some(option) = line.strip_prefix('#') {
+                let key = match reg.keys.last_mut() {
+                    Some(key) => key,
+                    None => {
+                        if let Some(arch) = option.strip_prefix("arch=") {
+                            reg.arch = Some(arch.to_owned());
+                        }
+                        continue;
+                    }
+                };
+                if let Some(time) = option.strip_prefix("time=") {
+                    match u64::from_str_radix(time, 16) {
+                        Ok(time) => key.last_write_time = Some(FileTime::from_u64(time)),
+                        Err(_) => return parse_error(n, "invalid time"),
+                    }
+                } else if let Some(class) = option.strip_prefix("class=\"") {
+                    let (class, _) =
+                        parse_escaped(class, Some('"')).or_else(|msg| parse_error(n, msg))?;
+                    key.class = Some(String::from_utf16_lossy(&class));
+                } else if option == "link" {
+                    key.link = true;
+                }
+                continue;
+            }
+            let key = match reg.keys.last_mut() {
+                Some(key) => key,
+                None => return parse_error(n, "value outside of a key section"),
+            };
+            match parse_value(line) {
+                Ok(value) => key.values.push(value),
+                Err(msg) => return parse_error(n, msg),
+            }
+        }
+        Ok(reg)
+    }
+
+    /// Find a key section, case insensitive
+    pub fn key<P: AsRef<OsStr>>(&self, path: P) -> Option<&WineKey> {
+        let path = path.as_ref().to_string_lossy();
+        self.keys.iter().find(|k| names_eq(&k.path, &path))
+    }
+
+    /// Get a key section to add values to, a new one is appended if it doesn't exist.
+    /// Wine creates missing parents when it loads the file.
+    pub fn add_key<P: AsRef<OsStr>>(&mut self, path: P) -> &mut WineKey {
+        let path = path.as_ref().to_string_lossy();
+        let path = path.trim_matches('\\');
+        match self.keys.iter().position(|k| names_eq(&k.path, path)) {
+            Some(i) => &mut self.keys[i],
+            None => {
+                self.keys.push(WineKey {
+                    path: path.to_owned(),
+                    last_write_time: None,
+                    class: None,
+                    link: false,
+                    values: Vec::new(),
+                });
+                self.keys.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Remove the key section with all its subkeys
+    pub fn delete_key<P: AsRef<OsStr>>(&mut self, path: P) {
+        let path = path.as_ref().to_string_lossy();
+        let path = path.trim_matches('\\');
+        self.keys.retain(|k| {
+            let is_subkey = k.path.len() > path.len()
+                && k.path.as_bytes()[path.len()] == b'\\'
+                && names_eq(&k.path[..path.len()], path);
+            !(names_eq(&k.path, path) || is_subkey)
+        });
+    }
+
+    /// Text in the format Wine writes: `\n` line endings and ASCII only
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(HEADER);
+        out.push('\n');
+        out.push_str(RELATIVE_TO);
+        out.push_str(&escape_str(&self.relative_to, ['\\', '\\']));
+        out.push('\n');
+        if let Some(ref arch) = self.arch {
+            out.push_str(&format!("\n#arch={}\n", arch));
+        }
+        for key in &self.keys {
+            out.push_str("\n[");
+            out.push_str(&escape_str(&key.path, ['[', ']']));
+            out.push(']');
+            if let Some(ref time) = key.last_write_time {
+                let ticks = time.as_u64();
+                let secs = (ticks / TICKS_PER_SEC).saturating_sub(EPOCH_DIFF);
+                out.push_str(&format!(
+                    " {}\n#time={:x}{:08x}",
+                    secs,
+                    ticks >> 32,
+                    ticks as u32
+                ));
+            }
+            out.push('\n');
+            if let Some(ref class) = key.class {
+                out.push_str(&format!("#class=\"{}\"\n", escape_str(class, ['"', '"'])));
+            }
+            if key.link {
+                out.push_str("#link\n");
+            }
+            for value in &key.values {
+                write_value(&mut out, value);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_text().into_bytes()
+    }
+
+    pub fn write<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&self.to_bytes())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+}
+
+impl WineKey {
+    pub fn get_raw_value<N: AsRef<OsStr>>(&self, name: N) -> Option<&RegValue<'static>> {
+        let name = name.as_ref().to_string_lossy();
+        self.values
+            .iter()
+            .find(|v| names_eq(&v.name, &name))
+            .map(|v| &v.value)
+    }
+
+    /// Get a value and convert it to a rust type.
+    /// Fails with `ERROR_FILE_NOT_FOUND` if there's no such value.
+    pub fn get_value<T: FromRegValue, N: AsRef<OsStr>>(&self, name: N) -> io::Result<T> {
+        match self.get_raw_value(name) {
+            Some(value) => T::from_reg_value(value),
+            None => werr!(Foundation::ERROR_FILE_NOT_FOUND),
+        }
+    }
+
+    /// Convert a value from a rust type and set it.
+    /// Will set the `Default` value if `name` is an empty string.
+    pub fn set_value<T: ToRegValue, N: AsRef<OsStr>>(&mut self, name: N, value: &T) {
+        self.set_raw_value(name, &value.to_reg_value())
+    }
+
+    /// Set a value, replacing an existing one with the same name (case insensitive)
+    pub fn set_raw_value<N: AsRef<OsStr>>(&mut self, name: N, value: &RegValue) {
+        let name = name.as_ref().to_string_lossy();
+        let value = to_static(value);
+        match self.values.iter_mut().find(|v| names_eq(&v.name, &name)) {
+            Some(v) => v.value = value,
+            None => self.values.push(WineValue {
+                name: name.into_owned(),
+                value,
+            }),
+        }
+    }
+
+    pub fn delete_value<N: AsRef<OsStr>>(&mut self, name: N) {
+        let name = name.as_ref().to_string_lossy();
+        self.values.retain(|v| !names_eq(&v.name, &name));
+    }
+}
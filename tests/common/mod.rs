@@ -4,7 +4,11 @@
 // may not be copied, modified, or distributed
 // except according to those terms.
 #![macro_use]
+use winreg::enums::RegType;
+use winreg::RegValue;
 
+#[cfg(windows)]
+#[allow(unused_macros)]
 macro_rules! with_key {
     ($k:ident, $path:expr => $b:block) => {{
         let mut path = "Software\\WinRegRsTest".to_owned();
@@ -14,3 +18,36 @@ macro_rules! with_key {
         winreg::HKCU.delete_subkey_all(path).unwrap();
     }}
 }
+
+/// Elsewhere the key is created in a fresh `MemRegistry`
+#[cfg(not(windows))]
+#[allow(unused_macros)]
+macro_rules! with_key {
+    ($k:ident, $path:expr => $b:block) => {{
+        use winreg::RegistryBackend;
+        let mut path = "Software\\WinRegRsTest".to_owned();
+        path.push_str($path);
+        let hkcu = winreg::mem::MemRegistry::new().predef(winreg::enums::HKEY_CURRENT_USER);
+        let ($k, _disp) = hkcu.create_subkey(&path).unwrap();
+        $b
+        hkcu.delete_subkey_all(path).unwrap();
+    }}
+}
+
+/// `REG_SZ` data of `s`: UTF-16LE with a terminating nul
+#[allow(dead_code)]
+pub fn utf16(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .chain(Some(0))
+        .flat_map(|c| c.to_le_bytes())
+        .collect()
+}
+
+/// Value of type `vtype` with a copy of `bytes`
+#[allow(dead_code)]
+pub fn raw(bytes: &[u8], vtype: RegType) -> RegValue<'static> {
+    RegValue {
+        bytes: bytes.to_vec().into(),
+        vtype,
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use winreg::diff::{diff, DiffEntry, DiffOptions};
+use winreg::enums::*;
+use winreg::hive::{Hive, KeyTree};
+use winreg::mem::{MemKey, MemRegistry};
+use winreg::types::ToRegValue;
+use winreg::RegistryBackend;
+
+/// Snapshot "before" as a hive file
+fn before() -> Hive {
+    let mut root = KeyTree::new("ROOT");
+    let app = root.create_subkey("Software\\App");
+    app.set_value("Version", &"1.0");
+    app.set_value("Removed", &1u32);
+    app.set_value("Same", &2u32);
+    root.create_subkey("Software\\App\\Old\\Nested")
+        .set_value("x", &1u32);
+    root.create_subkey("Software\\Cache").set_value("t", &1u32);
+    Hive::from_bytes(root.to_bytes().unwrap()).unwrap()
+}
+
+/// Live key "after"
+fn after() -> MemKey {
+    let (root, _) = MemRegistry::new()
+        .predef(HKEY_LOCAL_MACHINE)
+        .create_subkey("SOFTWARE")
+        .unwrap();
+    let (app, _) = root.create_subkey("software\\APP").unwrap();
+    app.set_value("version", &"2.0").unwrap();
+    app.set_value("SAME", &2u32).unwrap();
+    app.set_value("", &"default").unwrap();
+    let (new, _) = root.create_subkey("Software\\App\\New").unwrap();
+    new.set_value("Added", &3u64).unwrap();
+    let (cache, _) = root.create_subkey("Software\\Cache").unwrap();
+    cache.set_value("t", &2u32).unwrap();
+    root
+}
+
+#[test]
+fn test_diff_entries() {
+    let hive = before();
+    let changes = diff(&hive.root_key(), &after()).unwrap();
+    assert_eq!(
+        changes.entries,
+        vec![
+            DiffEntry::ValueAdded {
+                key: "Software\\App".to_owned(),
+                name: "".to_owned(),
+                value: "default".to_reg_value(),
+            },
+            DiffEntry::ValueRemoved {
+                key: "Software\\App".to_owned(),
+                name: "Removed".to_owned(),
+                value: 1u32.to_reg_value(),
+            },
+            DiffEntry::ValueModified {
+                key: "Software\\App".to_owned(),
+                name: "Version".to_owned(),
+                old: "1.0".to_reg_value(),
+                new: "2.0".to_reg_value(),
+            },
+            DiffEntry::KeyAdded("Software\\App\\New".to_owned()),
+            DiffEntry::ValueAdded {
+                key: "Software\\App\\New".to_owned(),
+                name: "Added".to_owned(),
+                value: 3u64.to_reg_value(),
+            },
+            DiffEntry::KeyRemoved("Software\\App\\Old".to_owned()),
+            DiffEntry::KeyRemoved("Software\\App\\Old\\Nested".to_owned()),
+            DiffEntry::ValueRemoved {
+                key: "Software\\App\\Old\\Nested".to_owned(),
+                name: "x".to_owned(),
+                value: 1u32.to_reg_value(),
+            },
+            DiffEntry::ValueModified {
+                key: "Software\\Cache".to_owned(),
+                name: "t".to_owned(),
+                old: 1u32.to_reg_value(),
+                new: 2u32.to_reg_value(),
+            },
+        ]
+    );
+    assert!(diff(&hive.root_key(), &hive.root_key()).unwrap().is_empty());
+}
+
+#[test]
+fn test_diff_filters() {
+    let hive = before();
+    let diff = DiffOptions::new()
+        .ignore_key("*\\cache")
+        .ignore_key("Software\\App\\Old")
+        .ignore_value("Software\\App", "version")
+        .ignore_value("Software\\App\\*", "*")
+        .diff(&hive.root_key(), &after())
+        .unwrap();
+    assert_eq!(
+        diff.entries,
+        vec![
+            DiffEntry::ValueAdded {
+                key: "Software\\App".to_owned(),
+                name: "".to_owned(),
+                value: "default".to_reg_value(),
+            },
+            DiffEntry::ValueRemoved {
+                key: "Software\\App".to_owned(),
+                name: "Removed".to_owned(),
+                value: 1u32.to_reg_value(),
+            },
+            DiffEntry::KeyAdded("Software\\App\\New".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_diff_to_reg_file() {
+    let hive = before();
+    let text = diff(&hive.root_key(), &after())
+        .unwrap()
+        .to_reg_file("HKEY_LOCAL_MACHINE\\SOFTWARE\\")
+        .to_text()
+        .unwrap();
+    assert_eq!(
+        text,
+        "Windows Registry Editor Version 5.00\r
+\r
+[HKEY_LOCAL_MACHINE\\SOFTWARE\\Software\\App]\r
+@=\"default\"\r
+\"Removed\"=-\r
+\"Version\"=\"2.0\"\r
+\r
+[HKEY_LOCAL_MACHINE\\SOFTWARE\\Software\\App\\New]\r
+\"Added\"=hex(b):03,00,00,00,00,00,00,00\r
+\r
+[-HKEY_LOCAL_MACHINE\\SOFTWARE\\Software\\App\\Old]\r
+\r
+[HKEY_LOCAL_MACHINE\\SOFTWARE\\Software\\Cache]\r
+\"t\"=dword:00000002\r
+\r
+"
+    );
+}
@@ -0,0 +1,84 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use std::collections::HashMap;
+use windows_sys::Win32::Foundation;
+use winreg::enums::*;
+use winreg::expand::{expand, ProcessEnv, RegistryEnv};
+use winreg::mem::MemRegistry;
+use winreg::{RegistryBackend, Value};
+
+fn env() -> HashMap<String, String> {
+    let mut env = HashMap::new();
+    env.insert("SystemRoot".to_owned(), "C:\\Windows".to_owned());
+    env.insert("A".to_owned(), "1".to_owned());
+    env.insert("Percent".to_owned(), "%A%".to_owned());
+    env
+}
+
+#[test]
+fn test_expand_rules() {
+    let env = env();
+    let cases = [
+        ("%SystemRoot%\\System32", "C:\\Windows\\System32"),
+        ("%SYSTEMROOT%", "C:\\Windows"),
+        ("%A%%A%", "11"),
+        ("x%A%y", "x1y"),
+        ("%Missing%", "%Missing%"),
+        ("%Missing%A%", "%Missing%A%"),
+        ("%Missing%%A%", "%Missing%1"),
+        ("%%", "%%"),
+        ("%%A%", "%%A%"),
+        ("50%", "50%"),
+        ("%A%50%", "150%"),
+        ("%Percent%", "%A%"),
+        ("no refs", "no refs"),
+        ("", ""),
+    ];
+    for (s, expected) in cases.iter() {
+        assert_eq!(&expand(s, &env), expected, "expanding {:?}", s);
+    }
+}
+
+#[test]
+fn test_expand_process_env() {
+    std::env::set_var("WINREG_EXPAND_TEST", "value");
+    assert_eq!(expand("<%WINREG_EXPAND_TEST%>", ProcessEnv), "<value>");
+    assert_eq!(expand("<%winreg_expand_test%>", ProcessEnv), "<value>");
+}
+
+#[test]
+fn test_expanded_value_with_registry_env() {
+    let registry = MemRegistry::new();
+    let (user, _) = registry
+        .predef(HKEY_CURRENT_USER)
+        .create_subkey("Environment")
+        .unwrap();
+    let (system, _) = registry
+        .predef(HKEY_LOCAL_MACHINE)
+        .create_subkey("SYSTEM\\CurrentControlSet\\Control\\Session Manager\\Environment")
+        .unwrap();
+    system.set_value("SystemRoot", &"C:\\Windows").unwrap();
+    system.set_value("TEMP", &"C:\\Temp").unwrap();
+    user.set_typed_value("TEMP", &Value::ExpandSz("%SystemRoot%\\Temp".to_owned()))
+        .unwrap();
+    let env = RegistryEnv::new().key(user.clone()).key(system);
+
+    user.set_typed_value("Cache", &Value::ExpandSz("%temp%\\%Missing%".to_owned()))
+        .unwrap();
+    assert_eq!(
+        user.get_expanded_value("Cache", &env).unwrap(),
+        "C:\\Windows\\Temp\\%Missing%"
+    );
+    user.set_value("Plain", &"%TEMP%").unwrap();
+    assert_eq!(user.get_expanded_value("Plain", &env).unwrap(), "%TEMP%");
+    user.set_value("Number", &1u32).unwrap();
+    assert_eq!(
+        user.get_expanded_value("Number", &env)
+            .unwrap_err()
+            .raw_os_error(),
+        Some(Foundation::ERROR_BAD_FILE_TYPE as i32)
+    );
+}
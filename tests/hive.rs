@@ -0,0 +1,702 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use windows_sys::Win32::Foundation;
+use winreg::enums::*;
+use winreg::hive::{Hive, KeyTree};
+use winreg::RegValue;
+
+/// Lays out cells by hand, independently of the crate's own hive code
+struct HiveImage {
+    bins: Vec<u8>,
+}
+
+impl HiveImage {
+    fn new() -> HiveImage {
+        let mut bins = b"hbin".to_vec();
+        bins.resize(32, 0);
+        HiveImage { bins }
+    }
+
+    fn cell(&mut self, data: &[u8]) -> u32 {
+        let offset = self.bins.len() as u32;
+        let size = (data.len() + 4 + 7) & !7;
+        self.bins.extend_from_slice(&(-(size as i32)).to_le_bytes());
+        self.bins.extend_from_slice(data);
+        self.bins.resize(offset as usize + size, 0);
+        offset
+    }
+
+    /// Unallocated cell
+    fn free(&mut self, data: &[u8]) -> u32 {
+        let offset = self.cell(data);
+        let size = -i32::from_le_bytes(self.bins[offset as usize..][..4].try_into().unwrap());
+        self.bins[offset as usize..][..4].copy_from_slice(&size.to_le_bytes());
+        offset
+    }
+
+    /// Merge an unallocated cell with the following one
+    fn merge_free(&mut self, first: u32, second: u32) {
+        let size = |img: &HiveImage, offset: u32| read_u32(&img.bins, offset as usize);
+        let total = size(self, first) + size(self, second);
+        self.bins[first as usize..][..4].copy_from_slice(&total.to_le_bytes());
+    }
+
+    fn nk(&mut self, name: &str, subkeys: (u32, u32), values: (u32, u32)) -> Vec<u8> {
+        let (name, flags) = match name.is_ascii() {
+            true => (name.as_bytes().to_vec(), 0x20u16),
+            false => (utf16(name, false), 0u16),
+        };
+        let mut nk = vec![0u8; 0x4C];
+        nk[..2].copy_from_slice(b"nk");
+        nk[0x02..0x04].copy_from_slice(&flags.to_le_bytes());
+        nk[0x04..0x0C].copy_from_slice(&133_000_000_000_000_000u64.to_le_bytes());
+        nk[0x14..0x18].copy_from_slice(&subkeys.0.to_le_bytes());
+        nk[0x1C..0x20].copy_from_slice(&subkeys.1.to_le_bytes());
+        nk[0x24..0x28].copy_from_slice(&values.0.to_le_bytes());
+        nk[0x28..0x2C].copy_from_slice(&values.1.to_le_bytes());
+        nk[0x48..0x4A].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        nk.extend_from_slice(&name);
+        nk
+    }
+
+    fn vk(&mut self, name: &str, vtype: u32, data: &[u8]) -> u32 {
+        let mut vk = vec![0u8; 0x14];
+        vk[..2].copy_from_slice(b"vk");
+        vk[0x02..0x04].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        if data.len() <= 4 {
+            let mut inline = [0u8; 4];
+            inline[..data.len()].copy_from_slice(data);
+            vk[0x04..0x08].copy_from_slice(&(data.len() as u32 | 0x8000_0000).to_le_bytes());
+            vk[0x08..0x0C].copy_from_slice(&inline);
+        } else {
+            let offset = if data.len() > 16344 {
+                let segments: Vec<u32> = data.chunks(16344).map(|c| self.cell(c)).collect();
+                let list: Vec<u8> = segments.iter().flat_map(|s| s.to_le_bytes()).collect();
+                let list = self.cell(&list);
+                let mut db = b"db".to_vec();
+                db.extend_from_slice(&(segments.len() as u16).to_le_bytes());
+                db.extend_from_slice(&list.to_le_bytes());
+                self.cell(&db)
+            } else {
+                self.cell(data)
+            };
+            vk[0x04..0x08].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            vk[0x08..0x0C].copy_from_slice(&offset.to_le_bytes());
+        }
+        vk[0x0C..0x10].copy_from_slice(&vtype.to_le_bytes());
+        vk[0x10..0x12].copy_from_slice(&1u16.to_le_bytes());
+        vk.extend_from_slice(name.as_bytes());
+        self.cell(&vk)
+    }
+
+    fn list(&mut self, sig: &[u8; 2], entries: &[u32], with_hints: bool) -> u32 {
+        let mut data = sig.to_vec();
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for e in entries {
+            data.extend_from_slice(&e.to_le_bytes());
+            if with_hints {
+                data.extend_from_slice(&[0; 4]);
+            }
+        }
+        self.cell(&data)
+    }
+
+    fn finish(mut self, root: u32) -> Vec<u8> {
+        let size = (self.bins.len() + 4095) & !4095;
+        let free = size - self.bins.len();
+        if free > 0 {
+            let offset = self.bins.len();
+            self.bins.resize(size, 0);
+            self.bins[offset..offset + 4].copy_from_slice(&(free as i32).to_le_bytes());
+        }
+        self.bins[8..12].copy_from_slice(&(size as u32).to_le_bytes());
+
+        let mut base = vec![0u8; 4096];
+        base[..4].copy_from_slice(b"regf");
+        base[0x04..0x08].copy_from_slice(&1u32.to_le_bytes());
+        base[0x08..0x0C].copy_from_slice(&1u32.to_le_bytes());
+        base[0x14..0x18].copy_from_slice(&1u32.to_le_bytes());
+        base[0x18..0x1C].copy_from_slice(&5u32.to_le_bytes());
+        base[0x20..0x24].copy_from_slice(&1u32.to_le_bytes());
+        base[0x24..0x28].copy_from_slice(&root.to_le_bytes());
+        base[0x28..0x2C].copy_from_slice(&(size as u32).to_le_bytes());
+        base[0x2C..0x30].copy_from_slice(&1u32.to_le_bytes());
+        fix_checksum(&mut base);
+        base.extend_from_slice(&self.bins);
+        base
+    }
+}
+
+fn fix_checksum(base: &mut [u8]) {
+    let checksum = base[..508].chunks(4).fold(0u32, |acc, c| {
+        acc ^ u32::from_le_bytes([c[0], c[1], c[2], c[3]])
+    });
+    base[0x1FC..0x200].copy_from_slice(&checksum.to_le_bytes());
+}
+
+fn utf16(s: &str, nul: bool) -> Vec<u8> {
+    let mut v: Vec<u8> = s.encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+    if nul {
+        v.extend_from_slice(&[0, 0]);
+    }
+    v
+}
+
+fn big_data() -> Vec<u8> {
+    (0..40000u32).map(|i| (i % 251) as u8).collect()
+}
+
+/// ROOT
+/// ├── Software
+/// │   ├── App (via `ri` list)
+/// │   └── Вложенный
+/// └── System
+fn test_hive() -> Vec<u8> {
+    test_hive_with(42)
+}
+
+fn test_hive_with(dword: u32) -> Vec<u8> {
+    let mut img = HiveImage::new();
+
+    let nested = img.nk("Вложенный", (0, u32::MAX), (0, u32::MAX));
+    let nested = img.cell(&nested);
+    let app_value = img.vk("Version", REG_SZ.raw(), &utf16("1.0", true));
+    let app_values = img.cell(&app_value.to_le_bytes());
+    let app = img.nk("App", (0, u32::MAX), (1, app_values));
+    let app = img.cell(&app);
+    let leaf1 = img.list(b"lf", &[app], true);
+    let leaf2 = img.list(b"li", &[nested], false);
+    let ri = img.list(b"ri", &[leaf1, leaf2], false);
+    let software = img.nk("Software", (2, ri), (0, u32::MAX));
+    let software = img.cell(&software);
+    let system = img.nk("System", (0, u32::MAX), (0, u32::MAX));
+    let system = img.cell(&system);
+    let subkeys = img.list(b"lh", &[software, system], true);
+
+    let values = [
+        img.vk("", REG_SZ.raw(), &utf16("default", true)),
+        img.vk("Dword", REG_DWORD.raw(), &dword.to_le_bytes()),
+        img.vk("Big", REG_BINARY.raw(), &big_data()),
+        img.vk("Unknown", 0xFFFF_0012, &[1, 2, 3, 4, 5]),
+    ];
+    let values: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let values = img.cell(&values);
+    let root = img.nk("ROOT", (2, subkeys), (4, values));
+    let root = img.cell(&root);
+    img.finish(root)
+}
+
+#[test]
+fn test_open_subkey() {
+    let hive = Hive::from_bytes(test_hive()).unwrap();
+    let root = hive.root_key();
+    assert_eq!(root.name().unwrap(), "ROOT");
+    let app = root.open_subkey("SOFTWARE\\app\\").unwrap();
+    assert_eq!(app.name().unwrap(), "App");
+    let nested = root.open_subkey("Software\\вложенный").unwrap();
+    assert_eq!(nested.name().unwrap(), "Вложенный");
+    assert_eq!(root.open_subkey("").unwrap().name().unwrap(), "ROOT");
+    let err = root.open_subkey("Software\\Missing").err().unwrap();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_FILE_NOT_FOUND as i32)
+    );
+}
+
+#[test]
+fn test_enum_keys() {
+    let hive = Hive::from_bytes(test_hive()).unwrap();
+    let root = hive.root_key();
+    let keys: Vec<_> = root.enum_keys().map(|x| x.unwrap()).collect();
+    assert_eq!(keys, vec!["Software", "System"]);
+    let software = root.open_subkey("Software").unwrap();
+    let keys: Vec<_> = software.enum_keys_os_string().map(|x| x.unwrap()).collect();
+    assert_eq!(keys, vec!["App", "Вложенный"]);
+    assert_eq!(software.enum_keys().nth(1).unwrap().unwrap(), "Вложенный");
+}
+
+#[test]
+fn test_values() {
+    let hive = Hive::from_bytes(test_hive()).unwrap();
+    let root = hive.root_key();
+    let default: String = root.get_value("").unwrap();
+    assert_eq!(default, "default");
+    let dword: u32 = root.get_value("DWORD").unwrap();
+    assert_eq!(dword, 42);
+    let big = root.get_raw_value("Big").unwrap();
+    assert_eq!(
+        big,
+        RegValue {
+            bytes: big_data().into(),
+            vtype: REG_BINARY
+        }
+    );
+    let version: String = root
+        .open_subkey("Software\\App")
+        .unwrap()
+        .get_value("Version")
+        .unwrap();
+    assert_eq!(version, "1.0");
+    let err = root.get_raw_value("Missing").unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_FILE_NOT_FOUND as i32)
+    );
+}
+
+#[test]
+fn test_enum_values() {
+    let hive = Hive::from_bytes(test_hive()).unwrap();
+    let values: Vec<_> = hive.root_key().enum_values().map(|v| v.unwrap()).collect();
+    let names: Vec<_> = values.iter().map(|v| v.0.as_str()).collect();
+    assert_eq!(names, vec!["", "Dword", "Big", "Unknown"]);
+    assert_eq!(values[1].1.vtype, REG_DWORD);
+    assert_eq!(values[3].1.vtype, RegType::Other(0xFFFF_0012));
+    assert_eq!(&values[3].1.bytes[..], &[1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_query_info() {
+    let hive = Hive::from_bytes(test_hive()).unwrap();
+    let info = hive.root_key().query_info().unwrap();
+    assert_eq!(info.sub_keys, 2);
+    assert_eq!(info.values, 4);
+    assert_eq!(info.last_write_time.as_u64(), 133_000_000_000_000_000);
+    assert!(!hive.base_block().is_dirty());
+}
+
+#[test]
+fn test_bad_base_block() {
+    let mut data = test_hive();
+    data[0x30] ^= 0xFF;
+    let err = Hive::from_bytes(data).err().unwrap();
+    assert_eq!(err.raw_os_error(), Some(Foundation::ERROR_BADDB as i32));
+    let err = Hive::from_bytes(vec![0; 100]).err().unwrap();
+    assert_eq!(err.raw_os_error(), Some(Foundation::ERROR_BADDB as i32));
+}
+
+#[test]
+fn test_corrupt_cell() {
+    let mut data = test_hive();
+    // point the root key to the hive bin header
+    data[0x24..0x28].copy_from_slice(&0u32.to_le_bytes());
+    fix_checksum(&mut data);
+    let err = Hive::from_bytes(data).err().unwrap();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_REGISTRY_CORRUPT as i32)
+    );
+}
+
+fn marvin32(data: &[u8]) -> u64 {
+    fn block(lo: &mut u32, hi: &mut u32) {
+        *hi ^= *lo;
+        *lo = lo.rotate_left(20);
+        *lo = lo.wrapping_add(*hi);
+        *hi = hi.rotate_left(9);
+        *hi ^= *lo;
+        *lo = lo.rotate_left(27);
+        *lo = lo.wrapping_add(*hi);
+        *hi = hi.rotate_left(19);
+    }
+    let (mut lo, mut hi) = (0x7A4E_55C5u32, 0x82EF_4D88u32);
+    let mut last = 0x80u32;
+    for c in data.chunks(4) {
+        if c.len() == 4 {
+            lo = lo.wrapping_add(read_u32(c, 0));
+            block(&mut lo, &mut hi);
+        } else {
+            let mut tail = [0u8; 4];
+            tail[..c.len()].copy_from_slice(c);
+            tail[c.len()] = 0x80;
+            last = read_u32(&tail, 0);
+        }
+    }
+    lo = lo.wrapping_add(last);
+    block(&mut lo, &mut hi);
+    block(&mut lo, &mut hi);
+    ((hi as u64) << 32) | lo as u64
+}
+
+/// Transaction log in the new format with one entry per hive,
+/// each one containing all hive bins as a single dirty page
+fn log_file(first_sequence: u32, hives: &[Vec<u8>]) -> Vec<u8> {
+    let mut log = hives[0][..512].to_vec();
+    log[0x04..0x08].copy_from_slice(&first_sequence.to_le_bytes());
+    log[0x08..0x0C].copy_from_slice(&first_sequence.to_le_bytes());
+    log[0x1C..0x20].copy_from_slice(&6u32.to_le_bytes());
+    fix_checksum(&mut log);
+    for (i, hive) in hives.iter().enumerate() {
+        let bins = &hive[4096..];
+        let mut entry = b"HvLE".to_vec();
+        entry.resize(40, 0);
+        entry.extend_from_slice(&0u32.to_le_bytes());
+        entry.extend_from_slice(&(bins.len() as u32).to_le_bytes());
+        entry.extend_from_slice(bins);
+        entry.resize((entry.len() + 511) & !511, 0);
+        let size = entry.len() as u32;
+        entry[0x04..0x08].copy_from_slice(&size.to_le_bytes());
+        entry[0x0C..0x10].copy_from_slice(&(first_sequence + i as u32).to_le_bytes());
+        entry[0x10..0x14].copy_from_slice(&(bins.len() as u32).to_le_bytes());
+        entry[0x14..0x18].copy_from_slice(&1u32.to_le_bytes());
+        let hash1 = marvin32(&entry[40..]);
+        entry[0x18..0x20].copy_from_slice(&hash1.to_le_bytes());
+        let hash2 = marvin32(&entry[..0x20]);
+        entry[0x20..0x28].copy_from_slice(&hash2.to_le_bytes());
+        log.extend_from_slice(&entry);
+    }
+    log
+}
+
+/// The hive as it was before the last write: stale data and sequence numbers differ
+fn dirty_hive() -> Vec<u8> {
+    let mut data = test_hive_with(1);
+    data[0x04..0x08].copy_from_slice(&2u32.to_le_bytes());
+    fix_checksum(&mut data);
+    data
+}
+
+fn dword(hive: &Hive) -> u32 {
+    hive.root_key().get_value("Dword").unwrap()
+}
+
+#[test]
+fn test_replay_logs() {
+    let log1 = log_file(1, &[test_hive_with(2)]);
+    let log2 = log_file(2, &[test_hive_with(3), test_hive_with(4)]);
+    let hive = Hive::from_bytes_with_logs(dirty_hive(), &[&log2, &log1]).unwrap();
+    assert_eq!(dword(&hive), 4);
+    assert!(!hive.base_block().is_dirty());
+    assert_eq!(hive.base_block().primary_sequence, 4);
+    assert_eq!(hive.base_block().file_type, 0);
+
+    // the first log is older than the last complete write and is ignored
+    let log1 = log_file(0, &[test_hive_with(2)]);
+    let log2 = log_file(3, &[test_hive_with(3)]);
+    let hive = Hive::from_bytes_with_logs(dirty_hive(), &[&log1, &log2]).unwrap();
+    assert_eq!(dword(&hive), 3);
+}
+
+#[test]
+fn test_replay_bad_entry() {
+    let mut log = log_file(1, &[test_hive_with(2), test_hive_with(3)]);
+    let len = log.len();
+    log[len - 100] ^= 0xFF;
+    let hive = Hive::from_bytes_with_logs(dirty_hive(), &[&log]).unwrap();
+    assert_eq!(dword(&hive), 2);
+
+    log[512 + 100] ^= 0xFF;
+    let hive = Hive::from_bytes_with_logs(dirty_hive(), &[&log]).unwrap();
+    assert_eq!(dword(&hive), 1);
+    assert!(hive.base_block().is_dirty());
+}
+
+#[test]
+fn test_replay_forged_size() {
+    let mut log = log_file(1, &[test_hive_with(2)]);
+    log[512 + 0x10..512 + 0x14].copy_from_slice(&0xFFFF_F000u32.to_le_bytes());
+    let hash2 = marvin32(&log[512..512 + 0x20]);
+    log[512 + 0x20..512 + 0x28].copy_from_slice(&hash2.to_le_bytes());
+    let err = Hive::from_bytes_with_logs(dirty_hive(), &[&log])
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_INVALID_DATA as i32)
+    );
+}
+
+#[test]
+fn test_replay_clean_hive() {
+    let log = log_file(1, &[test_hive_with(2)]);
+    let hive = Hive::from_bytes_with_logs(test_hive(), &[&log]).unwrap();
+    assert_eq!(dword(&hive), 42);
+}
+
+#[test]
+fn test_replay_old_log_format() {
+    let mut log = log_file(1, &[test_hive_with(2)]);
+    log[0x1C..0x20].copy_from_slice(&1u32.to_le_bytes());
+    fix_checksum(&mut log);
+    let hive = Hive::from_bytes_with_logs(dirty_hive(), &[&log]).unwrap();
+    assert_eq!(dword(&hive), 1);
+    assert!(hive.base_block().is_dirty());
+
+    // a usable log is still applied
+    let log2 = log_file(1, &[test_hive_with(2)]);
+    let hive = Hive::from_bytes_with_logs(dirty_hive(), &[&log, &log2]).unwrap();
+    assert_eq!(dword(&hive), 2);
+}
+
+#[test]
+fn test_replay_empty_log() {
+    let hive = Hive::from_bytes_with_logs(dirty_hive(), &[&[]]).unwrap();
+    assert_eq!(dword(&hive), 1);
+    assert!(hive.base_block().is_dirty());
+
+    let log2 = log_file(1, &[test_hive_with(2)]);
+    let hive = Hive::from_bytes_with_logs(dirty_hive(), &[&[], &log2]).unwrap();
+    assert_eq!(dword(&hive), 2);
+    assert!(!hive.base_block().is_dirty());
+}
+
+#[test]
+fn test_open_with_logs() {
+    let dir = std::env::temp_dir().join(format!("winreg-hive-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("NTUSER.DAT");
+    std::fs::write(&path, dirty_hive()).unwrap();
+    assert_eq!(dword(&Hive::open(&path).unwrap()), 1);
+    std::fs::write(dir.join("NTUSER.DAT.LOG1"), b"").unwrap();
+    let hive = Hive::open(&path).unwrap();
+    assert_eq!(dword(&hive), 1);
+    assert!(hive.base_block().is_dirty());
+    std::fs::write(
+        dir.join("NTUSER.DAT.LOG2"),
+        log_file(1, &[test_hive_with(2)]),
+    )
+    .unwrap();
+    let hive = Hive::open(&path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(dword(&hive), 2);
+}
+
+/// ROOT
+/// └── Software
+///     ├── (deleted) Gone, with a deleted value "Secret"
+///     └── (deleted) Orphan, its parent is lost
+/// and deleted values "Lost" and "Reused" without a key
+fn deleted_hive() -> Vec<u8> {
+    let mut img = HiveImage::new();
+    let software = img.nk("Software", (0, u32::MAX), (0, u32::MAX));
+    let software = img.cell(&software);
+    let subkeys = img.list(b"lh", &[software], true);
+    let root = img.nk("ROOT", (1, subkeys), (0, u32::MAX));
+    let root = img.cell(&root);
+    // mark the root key as `KEY_HIVE_ENTRY` and link Software to it
+    img.bins[root as usize + 4 + 0x02] |= 0x04;
+    img.bins[software as usize + 4 + 0x10..][..4].copy_from_slice(&root.to_le_bytes());
+
+    let data = img.free(&utf16("hidden", true));
+    let mut vk = vec![0u8; 0x14];
+    vk[..2].copy_from_slice(b"vk");
+    vk[0x02..0x04].copy_from_slice(&6u16.to_le_bytes());
+    vk[0x04..0x08].copy_from_slice(&14u32.to_le_bytes());
+    vk[0x08..0x0C].copy_from_slice(&data.to_le_bytes());
+    vk[0x0C..0x10].copy_from_slice(&REG_SZ.raw().to_le_bytes());
+    vk[0x10..0x12].copy_from_slice(&1u16.to_le_bytes());
+    vk.extend_from_slice(b"Secret");
+    let secret = img.free(&vk);
+    let values = img.free(&secret.to_le_bytes());
+    let mut gone = img.nk("Gone", (0, u32::MAX), (1, values));
+    gone[0x10..0x14].copy_from_slice(&software.to_le_bytes());
+    img.free(&gone);
+    let mut orphan = img.nk("Orphan", (0, u32::MAX), (0, u32::MAX));
+    orphan[0x10..0x14].copy_from_slice(&0xDEA0u32.to_le_bytes());
+    img.free(&orphan);
+
+    // a value with a resident data that was freed together with its neighbor
+    let mut vk = vec![0u8; 0x14];
+    vk[..2].copy_from_slice(b"vk");
+    vk[0x02..0x04].copy_from_slice(&4u16.to_le_bytes());
+    vk[0x04..0x08].copy_from_slice(&0x8000_0004u32.to_le_bytes());
+    vk[0x08..0x0C].copy_from_slice(&7u32.to_le_bytes());
+    vk[0x0C..0x10].copy_from_slice(&REG_DWORD.raw().to_le_bytes());
+    vk[0x10..0x12].copy_from_slice(&1u16.to_le_bytes());
+    vk.extend_from_slice(b"Lost");
+    let neighbor = img.free(&[0xAA; 20]);
+    let lost = img.free(&vk);
+    img.merge_free(neighbor, lost);
+
+    // a value whose data cell was allocated again for a live value
+    let live = img.cell(&utf16("live", true));
+    let mut vk = vec![0u8; 0x14];
+    vk[..2].copy_from_slice(b"vk");
+    vk[0x02..0x04].copy_from_slice(&6u16.to_le_bytes());
+    vk[0x04..0x08].copy_from_slice(&10u32.to_le_bytes());
+    vk[0x08..0x0C].copy_from_slice(&live.to_le_bytes());
+    vk[0x0C..0x10].copy_from_slice(&REG_SZ.raw().to_le_bytes());
+    vk[0x10..0x12].copy_from_slice(&1u16.to_le_bytes());
+    vk.extend_from_slice(b"Reused");
+    img.free(&vk);
+    img.finish(root)
+}
+
+#[test]
+fn test_deleted_keys() {
+    let hive = Hive::from_bytes(deleted_hive()).unwrap();
+    let keys = hive.deleted_keys();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0].name, "Gone");
+    assert_eq!(keys[0].parent_path.as_deref(), Some("Software"));
+    assert_eq!(keys[0].last_write_time.as_u64(), 133_000_000_000_000_000);
+    assert_eq!(keys[1].name, "Orphan");
+    assert_eq!(keys[1].parent_offset, 0xDEA0);
+    assert_eq!(keys[1].parent_path, None);
+    // deleted keys are not visible through the live tree
+    assert_eq!(
+        hive.root_key()
+            .open_subkey("Software")
+            .unwrap()
+            .enum_keys()
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn test_deleted_values() {
+    let hive = Hive::from_bytes(deleted_hive()).unwrap();
+    let values = hive.deleted_values();
+    assert_eq!(values.len(), 3);
+    assert_eq!(values[0].name, "Secret");
+    assert_eq!(values[0].vtype, REG_SZ);
+    assert_eq!(
+        values[0].data.as_deref(),
+        Some(utf16("hidden", true).as_slice())
+    );
+    assert_eq!(values[0].parent_path.as_deref(), Some("Software\\Gone"));
+    assert_eq!(
+        values[0].last_write_time.as_ref().map(|t| t.as_u64()),
+        Some(133_000_000_000_000_000)
+    );
+    assert_eq!(values[1].name, "Lost");
+    assert_eq!(values[1].vtype, REG_DWORD);
+    assert_eq!(values[1].data.as_deref(), Some(&7u32.to_le_bytes()[..]));
+    assert_eq!(values[1].parent_path, None);
+    assert!(values[1].last_write_time.is_none());
+    assert_eq!(values[2].name, "Reused");
+    assert_eq!(values[2].data, None);
+}
+
+#[test]
+fn test_no_deleted_cells() {
+    let hive = Hive::from_bytes(test_tree().to_bytes().unwrap()).unwrap();
+    assert!(hive.deleted_keys().is_empty());
+    assert!(hive.deleted_values().is_empty());
+}
+
+fn read_u32(data: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes([data[off], data[off + 1], data[off + 2], data[off + 3]])
+}
+
+fn test_tree() -> KeyTree {
+    let mut root = KeyTree::new("Root");
+    root.set_value("", &"default");
+    root.set_value("Dword", &42u32);
+    root.set_raw_value(
+        "Big",
+        &RegValue {
+            bytes: big_data().into(),
+            vtype: REG_BINARY,
+        },
+    );
+    let app = root.create_subkey("Software\\App");
+    app.set_value("Version", &"1.0");
+    app.set_raw_value(
+        "Empty",
+        &RegValue {
+            bytes: Vec::new().into(),
+            vtype: REG_NONE,
+        },
+    );
+    root.create_subkey("software\\Вложенный");
+    let many = root.create_subkey("Many");
+    for i in 0..1100 {
+        many.create_subkey(format!("Key{}", i));
+    }
+    root
+}
+
+#[test]
+fn test_write_round_trip() {
+    let data = test_tree().to_bytes().unwrap();
+    let hive = Hive::from_bytes(data).unwrap();
+    let root = hive.root_key();
+    assert_eq!(root.name().unwrap(), "Root");
+    let default: String = root.get_value("").unwrap();
+    assert_eq!(default, "default");
+    let dword: u32 = root.get_value("dword").unwrap();
+    assert_eq!(dword, 42);
+    assert_eq!(root.get_raw_value("Big").unwrap().bytes, big_data());
+    let app = root.open_subkey("SOFTWARE\\APP").unwrap();
+    let version: String = app.get_value("Version").unwrap();
+    assert_eq!(version, "1.0");
+    assert_eq!(app.get_raw_value("Empty").unwrap().bytes.len(), 0);
+    let software = root.open_subkey("Software").unwrap();
+    let keys: Vec<_> = software.enum_keys().map(|x| x.unwrap()).collect();
+    assert_eq!(keys, vec!["App", "Вложенный"]);
+    let many = root.open_subkey("Many").unwrap();
+    assert_eq!(many.query_info().unwrap().sub_keys, 1100);
+    assert!(many.open_subkey("key1099").is_ok());
+    let keys: Vec<_> = many.enum_keys().map(|x| x.unwrap()).collect();
+    let mut sorted = keys.clone();
+    sorted.sort_by_key(|k| k.to_uppercase());
+    assert_eq!(keys, sorted);
+}
+
+#[test]
+fn test_write_layout() {
+    let data = test_tree().to_bytes().unwrap();
+    assert_eq!(data.len() % 4096, 0);
+    assert_eq!(&data[..4], b"regf");
+    let checksum = data[..508]
+        .chunks(4)
+        .fold(0u32, |acc, c| acc ^ read_u32(c, 0));
+    assert_eq!(read_u32(&data, 0x1FC), checksum);
+    assert_eq!(read_u32(&data, 0x28) as usize, data.len() - 4096);
+
+    // walk the hive bins and their cells
+    let mut offset = 4096;
+    while offset < data.len() {
+        assert_eq!(&data[offset..offset + 4], b"hbin");
+        assert_eq!(read_u32(&data, offset + 4) as usize, offset - 4096);
+        let size = read_u32(&data, offset + 8) as usize;
+        assert_eq!(size % 4096, 0);
+        let mut cell = offset + 32;
+        while cell < offset + size {
+            let cell_size = (read_u32(&data, cell) as i32).unsigned_abs() as usize;
+            assert_eq!(cell_size % 8, 0);
+            assert!(cell_size > 0);
+            cell += cell_size;
+        }
+        assert_eq!(cell, offset + size);
+        offset += size;
+    }
+
+    // the root key references an `lh` list with name hashes
+    let root = 4096 + read_u32(&data, 0x24) as usize;
+    assert_eq!(&data[root + 4..root + 6], b"nk");
+    let list = 4096 + read_u32(&data, root + 4 + 0x1C) as usize;
+    assert_eq!(&data[list + 4..list + 6], b"lh");
+    assert_eq!(u16::from_le_bytes([data[list + 6], data[list + 7]]), 2);
+    let hash = |name: &str| {
+        name.to_uppercase()
+            .encode_utf16()
+            .fold(0u32, |h, c| h.wrapping_mul(37).wrapping_add(c as u32))
+    };
+    assert_eq!(read_u32(&data, list + 12), hash("Many"));
+    assert_eq!(read_u32(&data, list + 20), hash("Software"));
+}
+
+#[test]
+fn test_write_reproducible() {
+    assert_eq!(
+        test_tree().to_bytes().unwrap(),
+        test_tree().to_bytes().unwrap()
+    );
+}
+
+#[test]
+fn test_write_bad_name() {
+    let mut root = KeyTree::new("Root");
+    root.create_subkey("x".repeat(256));
+    let err = root.to_bytes().unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_INVALID_PARAMETER as i32)
+    );
+}
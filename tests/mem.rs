@@ -0,0 +1,203 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use std::io;
+use windows_sys::Win32::Foundation;
+use winreg::enums::*;
+use winreg::mem::MemRegistry;
+use winreg::reg_key_metadata::FileTime;
+use winreg::{RegValue, RegistryBackend};
+
+fn assert_code<T: std::fmt::Debug>(res: io::Result<T>, code: u32) {
+    assert_eq!(res.unwrap_err().raw_os_error(), Some(code as i32));
+}
+
+#[test]
+fn test_mem_names_case_insensitive() {
+    let hkcu = MemRegistry::new().predef(HKEY_CURRENT_USER);
+    let (key, disp) = hkcu.create_subkey("Software\\MyProduct").unwrap();
+    assert_eq!(disp, REG_CREATED_NEW_KEY);
+    let (_, disp) = hkcu.create_subkey("SOFTWARE\\myproduct").unwrap();
+    assert_eq!(disp, REG_OPENED_EXISTING_KEY);
+
+    key.set_value("Port", &8080u32).unwrap();
+    key.set_value("PORT", &8081u32).unwrap();
+    key.set_value("", &"default").unwrap();
+    let values: Vec<_> = key.enum_values().map(|v| v.unwrap().0).collect();
+    assert_eq!(values, vec!["Port", ""]);
+    let key = hkcu.open_subkey("software\\MYPRODUCT").unwrap();
+    assert_eq!(key.get_value::<u32, _>("port").unwrap(), 8081);
+    assert_eq!(key.get_value::<String, _>("").unwrap(), "default");
+
+    let (software, _) = hkcu.create_subkey("Software").unwrap();
+    software.create_subkey("b").unwrap();
+    software.create_subkey("A").unwrap();
+    software.create_subkey("_").unwrap();
+    let keys: Vec<_> = software.enum_keys().map(|k| k.unwrap()).collect();
+    assert_eq!(keys, vec!["A", "b", "MyProduct", "_"]);
+}
+
+#[test]
+fn test_mem_errors() {
+    let hkcu = MemRegistry::new().predef(HKEY_CURRENT_USER);
+    let (key, _) = hkcu.create_subkey("Software\\Test").unwrap();
+    assert_code(
+        hkcu.open_subkey("Software\\Missing"),
+        Foundation::ERROR_FILE_NOT_FOUND,
+    );
+    assert_code(
+        key.get_raw_value("Missing"),
+        Foundation::ERROR_FILE_NOT_FOUND,
+    );
+    assert_code(
+        key.delete_value("Missing"),
+        Foundation::ERROR_FILE_NOT_FOUND,
+    );
+    assert_code(
+        hkcu.create_subkey("\\Software"),
+        Foundation::ERROR_BAD_PATHNAME,
+    );
+    assert_code(
+        hkcu.create_subkey("x".repeat(256)),
+        Foundation::ERROR_INVALID_PARAMETER,
+    );
+
+    key.set_value("Text", &"text").unwrap();
+    let err = key.get_value::<u32, _>("Text").unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_BAD_FILE_TYPE as i32)
+    );
+
+    let read_only = hkcu.open_subkey("Software\\Test").unwrap();
+    assert_code(
+        read_only.set_value("Text", &"x"),
+        Foundation::ERROR_ACCESS_DENIED,
+    );
+    assert_code(
+        read_only.create_subkey("Sub"),
+        Foundation::ERROR_ACCESS_DENIED,
+    );
+    let write_only = hkcu
+        .open_subkey_with_flags("Software\\Test", KEY_SET_VALUE)
+        .unwrap();
+    assert_code(
+        write_only.get_raw_value("Text"),
+        Foundation::ERROR_ACCESS_DENIED,
+    );
+    assert!(write_only.enum_keys().next().unwrap().is_err());
+
+    assert_code(
+        hkcu.delete_subkey("Software"),
+        Foundation::ERROR_ACCESS_DENIED,
+    );
+    assert_code(
+        hkcu.rename_subkey("Software\\Test", "Other\\Name"),
+        Foundation::ERROR_INVALID_PARAMETER,
+    );
+}
+
+#[test]
+fn test_mem_delete_and_rename() {
+    let hkcu = MemRegistry::new().predef(HKEY_CURRENT_USER);
+    let (key, _) = hkcu.create_subkey("Software\\Test\\Sub").unwrap();
+    hkcu.create_subkey("Software\\Other").unwrap();
+    assert_code(
+        hkcu.rename_subkey("Software\\Test", "OTHER"),
+        Foundation::ERROR_ACCESS_DENIED,
+    );
+    hkcu.rename_subkey("Software\\Test", "Renamed").unwrap();
+    key.set_value("Value", &1u32).unwrap();
+    let renamed = hkcu.open_subkey("Software\\Renamed\\Sub").unwrap();
+    assert_eq!(renamed.get_value::<u32, _>("Value").unwrap(), 1);
+    let keys: Vec<_> = hkcu
+        .open_subkey("Software")
+        .unwrap()
+        .enum_keys()
+        .map(|k| k.unwrap())
+        .collect();
+    assert_eq!(keys, vec!["Other", "Renamed"]);
+
+    hkcu.delete_subkey_all("Software\\Renamed").unwrap();
+    assert_code(key.get_raw_value("Value"), Foundation::ERROR_KEY_DELETED);
+    assert_code(key.set_value("Value", &2u32), Foundation::ERROR_KEY_DELETED);
+    assert_code(
+        hkcu.open_subkey("Software\\Renamed"),
+        Foundation::ERROR_FILE_NOT_FOUND,
+    );
+    let (key, disp) = hkcu.create_subkey("Software\\Renamed\\Sub").unwrap();
+    assert_eq!(disp, REG_CREATED_NEW_KEY);
+    assert!(key.get_raw_value("Value").is_err());
+
+    let software = hkcu
+        .open_subkey_with_flags("Software", KEY_ALL_ACCESS)
+        .unwrap();
+    software.set_value("", &1u32).unwrap();
+    software.delete_subkey_all("").unwrap();
+    let info = software.query_info().unwrap();
+    assert_eq!((info.sub_keys, info.values), (0, 0));
+}
+
+#[test]
+fn test_mem_query_info() {
+    let reg = MemRegistry::new();
+    reg.set_time(FileTime::from_u64(100));
+    let hklm = reg.predef(HKEY_LOCAL_MACHINE);
+    let (key, _) = hklm.create_subkey("Software").unwrap();
+    key.create_subkey("Sub").unwrap();
+    key.create_subkey("LongerName").unwrap();
+    key.set_value("Name", &"text").unwrap();
+    key.set_raw_value(
+        "Bin",
+        &RegValue {
+            bytes: vec![0; 20].into(),
+            vtype: REG_BINARY,
+        },
+    )
+    .unwrap();
+    let info = key.query_info().unwrap();
+    assert_eq!(info.sub_keys, 2);
+    assert_eq!(info.max_sub_key_len, 10);
+    assert_eq!(info.values, 2);
+    assert_eq!(info.max_value_name_len, 4);
+    assert_eq!(info.max_value_len, 20);
+    assert_eq!(info.last_write_time.as_u64(), 100);
+
+    reg.set_time(FileTime::from_u64(200));
+    key.delete_value("Name").unwrap();
+    assert_eq!(key.query_info().unwrap().last_write_time.as_u64(), 200);
+    let sub = key.open_subkey("Sub").unwrap();
+    assert_eq!(sub.query_info().unwrap().last_write_time.as_u64(), 100);
+    key.delete_subkey("Sub").unwrap();
+    assert_code(sub.query_info(), Foundation::ERROR_KEY_DELETED);
+
+    // other predefined keys are separate trees
+    assert_code(
+        reg.predef(HKEY_CURRENT_USER).open_subkey("Software"),
+        Foundation::ERROR_FILE_NOT_FOUND,
+    );
+}
+
+#[test]
+fn test_mem_copy_and_export() {
+    let reg = MemRegistry::new();
+    let hkcu = reg.predef(HKEY_CURRENT_USER);
+    let (src, _) = hkcu.create_subkey("Source\\Sub").unwrap();
+    src.set_value("Name", &"value").unwrap();
+    let (dest, _) = hkcu.create_subkey("Dest").unwrap();
+    hkcu.copy_tree("Source", &dest).unwrap();
+
+    let mut out = Vec::new();
+    dest.export_reg("HKEY_CURRENT_USER\\Dest", &mut out)
+        .unwrap();
+    let text: String = String::from_utf16(
+        &out[2..]
+            .chunks(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>(),
+    )
+    .unwrap();
+    assert!(text.contains("[HKEY_CURRENT_USER\\Dest\\Sub]\r\n\"Name\"=\"value\"\r\n"));
+}
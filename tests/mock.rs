@@ -0,0 +1,156 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use std::io;
+use windows_sys::Win32::Foundation;
+use winreg::enums::*;
+use winreg::mem::MemRegistry;
+use winreg::mock::{Call, Fault, MockRegistry, Op};
+use winreg::RegistryBackend;
+
+fn read_all<K: RegistryBackend>(key: &K) -> io::Result<Vec<(String, u32)>> {
+    let mut out = Vec::new();
+    for value in key.enum_values() {
+        let (name, _) = value?;
+        out.push((name.clone(), key.get_value(name)?));
+    }
+    Ok(out)
+}
+
+#[test]
+fn test_mock_enum_ends_early() {
+    let mock = MockRegistry::new();
+    let hkcu = mock.predef(HKEY_CURRENT_USER);
+    let (key, _) = hkcu.create_subkey("Software\\X").unwrap();
+    for i in 0..5u32 {
+        key.set_value(format!("v{}", i), &i).unwrap();
+    }
+    mock.inject(
+        Fault::new(
+            Op::EnumValue,
+            "software\\x",
+            Foundation::ERROR_NO_MORE_ITEMS,
+        )
+        .nth(3),
+    );
+    assert_eq!(read_all(&key).unwrap().len(), 2);
+    assert_eq!(read_all(&key).unwrap().len(), 5);
+
+    mock.inject(Fault::new(
+        Op::EnumKey,
+        "Software",
+        Foundation::ERROR_MORE_DATA,
+    ));
+    let software = hkcu.open_subkey("Software").unwrap();
+    let err = software.enum_keys().next().unwrap().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(Foundation::ERROR_MORE_DATA as i32));
+}
+
+#[test]
+fn test_mock_faults() {
+    let reg = MemRegistry::new();
+    let (key, _) = reg
+        .predef(HKEY_LOCAL_MACHINE)
+        .create_subkey("Software\\X\\Sub")
+        .unwrap();
+    key.set_value("Name", &1u32).unwrap();
+
+    let mock = MockRegistry::from_registry(reg);
+    let hklm = mock.predef(HKEY_LOCAL_MACHINE);
+    mock.inject(Fault::new(
+        Op::OpenKey,
+        "Software\\X",
+        Foundation::ERROR_ACCESS_DENIED,
+    ));
+    mock.inject(
+        Fault::new(
+            Op::GetValue,
+            "Software\\X\\Sub",
+            Foundation::ERROR_KEY_DELETED,
+        )
+        .nth(2),
+    );
+
+    let err = hklm.open_subkey("Software\\X").unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_ACCESS_DENIED as i32)
+    );
+    let x = hklm.open_subkey("Software").unwrap();
+    assert!(x.open_subkey("X").is_err());
+
+    let sub = hklm.open_subkey("Software\\X\\Sub").unwrap();
+    assert_eq!(sub.path(), "Software\\X\\Sub");
+    assert_eq!(sub.get_value::<u32, _>("Name").unwrap(), 1);
+    let err = sub.get_value::<u32, _>("Name").unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_KEY_DELETED as i32)
+    );
+    assert_eq!(sub.get_value::<u32, _>("Name").unwrap(), 1);
+
+    mock.clear_faults();
+    assert!(hklm.open_subkey("Software\\X").is_ok());
+}
+
+#[test]
+fn test_mock_records_calls() {
+    let mock = MockRegistry::new();
+    let hkcu = mock.predef(HKEY_CURRENT_USER);
+    mock.inject(Fault::new(
+        Op::DeleteValue,
+        "Software",
+        Foundation::ERROR_ACCESS_DENIED,
+    ));
+    let (key, _) = hkcu.create_subkey("Software").unwrap();
+    key.set_value("Name", &"value").unwrap();
+    assert!(key.delete_value("Name").is_err());
+    assert_eq!(key.enum_keys().count(), 0);
+    hkcu.rename_subkey("Software", "Renamed").unwrap();
+
+    assert_eq!(
+        mock.calls(),
+        vec![
+            Call {
+                op: Op::CreateKey,
+                path: "Software".to_owned(),
+                name: None,
+                index: None,
+                fault: None,
+            },
+            Call {
+                op: Op::SetValue,
+                path: "Software".to_owned(),
+                name: Some("Name".to_owned()),
+                index: None,
+                fault: None,
+            },
+            Call {
+                op: Op::DeleteValue,
+                path: "Software".to_owned(),
+                name: Some("Name".to_owned()),
+                index: None,
+                fault: Some(Foundation::ERROR_ACCESS_DENIED),
+            },
+            Call {
+                op: Op::EnumKey,
+                path: "Software".to_owned(),
+                name: None,
+                index: Some(0),
+                fault: None,
+            },
+            Call {
+                op: Op::RenameKey,
+                path: "Software".to_owned(),
+                name: Some("Renamed".to_owned()),
+                index: None,
+                fault: None,
+            },
+        ]
+    );
+    assert_eq!(mock.calls_of(Op::SetValue).len(), 1);
+    mock.clear_calls();
+    assert!(mock.calls().is_empty());
+}
@@ -0,0 +1,252 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use std::io;
+use winreg::enums::*;
+use winreg::policy::PolFile;
+use winreg::regfile::{ApplyTarget, Change, DryRun};
+use winreg::types::ToRegValue;
+use winreg::RegValue;
+
+mod common;
+
+use common::utf16;
+
+/// Hand-made record the way Group Policy editors write them
+fn record(key: &str, name: &str, vtype: u32, data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&[b'[', 0]);
+    out.extend(utf16(key));
+    out.extend_from_slice(&[b';', 0]);
+    out.extend(utf16(name));
+    out.extend_from_slice(&[b';', 0]);
+    out.extend_from_slice(&vtype.to_le_bytes());
+    out.extend_from_slice(&[b';', 0]);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&[b';', 0]);
+    out.extend_from_slice(data);
+    out.extend_from_slice(&[b']', 0]);
+    out
+}
+
+fn sample() -> Vec<u8> {
+    let mut data = b"PReg\x01\x00\x00\x00".to_vec();
+    data.extend(record("Software\\Policies\\Test", "", 0, &[]));
+    data.extend(record(
+        "Software\\Policies\\Test",
+        "Dword",
+        4,
+        &[42, 0, 0, 0],
+    ));
+    data.extend(record(
+        "Software\\Policies\\Test",
+        "Name",
+        1,
+        &utf16("value"),
+    ));
+    data.extend(record(
+        "Software\\Policies\\Test",
+        "**del.Gone",
+        1,
+        &utf16(" "),
+    ));
+    data.extend(record(
+        "Software\\Policies\\Other",
+        "**delvals.",
+        1,
+        &utf16(" "),
+    ));
+    data.extend(record(
+        "Software\\Policies\\Other",
+        "**DeleteValues",
+        1,
+        &utf16("A;B;"),
+    ));
+    data.extend(record(
+        "Software\\Policies\\Other",
+        "**DeleteKeys",
+        1,
+        &utf16("Sub1;Sub2"),
+    ));
+    data.extend(record(
+        "Software\\Policies\\Other",
+        "**SecureKey",
+        4,
+        &[1, 0, 0, 0],
+    ));
+    data
+}
+
+fn path(sub: &str) -> String {
+    format!("HKEY_LOCAL_MACHINE\\Software\\Policies\\{}", sub)
+}
+
+#[test]
+fn test_parse_pol() {
+    let file = PolFile::parse(&sample()).unwrap();
+    assert_eq!(file.entries.len(), 8);
+    let dword = &file.entries[1];
+    assert_eq!(dword.key, "Software\\Policies\\Test");
+    assert_eq!(dword.name, "Dword");
+    assert_eq!(dword.value, 42u32.to_reg_value());
+    assert_eq!(file.entries[2].value, "value".to_reg_value());
+    assert_eq!(file.entries[7].name, "**SecureKey");
+}
+
+#[test]
+fn test_pol_round_trip() {
+    let data = sample();
+    assert_eq!(PolFile::parse(&data).unwrap().to_bytes(), data);
+
+    let mut file = PolFile::new();
+    file.set_value("Software\\Policies\\Test", "Dword", &42u32);
+    file.delete_value("Software\\Policies\\Test", "Gone");
+    file.delete_all_values("Software\\Policies\\Other");
+    let mut expected = b"PReg\x01\x00\x00\x00".to_vec();
+    expected.extend(record(
+        "Software\\Policies\\Test",
+        "Dword",
+        4,
+        &[42, 0, 0, 0],
+    ));
+    expected.extend(record(
+        "Software\\Policies\\Test",
+        "**del.Gone",
+        1,
+        &utf16(" "),
+    ));
+    expected.extend(record(
+        "Software\\Policies\\Other",
+        "**delvals.",
+        1,
+        &utf16(" "),
+    ));
+    assert_eq!(file.to_bytes(), expected);
+}
+
+#[test]
+fn test_parse_pol_errors() {
+    let err = PolFile::parse(b"REGEDIT4").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut data = sample();
+    data.pop();
+    let err = PolFile::parse(&data).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut data = b"PReg\x01\x00\x00\x00".to_vec();
+    let mut rec = record("Key", "Name", 3, &[1, 2]);
+    let size_pos = rec.len() - 2 - 2 - 2 - 4;
+    rec[size_pos..size_pos + 4].copy_from_slice(&100u32.to_le_bytes());
+    data.extend(rec);
+    let err = PolFile::parse(&data).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().starts_with("offset "));
+}
+
+#[test]
+fn test_pol_changes() {
+    let file = PolFile::parse(&sample()).unwrap();
+    let mut dry_run = DryRun::default();
+    let results = file.apply("HKEY_LOCAL_MACHINE", &mut dry_run);
+    assert!(results.iter().all(|r| r.result.is_ok()));
+    assert_eq!(
+        dry_run.changes,
+        vec![
+            Change::CreateKey(path("Test")),
+            Change::SetValue(path("Test"), "Dword".to_owned(), 42u32.to_reg_value()),
+            Change::SetValue(path("Test"), "Name".to_owned(), "value".to_reg_value()),
+            Change::DeleteValue(path("Test"), "Gone".to_owned()),
+            Change::DeleteAllValues(path("Other")),
+            Change::DeleteValue(path("Other"), "A".to_owned()),
+            Change::DeleteValue(path("Other"), "B".to_owned()),
+            Change::DeleteKey(path("Other\\Sub1")),
+            Change::DeleteKey(path("Other\\Sub2")),
+        ]
+    );
+    assert_eq!(file.changes("HKEY_LOCAL_MACHINE"), dry_run.changes);
+}
+
+/// Values of a single key, without `delete_all_values`
+struct Values(Vec<String>);
+
+impl ApplyTarget for Values {
+    fn create_key(&mut self, _path: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn delete_key(&mut self, _path: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_value(&mut self, _path: &str, name: &str, _value: &RegValue) -> io::Result<()> {
+        self.0.push(name.to_owned());
+        Ok(())
+    }
+
+    fn delete_value(&mut self, _path: &str, name: &str) -> io::Result<()> {
+        self.0.retain(|n| n != name);
+        Ok(())
+    }
+
+    fn value_names(&mut self, _path: &str) -> io::Result<Vec<String>> {
+        Ok(self.0.clone())
+    }
+}
+
+#[test]
+fn test_pol_default_delete_all_values() {
+    let mut file = PolFile::new();
+    file.set_value("Software\\Policies\\Test", "A", &1u32);
+    file.set_value("Software\\Policies\\Test", "B", &2u32);
+    file.delete_all_values("Software\\Policies\\Test");
+    file.set_value("Software\\Policies\\Test", "C", &3u32);
+    let mut target = Values(Vec::new());
+    let results = file.apply("HKEY_CURRENT_USER", &mut target);
+    assert!(results.iter().all(|r| r.result.is_ok()));
+    assert_eq!(target.0, vec!["C".to_owned()]);
+}
+
+#[test]
+fn test_pol_default_value() {
+    let mut file = PolFile::new();
+    file.set_raw_value(
+        "Software\\Policies\\Test",
+        "",
+        &RegValue {
+            bytes: vec![1, 2].into(),
+            vtype: REG_BINARY,
+        },
+    );
+    let changes = file.changes("HKCU\\");
+    assert_eq!(changes.len(), 2);
+    assert_eq!(
+        changes[1],
+        Change::SetValue(
+            "HKCU\\Software\\Policies\\Test".to_owned(),
+            "".to_owned(),
+            RegValue {
+                bytes: vec![1, 2].into(),
+                vtype: REG_BINARY,
+            }
+        )
+    );
+}
+
+#[cfg(windows)]
+#[test]
+fn test_apply_pol_to_key() {
+    with_key!(key, "ApplyPol" => {
+        key.set_value("Old", &1u32).unwrap();
+        let mut file = PolFile::new();
+        file.delete_all_values("");
+        file.set_value("Sub", "Dword", &42u32);
+        let results = file.apply_to_key(&key).unwrap();
+        assert!(results.iter().all(|r| r.result.is_ok()));
+        assert!(key.get_raw_value("Old").is_err());
+        let sub = key.open_subkey("Sub").unwrap();
+        assert_eq!(sub.get_value::<u32, _>("Dword").unwrap(), 42);
+    });
+}
@@ -3,14 +3,16 @@
 // http://opensource.org/licenses/MIT>. This file
 // may not be copied, modified, or distributed
 // except according to those terms.
+#![cfg(windows)]
 use rand::Rng;
 use std::collections::HashMap;
 use std::ffi::{OsStr, OsString};
+use std::io;
 use tempfile::tempdir;
 use windows_sys::Win32::Foundation;
 use winreg::enums::*;
 use winreg::types::FromRegValue;
-use winreg::{RegKey, RegValue, HKCU, HKLM};
+use winreg::{RegKey, RegValue, RegistryBackend, HKCU, HKLM};
 
 mod common;
 
@@ -290,6 +292,23 @@ fn test_enum_values_os_string() {
     });
 }
 
+#[test]
+fn test_unknown_value_type() {
+    with_key!(key, "UnknownValueType" => {
+        let val = RegValue {
+            bytes: vec![1, 2, 3].into(),
+            vtype: RegType::Other(0xFFFF_0012),
+        };
+        key.set_value("Before", &1u32).unwrap();
+        key.set_raw_value("Device", &val).unwrap();
+        key.set_value("After", &2u32).unwrap();
+        assert_eq!(key.get_raw_value("Device").unwrap(), val);
+        let vals: Vec<_> = key.enum_values().map(|x| x.unwrap()).collect();
+        assert_eq!(vals.len(), 3);
+        assert_eq!(vals[1], ("Device".to_owned(), val));
+    });
+}
+
 #[test]
 fn test_enum_long_values() {
     with_key!(key, "EnumLongValues" => {
@@ -308,3 +327,28 @@ fn test_enum_long_values() {
         }
     });
 }
+
+fn write_settings<K: RegistryBackend>(root: &K) -> io::Result<()> {
+    let (key, disp) = root.create_subkey("Settings")?;
+    assert!(matches!(disp, REG_CREATED_NEW_KEY));
+    key.set_value("server", &"localhost")?;
+    key.set_value("port", &8080u32)
+}
+
+fn read_settings<K: RegistryBackend>(root: &K) -> io::Result<(String, u32)> {
+    let key = root.open_subkey("Settings")?;
+    Ok((key.get_value("server")?, key.get_value("port")?))
+}
+
+#[test]
+fn test_registry_backend() {
+    with_key!(key, "RegistryBackend" => {
+        write_settings(&key).unwrap();
+        assert_eq!(read_settings(&key).unwrap(), ("localhost".to_owned(), 8080));
+        let names: Vec<String> = RegistryBackend::enum_keys(&key).map(|k| k.unwrap()).collect();
+        assert_eq!(names, vec!["Settings".to_owned()]);
+        RegistryBackend::delete_subkey_all(&key, "Settings").unwrap();
+        let err = read_settings(&key).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    });
+}
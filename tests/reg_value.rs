@@ -1,4 +1,8 @@
-use winreg::{types::ToRegValue, RegValue};
+use std::ffi::OsString;
+use windows_sys::Win32::Foundation;
+use winreg::enums::*;
+use winreg::types::{BigEndian, Binary, ExpandSz, FromRegValue, Link, NoneValue, ToRegValue};
+use winreg::RegValue;
 
 macro_rules! test_display {
     ($f:ident, $v:expr) => {
@@ -14,3 +18,143 @@ macro_rules! test_display {
 test_display!(test_display_string, "Test\\123");
 test_display!(test_display_u32, 1234u32);
 test_display!(test_display_u64, 1234567890u64);
+
+#[test]
+fn test_sz_bytes_are_utf16le() {
+    let val = "Ab".to_reg_value();
+    assert_eq!(val.vtype, REG_SZ);
+    assert_eq!(&val.bytes[..], &[0x41, 0, 0x62, 0, 0, 0]);
+    assert_eq!(String::from_reg_value(&val).unwrap(), "Ab");
+}
+
+#[test]
+fn test_int_bytes_are_le() {
+    let val = 0x1234_5678u32.to_reg_value();
+    assert_eq!(&val.bytes[..], &[0x78, 0x56, 0x34, 0x12]);
+    assert_eq!(u32::from_reg_value(&val).unwrap(), 0x1234_5678);
+
+    let val = 0x0102_0304_0506_0708u64.to_reg_value();
+    assert_eq!(&val.bytes[..], &[8, 7, 6, 5, 4, 3, 2, 1]);
+    assert_eq!(u64::from_reg_value(&val).unwrap(), 0x0102_0304_0506_0708);
+}
+
+#[test]
+fn test_dword_big_endian() {
+    let val = RegValue {
+        bytes: vec![0x12, 0x34, 0x56, 0x78].into(),
+        vtype: REG_DWORD_BIG_ENDIAN,
+    };
+    assert_eq!(u32::from_reg_value(&val).unwrap(), 0x1234_5678);
+}
+
+#[test]
+fn test_multi_sz() {
+    let strings = vec!["lorem", "ipsum"];
+    let val = strings.to_reg_value();
+    assert_eq!(val.vtype, REG_MULTI_SZ);
+    let strings: Vec<String> = Vec::from_reg_value(&val).unwrap();
+    assert_eq!(strings, vec!["lorem", "ipsum"]);
+    let os_strings: Vec<OsString> = Vec::from_reg_value(&val).unwrap();
+    assert_eq!(
+        os_strings,
+        vec![OsString::from("lorem"), OsString::from("ipsum")]
+    );
+    assert_eq!(String::from_reg_value(&val).unwrap(), "lorem\nipsum");
+}
+
+#[test]
+fn test_wrong_type() {
+    let val = 1u32.to_reg_value();
+    let err = String::from_reg_value(&val).unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_BAD_FILE_TYPE as i32)
+    );
+    let val = RegValue {
+        bytes: vec![1, 2, 3].into(),
+        vtype: REG_DWORD,
+    };
+    let err = u32::from_reg_value(&val).unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_INVALID_DATA as i32)
+    );
+}
+
+#[test]
+fn test_wrappers() {
+    let val = ExpandSz("%A%").to_reg_value();
+    assert_eq!(val.vtype, REG_EXPAND_SZ);
+    assert_eq!(&val.bytes[..], b"%\0A\0%\0\0\0");
+    let ExpandSz(s) = ExpandSz::<String>::from_reg_value(&val).unwrap();
+    assert_eq!(s, "%A%");
+
+    let val = BigEndian(0x1234_5678u32).to_reg_value();
+    assert_eq!(val.vtype, REG_DWORD_BIG_ENDIAN);
+    assert_eq!(&val.bytes[..], &[0x12, 0x34, 0x56, 0x78]);
+    assert_eq!(
+        BigEndian::<u32>::from_reg_value(&val).unwrap(),
+        BigEndian(0x1234_5678)
+    );
+
+    let bytes = [1u8, 2, 3];
+    let binary = Binary(&bytes[..]);
+    let val = binary.to_reg_value();
+    assert_eq!(val.vtype, REG_BINARY);
+    assert_eq!(
+        Binary::<Vec<u8>>::from_reg_value(&val).unwrap(),
+        Binary(vec![1, 2, 3])
+    );
+
+    let none = NoneValue(Vec::new());
+    let val = none.to_reg_value();
+    assert_eq!(val.vtype, REG_NONE);
+    assert_eq!(
+        NoneValue::<Vec<u8>>::from_reg_value(&val).unwrap(),
+        NoneValue(vec![])
+    );
+
+    let val = Link("\\R").to_reg_value();
+    assert_eq!(val.vtype, REG_LINK);
+    assert_eq!(&val.bytes[..], b"\\\0R\0");
+    assert_eq!(
+        Link::<String>::from_reg_value(&val).unwrap(),
+        Link("\\R".to_owned())
+    );
+    assert_eq!(
+        Link::<OsString>::from_reg_value(&val).unwrap(),
+        Link(OsString::from("\\R"))
+    );
+}
+
+#[test]
+fn test_wrappers_wrong_type() {
+    let sz = "x".to_reg_value();
+    let dword = 1u32.to_reg_value();
+    let errors = vec![
+        ExpandSz::<String>::from_reg_value(&sz).unwrap_err(),
+        BigEndian::<u32>::from_reg_value(&dword).unwrap_err(),
+        Binary::<Vec<u8>>::from_reg_value(&dword).unwrap_err(),
+        NoneValue::<Vec<u8>>::from_reg_value(&dword).unwrap_err(),
+        Link::<String>::from_reg_value(&sz).unwrap_err(),
+    ];
+    for err in errors {
+        assert_eq!(
+            err.raw_os_error(),
+            Some(Foundation::ERROR_BAD_FILE_TYPE as i32)
+        );
+    }
+}
+
+#[test]
+#[cfg(windows)]
+fn test_os_string_unpaired_surrogate() {
+    use std::os::windows::ffi::OsStringExt;
+    let s = OsString::from_wide(&[0x61, 0xD800, 0x62]);
+    let val = s.to_reg_value();
+    assert_eq!(val.bytes, vec![0x61, 0, 0x00, 0xD8, 0x62, 0, 0, 0]);
+    assert_eq!(OsString::from_reg_value(&val).unwrap(), s);
+    let list = vec![s.clone()];
+    let multi = list.to_reg_value();
+    assert_eq!(Vec::<OsString>::from_reg_value(&multi).unwrap(), vec![s]);
+}
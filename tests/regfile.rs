@@ -0,0 +1,404 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use std::io;
+use windows_sys::Win32::Foundation;
+use winreg::enums::*;
+use winreg::hive::{Hive, KeyTree};
+use winreg::regfile::{ApplyTarget, Change, DryRun, RegFile, RegFileVersion};
+use winreg::types::ToRegValue;
+use winreg::RegValue;
+
+mod common;
+
+use common::{raw, utf16};
+
+const SAMPLE: &str = "Windows Registry Editor Version 5.00\r
+\r
+; a comment\r
+[HKEY_CURRENT_USER\\Software\\Test]\r
+@=\"default\"\r
+\"Path\"=\"C:\\\\Program Files\\\\\\\"Quoted\\\"\"\r
+\"Dword\"=dword:0000002a\r
+\"Qword\"=hex(b):2a,00,00,00,00,00,00,00\r
+\"Expand\"=hex(2):25,00,54,00,45,00,4d,00,50,00,25,00,00,00\r
+\"Multi\"=hex(7):61,00,00,00,62,00,00,00,00,00\r
+\"Binary\"=hex:01,02,03,\\\r
+  04,05\r
+\"Empty\"=hex:\r
+\"Gone\"=-\r
+\r
+[-HKEY_CURRENT_USER\\Software\\Old]\r
+\r
+";
+
+fn value(file: &RegFile, key: usize, name: &str) -> Option<RegValue<'static>> {
+    let value = file.keys[key]
+        .values
+        .iter()
+        .find(|v| v.name == name)
+        .unwrap();
+    value.value.as_ref().map(|v| RegValue {
+        bytes: v.bytes.to_vec().into(),
+        vtype: v.vtype.clone(),
+    })
+}
+
+#[test]
+fn test_parse_v5() {
+    let file = RegFile::parse_str(SAMPLE).unwrap();
+    assert_eq!(file.version, RegFileVersion::Regedit5);
+    assert_eq!(file.keys.len(), 2);
+    assert_eq!(file.keys[0].path, "HKEY_CURRENT_USER\\Software\\Test");
+    assert!(!file.keys[0].delete);
+    assert_eq!(file.keys[1].path, "HKEY_CURRENT_USER\\Software\\Old");
+    assert!(file.keys[1].delete);
+
+    let sz = value(&file, 0, "").unwrap();
+    assert_eq!(sz.vtype, REG_SZ);
+    assert_eq!(sz.bytes, utf16("default"));
+    let path = value(&file, 0, "Path").unwrap();
+    assert_eq!(path.bytes, utf16("C:\\Program Files\\\"Quoted\""));
+    let dword = value(&file, 0, "Dword").unwrap();
+    assert_eq!(
+        (dword.vtype, &dword.bytes[..]),
+        (REG_DWORD, &[42, 0, 0, 0][..])
+    );
+    let qword = value(&file, 0, "Qword").unwrap();
+    assert_eq!(qword.vtype, REG_QWORD);
+    assert_eq!(qword.bytes, 42u64.to_le_bytes().to_vec());
+    let expand = value(&file, 0, "Expand").unwrap();
+    assert_eq!(
+        (expand.vtype, expand.bytes.to_vec()),
+        (REG_EXPAND_SZ, utf16("%TEMP%"))
+    );
+    let multi = value(&file, 0, "Multi").unwrap();
+    assert_eq!(multi.vtype, REG_MULTI_SZ);
+    assert_eq!(multi.bytes, b"a\0\0\0b\0\0\0\0\0".to_vec());
+    let binary = value(&file, 0, "Binary").unwrap();
+    assert_eq!(binary.bytes, vec![1, 2, 3, 4, 5]);
+    assert!(value(&file, 0, "Empty").unwrap().bytes.is_empty());
+    assert!(value(&file, 0, "Gone").is_none());
+}
+
+#[test]
+fn test_parse_encodings() {
+    let mut utf16le = vec![0xFF, 0xFE];
+    utf16le.extend(SAMPLE.encode_utf16().flat_map(|c| c.to_le_bytes()));
+    let file = RegFile::parse(&utf16le).unwrap();
+    assert_eq!(file.keys.len(), 2);
+
+    // REGEDIT4 is ANSI: strings in hex(2) are single-byte
+    let mut ansi = b"REGEDIT4\r\n\r\n[HKEY_CURRENT_USER\\Software\\Test]\r\n\"Caf".to_vec();
+    ansi.extend_from_slice(b"\xe9\"=\"\xe9t\xe9\"\r\n\"Expand\"=hex(2):25,54,25,00\r\n");
+    let file = RegFile::parse(&ansi).unwrap();
+    assert_eq!(file.version, RegFileVersion::Regedit4);
+    assert_eq!(value(&file, 0, "Café").unwrap().bytes, utf16("été"));
+    let expand = value(&file, 0, "Expand").unwrap();
+    assert_eq!(expand.bytes, utf16("%T%"));
+}
+
+#[test]
+fn test_parse_errors() {
+    let check = |text: &str, line: &str| {
+        let err = RegFile::parse_str(text).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with(line), "{}", err);
+    };
+    check("", "line 1:");
+    check("REGEDIT5\r\n", "line 1:");
+    check("REGEDIT4\r\n\"A\"=dword:1\r\n", "line 2:");
+    check("REGEDIT4\r\n[HKEY_CURRENT_USER\r\n", "line 2:");
+    check(
+        "REGEDIT4\r\n\r\n[A]\r\n\"A\"=dword:123456789\r\n",
+        "line 4:",
+    );
+    check(
+        "REGEDIT4\r\n[A]\r\n\"A\"=hex:1,2,\\\r\n  3,xy\r\n",
+        "line 3:",
+    );
+    check("REGEDIT4\r\n[A]\r\n\"A=\"1\"\r\n", "line 3:");
+    check("REGEDIT4\r\n[-A]\r\n\"A\"=\"1\"\r\n", "line 3:");
+}
+
+#[test]
+fn test_write_v5() {
+    let mut file = RegFile::new(RegFileVersion::Regedit5);
+    let key = file.add_key("HKEY_CURRENT_USER\\Software\\Test");
+    key.set_value("", &"default");
+    key.set_value("Quote\"d", &"C:\\dir");
+    key.set_value("Dword", &42u32);
+    key.set_value("Qword", &42u64);
+    key.delete_value("Gone");
+    file.delete_key("HKEY_CURRENT_USER\\Software\\Old");
+    let expected = "Windows Registry Editor Version 5.00\r
+\r
+[HKEY_CURRENT_USER\\Software\\Test]\r
+@=\"default\"\r
+\"Quote\\\"d\"=\"C:\\\\dir\"\r
+\"Dword\"=dword:0000002a\r
+\"Qword\"=hex(b):2a,00,00,00,00,00,00,00\r
+\"Gone\"=-\r
+\r
+[-HKEY_CURRENT_USER\\Software\\Old]\r
+\r
+";
+    assert_eq!(file.to_text().unwrap(), expected);
+    let bytes = file.to_bytes().unwrap();
+    assert_eq!(&bytes[..4], &[0xFF, 0xFE, b'W', 0]);
+}
+
+#[test]
+fn test_write_long_hex() {
+    let mut file = RegFile::new(RegFileVersion::Regedit5);
+    let data: Vec<u8> = (0..100).collect();
+    file.add_key("HKEY_CURRENT_USER\\Test").set_raw_value(
+        "Binary",
+        &RegValue {
+            bytes: data.clone().into(),
+            vtype: REG_BINARY,
+        },
+    );
+    let text = file.to_text().unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[3].starts_with("\"Binary\"=hex:00,01,"));
+    assert!(lines[3].ends_with(",\\"));
+    assert!(lines[4].starts_with("  "));
+    assert!(lines.iter().all(|l| l.len() <= 80));
+    let parsed = RegFile::parse_str(&text).unwrap();
+    assert_eq!(value(&parsed, 0, "Binary").unwrap().bytes, data);
+}
+
+#[test]
+fn test_round_trip() {
+    let odd = |vtype| RegValue {
+        bytes: vec![0x61, 0x00, 0x0A, 0x00, 0x00].into(),
+        vtype,
+    };
+    for version in [RegFileVersion::Regedit4, RegFileVersion::Regedit5].iter() {
+        let mut file = RegFile::parse_str(SAMPLE).unwrap();
+        file.version = *version;
+        let key = file.add_key("HKEY_CURRENT_USER\\Software\\Test");
+        key.set_value("Lines", &"a\r\nb");
+        key.set_raw_value("Odd", &odd(REG_BINARY));
+        key.set_raw_value(
+            "ShortDword",
+            &RegValue {
+                bytes: vec![1, 2].into(),
+                vtype: REG_DWORD,
+            },
+        );
+        key.set_raw_value(
+            "Unterminated",
+            &RegValue {
+                bytes: vec![0x61, 0x00].into(),
+                vtype: REG_SZ,
+            },
+        );
+        if *version == RegFileVersion::Regedit5 {
+            key.set_value("Юникод", &"значение");
+            key.set_raw_value("OddSz", &odd(REG_SZ));
+        }
+        let parsed = RegFile::parse(&file.to_bytes().unwrap()).unwrap();
+        assert_eq!(parsed.version, *version);
+        assert_eq!(parsed.keys.len(), file.keys.len());
+        for (k, (a, b)) in file.keys.iter().zip(parsed.keys.iter()).enumerate() {
+            assert_eq!((&a.path, a.delete), (&b.path, b.delete));
+            for v in &a.values {
+                assert_eq!(v.value, value(&parsed, k, &v.name), "{}", v.name);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_regedit4_unrepresentable() {
+    let mut file = RegFile::new(RegFileVersion::Regedit4);
+    file.add_key("HKEY_CURRENT_USER\\Test")
+        .set_value("Name", &"Юникод");
+    let err = file.to_bytes().unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_NO_UNICODE_TRANSLATION as i32)
+    );
+}
+
+#[test]
+fn test_apply_dry_run() {
+    let file = RegFile::parse_str(SAMPLE).unwrap();
+    let mut dry_run = DryRun::default();
+    let results = file.apply(&mut dry_run);
+    assert_eq!(results.len(), 11);
+    assert!(results.iter().all(|r| r.result.is_ok()));
+    let key = "HKEY_CURRENT_USER\\Software\\Test".to_owned();
+    assert_eq!(dry_run.changes[0], Change::CreateKey(key.clone()));
+    assert_eq!(
+        dry_run.changes[3],
+        Change::SetValue(key.clone(), "Dword".to_owned(), 42u32.to_reg_value())
+    );
+    assert_eq!(
+        dry_run.changes[9],
+        Change::DeleteValue(key, "Gone".to_owned())
+    );
+    assert_eq!(
+        dry_run.changes[10],
+        Change::DeleteKey("HKEY_CURRENT_USER\\Software\\Old".to_owned())
+    );
+    let applied: Vec<_> = results.into_iter().map(|r| r.change).collect();
+    assert_eq!(applied, dry_run.changes);
+}
+
+/// Fails to set values with names starting with `Bad`
+struct Failing {
+    deleted: Vec<String>,
+}
+
+impl ApplyTarget for Failing {
+    fn create_key(&mut self, _path: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn delete_key(&mut self, path: &str) -> io::Result<()> {
+        self.deleted.push(path.to_owned());
+        Ok(())
+    }
+
+    fn set_value(&mut self, _path: &str, name: &str, _value: &RegValue) -> io::Result<()> {
+        match name.starts_with("Bad") {
+            true => Err(io::Error::from_raw_os_error(
+                Foundation::ERROR_ACCESS_DENIED as i32,
+            )),
+            false => Ok(()),
+        }
+    }
+
+    fn delete_value(&mut self, _path: &str, _name: &str) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_apply_errors() {
+    let file = RegFile::parse_str(
+        "REGEDIT4\r\n[A]\r\n\"Bad1\"=\"1\"\r\n\"Good\"=\"2\"\r\n\"Bad2\"=\"3\"\r\n[-B]\r\n",
+    )
+    .unwrap();
+    let mut target = Failing {
+        deleted: Vec::new(),
+    };
+    let results = file.apply(&mut target);
+    let failed: Vec<_> = results
+        .iter()
+        .filter(|r| r.result.is_err())
+        .map(|r| match r.change {
+            Change::SetValue(_, ref name, _) => name.as_str(),
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(failed, vec!["Bad1", "Bad2"]);
+    // errors don't stop the import
+    assert_eq!(target.deleted, vec!["B"]);
+}
+
+#[test]
+#[cfg(windows)]
+fn test_apply_registry() {
+    use winreg::regfile::RegistryTarget;
+    with_key!(key, "ApplyRegFile" => {
+        let sandbox = key.open_subkey_with_flags("", KEY_ALL_ACCESS).unwrap();
+        let mut target = RegistryTarget::new().with_root("HKCU", sandbox).unwrap();
+        let results = RegFile::parse_str(SAMPLE).unwrap().apply(&mut target);
+        assert!(results.iter().all(|r| r.result.is_ok()), "{:?}", results);
+        let test = key.open_subkey("Software\\Test").unwrap();
+        let dword: u32 = test.get_value("Dword").unwrap();
+        assert_eq!(dword, 42);
+        let default: String = test.get_value("").unwrap();
+        assert_eq!(default, "default");
+    });
+}
+
+#[test]
+fn test_export_hive_key() {
+    let mut root = KeyTree::new("Root");
+    let key = root.create_subkey("Export");
+    key.set_raw_value("Qword", &raw(&[1, 0, 0, 0, 0, 0, 0, 0], REG_QWORD));
+    key.set_raw_value("None", &raw(&[1], REG_NONE));
+    key.set_raw_value("Multi", &raw(b"a\0\0\0\0\0", REG_MULTI_SZ));
+    key.set_raw_value("Link", &raw(b"a\0", REG_LINK));
+    key.set_raw_value("Big", &raw(&[0, 0, 0, 1], REG_DWORD_BIG_ENDIAN));
+    key.set_raw_value("Res", &raw(&[8], REG_RESOURCE_LIST));
+    key.set_raw_value("Full", &raw(&[9], REG_FULL_RESOURCE_DESCRIPTOR));
+    key.set_raw_value("Req", &raw(&[10], REG_RESOURCE_REQUIREMENTS_LIST));
+    key.set_raw_value("Expand", &raw(b"%\0T\0%\0\0\0", REG_EXPAND_SZ));
+    key.set_raw_value("Binary", &raw(&[0xAB; 30], REG_BINARY));
+    key.set_raw_value("Device", &raw(&[1, 2], RegType::Other(0xFFFF_0012)));
+    key.set_value("dword", &1u32);
+    key.set_value("", &"default");
+    key.create_subkey("b");
+    key.create_subkey("A").set_value("Sz", &"x");
+    let hive = Hive::from_bytes(root.to_bytes().unwrap()).unwrap();
+    let key = hive.root_key().open_subkey("Export").unwrap();
+    let mut out = Vec::new();
+    key.export_reg("HKEY_CURRENT_USER\\Export\\", &mut out)
+        .unwrap();
+    let text: String = String::from_utf16(
+        &out[2..]
+            .chunks(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>(),
+    )
+    .unwrap();
+    let expected = "Windows Registry Editor Version 5.00\r
+\r
+[HKEY_CURRENT_USER\\Export]\r
+\"Qword\"=hex(b):01,00,00,00,00,00,00,00\r
+\"None\"=hex(0):01\r
+\"Multi\"=hex(7):61,00,00,00,00,00\r
+\"Link\"=hex(6):61,00\r
+\"Big\"=hex(5):00,00,00,01\r
+\"Res\"=hex(8):08\r
+\"Full\"=hex(9):09\r
+\"Req\"=hex(a):0a\r
+\"Expand\"=hex(2):25,00,54,00,25,00,00,00\r
+\"Binary\"=hex:ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,ab,\\\r
+  ab,ab,ab,ab,ab,ab,ab,ab\r
+\"Device\"=hex(ffff0012):01,02\r
+\"dword\"=dword:00000001\r
+@=\"default\"\r
+\r
+[HKEY_CURRENT_USER\\Export\\A]\r
+\"Sz\"=\"x\"\r
+\r
+[HKEY_CURRENT_USER\\Export\\b]\r
+\r
+";
+    assert_eq!(text, expected);
+
+    let parsed = RegFile::parse(&out).unwrap();
+    assert_eq!(parsed.keys.len(), 3);
+    assert_eq!(
+        value(&parsed, 0, "Binary").unwrap(),
+        raw(&[0xAB; 30], REG_BINARY)
+    );
+    assert_eq!(
+        value(&parsed, 0, "Device").unwrap(),
+        raw(&[1, 2], RegType::Other(0xFFFF_0012))
+    );
+}
+
+#[test]
+#[cfg(windows)]
+fn test_export_reg_key() {
+    with_key!(key, "ExportRegFile" => {
+        key.set_value("Dword", &42u32).unwrap();
+        key.create_subkey("Sub").unwrap().0.set_value("Sz", &"x").unwrap();
+        let mut out = Vec::new();
+        key.export_reg("HKEY_CURRENT_USER\\Test", &mut out).unwrap();
+        let parsed = RegFile::parse(&out).unwrap();
+        assert_eq!(parsed.keys.len(), 2);
+        assert_eq!(parsed.keys[1].path, "HKEY_CURRENT_USER\\Test\\Sub");
+        assert_eq!(value(&parsed, 0, "Dword").unwrap(), raw(&[42, 0, 0, 0], REG_DWORD));
+    });
+}
@@ -0,0 +1,229 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use windows_sys::Win32::Foundation;
+use winreg::enums::*;
+use winreg::resource::*;
+use winreg::types::{FromRegValue, ToRegValue};
+use winreg::RegValue;
+
+/// `CM_RESOURCE_LIST` with one PCI device: a port range, an interrupt and 2 bytes
+/// of device specific data, laid out by hand
+fn resource_list_bytes(layout: Layout) -> Vec<u8> {
+    // the union of a partial descriptor is 16 bytes long on 64-bit Windows and 12 on 32-bit
+    let pad = match layout {
+        Layout::Win32 => 0,
+        Layout::Win64 => 4,
+    };
+    let mut b = Vec::new();
+    b.extend_from_slice(&1u32.to_le_bytes()); // Count
+    b.extend_from_slice(&5u32.to_le_bytes()); // InterfaceType = PCIBus
+    b.extend_from_slice(&2u32.to_le_bytes()); // BusNumber
+    b.extend_from_slice(&1u16.to_le_bytes()); // Version
+    b.extend_from_slice(&1u16.to_le_bytes()); // Revision
+    b.extend_from_slice(&3u32.to_le_bytes()); // Count
+                                              // port
+    b.extend_from_slice(&[1, 1, 5, 0]);
+    b.extend_from_slice(&0x3F8u64.to_le_bytes());
+    b.extend_from_slice(&8u32.to_le_bytes());
+    b.extend_from_slice(&vec![0; pad]);
+    // interrupt
+    b.extend_from_slice(&[2, 3, 1, 0]);
+    b.extend_from_slice(&4u16.to_le_bytes());
+    b.extend_from_slice(&0u16.to_le_bytes());
+    b.extend_from_slice(&0x34u32.to_le_bytes());
+    match layout {
+        Layout::Win32 => b.extend_from_slice(&0xFFu32.to_le_bytes()),
+        Layout::Win64 => b.extend_from_slice(&0xFFu64.to_le_bytes()),
+    }
+    // device specific
+    b.extend_from_slice(&[5, 0, 0, 0]);
+    b.extend_from_slice(&2u32.to_le_bytes());
+    b.extend_from_slice(&vec![0; 8 + pad]);
+    b.extend_from_slice(&[0xAB, 0xCD]);
+    b
+}
+
+fn resource_list() -> ResourceList {
+    ResourceList {
+        lists: vec![FullResourceDescriptor {
+            interface_type: 5,
+            bus_number: 2,
+            version: 1,
+            revision: 1,
+            descriptors: vec![
+                PartialResourceDescriptor {
+                    share_disposition: 1,
+                    flags: 5,
+                    resource: Resource::Port {
+                        start: 0x3F8,
+                        length: 8,
+                    },
+                },
+                PartialResourceDescriptor {
+                    share_disposition: 3,
+                    flags: 1,
+                    resource: Resource::Interrupt {
+                        level: 4,
+                        group: 0,
+                        vector: 0x34,
+                        affinity: 0xFF,
+                    },
+                },
+                PartialResourceDescriptor {
+                    share_disposition: 0,
+                    flags: 0,
+                    resource: Resource::DeviceSpecific(vec![0xAB, 0xCD]),
+                },
+            ],
+        }],
+    }
+}
+
+#[test]
+fn test_resource_list() {
+    let val = RegValue {
+        bytes: resource_list_bytes(Layout::NATIVE).into(),
+        vtype: REG_RESOURCE_LIST,
+    };
+    let list = ResourceList::from_reg_value(&val).unwrap();
+    assert_eq!(list, resource_list());
+    assert_eq!(list.to_reg_value(), val);
+
+    let full = &list.lists[0];
+    let val = full.to_reg_value();
+    assert_eq!(val.vtype, REG_FULL_RESOURCE_DESCRIPTOR);
+    assert_eq!(&val.bytes[..], &resource_list_bytes(Layout::NATIVE)[4..]);
+    assert_eq!(&FullResourceDescriptor::from_reg_value(&val).unwrap(), full);
+    assert!(val.to_string().starts_with("FullResourceDescriptor {"));
+}
+
+#[test]
+fn test_resource_list_layouts() {
+    let list = resource_list();
+    for &layout in [Layout::Win32, Layout::Win64].iter() {
+        let bytes = resource_list_bytes(layout);
+        assert_eq!(
+            ResourceList::from_bytes_with_layout(&bytes, layout).unwrap(),
+            list
+        );
+        assert_eq!(list.to_bytes_with_layout(layout), bytes);
+    }
+    assert_eq!(
+        resource_list_bytes(Layout::Win32).len(),
+        4 + 16 + 3 * 16 + 2
+    );
+    assert_eq!(
+        resource_list_bytes(Layout::Win64).len(),
+        4 + 16 + 3 * 20 + 2
+    );
+    assert!(ResourceList::from_bytes_with_layout(
+        &resource_list_bytes(Layout::Win64),
+        Layout::Win32
+    )
+    .is_err());
+
+    // the upper half of an affinity doesn't fit into the 32-bit layout
+    let mut wide = list.clone();
+    if let Resource::Interrupt { affinity, .. } = &mut wide.lists[0].descriptors[1].resource {
+        *affinity = u64::MAX;
+    }
+    let mut expected = resource_list_bytes(Layout::Win32);
+    expected[48..52].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(wide.to_bytes_with_layout(Layout::Win32), expected);
+}
+
+#[test]
+fn test_resource_requirements_list() {
+    let list = ResourceRequirementsList {
+        interface_type: 1,
+        bus_number: 0,
+        slot_number: 7,
+        alternatives: vec![
+            IoResourceList {
+                version: 1,
+                revision: 1,
+                descriptors: vec![
+                    IoResourceDescriptor {
+                        option: 0,
+                        share_disposition: 1,
+                        flags: 0,
+                        requirement: Requirement::Memory {
+                            length: 0x1000,
+                            alignment: 0x1000,
+                            minimum: 0xF000_0000,
+                            maximum: 0xFFFF_FFFF,
+                        },
+                    },
+                    IoResourceDescriptor {
+                        option: 8,
+                        share_disposition: 3,
+                        flags: 1,
+                        requirement: Requirement::Interrupt {
+                            minimum_vector: 3,
+                            maximum_vector: 4,
+                            affinity_policy: 2,
+                            group: 1,
+                            priority_policy: 3,
+                            targeted_processors: 1,
+                        },
+                    },
+                ],
+            },
+            IoResourceList {
+                version: 1,
+                revision: 1,
+                descriptors: vec![IoResourceDescriptor {
+                    option: 0,
+                    share_disposition: 1,
+                    flags: 0,
+                    requirement: Requirement::Dma {
+                        minimum_channel: 1,
+                        maximum_channel: 3,
+                    },
+                }],
+            },
+        ],
+    };
+    let bytes = list.to_bytes();
+    assert_eq!(bytes.len(), 32 + 8 + 2 * 32 + 8 + 32);
+    assert_eq!(&bytes[..4], &(bytes.len() as u32).to_le_bytes());
+    assert_eq!(&bytes[40..48], &[0, 3, 1, 0, 0, 0, 0, 0]);
+    // affinity policy, group and priority policy of the interrupt
+    assert_eq!(&bytes[88..96], &[2, 0, 1, 0, 3, 0, 0, 0]);
+    assert_eq!(ResourceRequirementsList::from_bytes(&bytes).unwrap(), list);
+    let bytes = list.to_bytes_with_layout(Layout::Win32);
+    assert_eq!(bytes.len(), 32 + 8 + 2 * 32 + 8 + 32);
+    assert_eq!(
+        ResourceRequirementsList::from_bytes_with_layout(&bytes, Layout::Win32).unwrap(),
+        list
+    );
+}
+
+#[test]
+fn test_resource_errors() {
+    let mut bytes = resource_list_bytes(Layout::NATIVE);
+    bytes.pop();
+    let err = ResourceList::from_bytes(&bytes).unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_INVALID_DATA as i32)
+    );
+    let err = ResourceList::from_bytes(&[0xFF, 0xFF, 0xFF, 0xFF]).unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_INVALID_DATA as i32)
+    );
+    let err = ResourceList::from_reg_value(&1u32.to_reg_value()).unwrap_err();
+    assert_eq!(
+        err.raw_os_error(),
+        Some(Foundation::ERROR_BAD_FILE_TYPE as i32)
+    );
+    let val = RegValue {
+        bytes: vec![1, 2, 3].into(),
+        vtype: REG_RESOURCE_LIST,
+    };
+    assert_eq!(val.to_string(), "[1, 2, 3]");
+}
@@ -146,6 +146,7 @@ fn test_serialization_all() {
     });
 }
 
+#[cfg(windows)]
 #[test]
 fn test_serialization_some_transacted() {
     let v1 = AllFields::test_val();
@@ -159,6 +160,7 @@ fn test_serialization_some_transacted() {
     });
 }
 
+#[cfg(windows)]
 #[test]
 fn test_serialization_all_transacted() {
     let v1 = AllFields::test_val();
@@ -192,6 +194,7 @@ fn test_serialization_destructive() {
     });
 }
 
+#[cfg(windows)]
 #[test]
 fn test_serialization_destructive_transacted() {
     with_key!(key, "SerializationDestructiveTransacted" => {
@@ -214,3 +217,515 @@ fn test_serialization_destructive_transacted() {
         assert_eq!(v3, v2);
     });
 }
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Sequences {
+    t_strings: Vec<String>,
+    t_empty: Vec<String>,
+    t_with_empty: Vec<String>,
+    t_numbers: Vec<u32>,
+    t_optional: Vec<Option<u64>>,
+    t_structs: Vec<Coords>,
+    t_nested: Vec<Vec<String>>,
+    t_tuple: (u32, String, Size),
+}
+
+#[test]
+fn test_serialization_sequences() {
+    let mut v1 = Sequences {
+        t_strings: vec!["one".to_owned(), "two".to_owned()],
+        t_empty: vec![],
+        t_with_empty: vec!["".to_owned(), "x".to_owned()],
+        t_numbers: vec![1, 2, 3],
+        t_optional: vec![Some(1), None, Some(3)],
+        t_structs: vec![Coords { x: 1, y: 2 }, Coords { x: 3, y: 4 }],
+        t_nested: vec![vec!["a".to_owned()], vec![]],
+        t_tuple: (7, "seven".to_owned(), Size { w: 8, h: 9 }),
+    };
+
+    with_key!(key, "SerializationSequences" => {
+        key.encode(&v1).unwrap();
+        let v2: Sequences = key.decode().unwrap();
+        assert_eq!(v2, v1);
+
+        use winreg::enums::RegType::*;
+        let strings = key.get_raw_value("t_strings").unwrap();
+        assert_eq!(strings.vtype, REG_MULTI_SZ);
+        let numbers = key.open_subkey("t_numbers").unwrap();
+        assert_eq!(numbers.get_raw_value("2").unwrap().vtype, REG_DWORD);
+        let with_empty = key.open_subkey("t_with_empty").unwrap();
+        let empty: String = with_empty.get_value("0").unwrap();
+        assert_eq!(empty, "");
+        let optional = key.open_subkey("t_optional").unwrap();
+        assert!(optional.get_raw_value("1").is_err());
+        let tuple = key.open_subkey("t_tuple").unwrap();
+        assert!(tuple.open_subkey("2").is_ok());
+
+        // shorter sequences and switched layouts leave nothing behind
+        v1.t_strings = vec!["".to_owned()];
+        v1.t_with_empty = vec!["y".to_owned()];
+        v1.t_numbers = vec![9];
+        v1.t_optional = vec![None, Some(2)];
+        v1.t_structs = vec![Coords { x: 5, y: 6 }];
+        key.encode(&v1).unwrap();
+        let v3: Sequences = key.decode().unwrap();
+        assert_eq!(v3, v1);
+        assert!(key.get_raw_value("t_strings").is_err());
+        assert!(key.open_subkey("t_with_empty").is_err());
+        let numbers = key.open_subkey("t_numbers").unwrap();
+        assert!(numbers.get_raw_value("1").is_err());
+        let structs = key.open_subkey("t_structs").unwrap();
+        assert!(structs.open_subkey("1").is_err());
+    });
+}
+
+#[test]
+fn test_serialization_root_sequence() {
+    let v1 = vec![Coords { x: 1, y: 2 }, Coords { x: 3, y: 4 }];
+
+    with_key!(key, "SerializationRootSequence" => {
+        key.encode(&v1).unwrap();
+        let v2: Vec<Coords> = key.decode().unwrap();
+        assert_eq!(v2, v1);
+        assert!(key.decode::<Vec<u32>>().is_err());
+    });
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum LogLevel {
+    Debug,
+    Info,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Point,
+    Circle(u32),
+    Rect(Size),
+    Line(Coords, Coords),
+    Polygon { name: String, points: Vec<Coords> },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum Internal {
+    Empty,
+    Sized { w: u32, h: u32 },
+    Wrapped(Coords),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "t", content = "c")]
+enum Adjacent {
+    Empty,
+    Number(u32),
+    Named { name: String },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Enums {
+    t_level: LogLevel,
+    t_levels: Vec<LogLevel>,
+    t_optional: Option<LogLevel>,
+    t_point: Shape,
+    t_circle: Shape,
+    t_rect: Shape,
+    t_line: Shape,
+    t_polygon: Shape,
+    t_shapes: Vec<Shape>,
+    t_internal_empty: Internal,
+    t_internal_sized: Internal,
+    t_internal_wrapped: Internal,
+    t_adjacent_empty: Adjacent,
+    t_adjacent_number: Adjacent,
+    t_adjacent_named: Adjacent,
+}
+
+#[test]
+fn test_serialization_enums() {
+    let mut v1 = Enums {
+        t_level: LogLevel::Info,
+        t_levels: vec![LogLevel::Debug, LogLevel::Info],
+        t_optional: Some(LogLevel::Debug),
+        t_point: Shape::Point,
+        t_circle: Shape::Circle(5),
+        t_rect: Shape::Rect(Size { w: 1, h: 2 }),
+        t_line: Shape::Line(Coords { x: 1, y: 2 }, Coords { x: 3, y: 4 }),
+        t_polygon: Shape::Polygon {
+            name: "triangle".to_owned(),
+            points: vec![Coords { x: 0, y: 0 }, Coords { x: 1, y: 1 }],
+        },
+        t_shapes: vec![Shape::Point, Shape::Circle(1)],
+        t_internal_empty: Internal::Empty,
+        t_internal_sized: Internal::Sized { w: 3, h: 4 },
+        t_internal_wrapped: Internal::Wrapped(Coords { x: 5, y: 6 }),
+        t_adjacent_empty: Adjacent::Empty,
+        t_adjacent_number: Adjacent::Number(7),
+        t_adjacent_named: Adjacent::Named {
+            name: "eight".to_owned(),
+        },
+    };
+
+    with_key!(key, "SerializationEnums" => {
+        key.encode(&v1).unwrap();
+        let v2: Enums = key.decode().unwrap();
+        assert_eq!(v2, v1);
+
+        let level: String = key.get_value("t_level").unwrap();
+        assert_eq!(level, "Info");
+        let circle = key.open_subkey("t_circle").unwrap();
+        let radius: u32 = circle.get_value("Circle").unwrap();
+        assert_eq!(radius, 5);
+        let internal = key.open_subkey("t_internal_sized").unwrap();
+        let tag: String = internal.get_value("type").unwrap();
+        assert_eq!(tag, "Sized");
+
+        // the subkey only keeps the current variant
+        v1.t_rect = Shape::Circle(2);
+        key.encode(&v1).unwrap();
+        let v3: Enums = key.decode().unwrap();
+        assert_eq!(v3, v1);
+        assert!(key.open_subkey("t_rect\\Rect").is_err());
+
+        // unit variants are values, the others subkeys
+        v1.t_point = Shape::Circle(3);
+        v1.t_circle = Shape::Point;
+        v1.t_polygon = Shape::Point;
+        key.encode(&v1).unwrap();
+        let v4: Enums = key.decode().unwrap();
+        assert_eq!(v4, v1);
+        assert!(key.open_subkey("t_circle").is_err());
+        assert!(key.open_subkey("t_polygon").is_err());
+
+        v1.t_point = Shape::Point;
+        v1.t_circle = Shape::Line(Coords { x: 1, y: 1 }, Coords { x: 2, y: 2 });
+        key.encode(&v1).unwrap();
+        let v5: Enums = key.decode().unwrap();
+        assert_eq!(v5, v1);
+        assert!(key.open_subkey("t_point").is_err());
+        assert!(key.get_raw_value("t_circle").is_err());
+    });
+}
+
+#[test]
+fn test_serialization_root_enum() {
+    let v1 = Shape::Polygon {
+        name: "square".to_owned(),
+        points: vec![Coords { x: 0, y: 0 }],
+    };
+
+    with_key!(key, "SerializationRootEnum" => {
+        key.encode(&v1).unwrap();
+        let v2: Shape = key.decode().unwrap();
+        assert_eq!(v2, v1);
+    });
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Numbers {
+    t_i8: i8,
+    t_i16: i16,
+    t_i32: i32,
+    t_i64: i64,
+    t_f32: f32,
+    t_f64: f64,
+}
+
+#[test]
+fn test_serialization_number_formats() {
+    use winreg::decoder::DecoderOptions;
+    use winreg::encoder::{EncoderOptions, FloatFormat, SignedFormat};
+    use winreg::enums::RegType::*;
+
+    let v1 = Numbers {
+        t_i8: -1,
+        t_i16: -2049,
+        t_i32: i32::MIN,
+        t_i64: -12_345_678_910,
+        t_f32: 3.15,
+        t_f64: -0.01,
+    };
+    let encoder_options = EncoderOptions::new()
+        .signed(SignedFormat::TwosComplement)
+        .floats(FloatFormat::Bits);
+    let decoder_options = DecoderOptions::new()
+        .signed(SignedFormat::TwosComplement)
+        .floats(FloatFormat::Bits);
+
+    with_key!(key, "SerializationNumberFormats" => {
+        key.encode_with_options(&v1, encoder_options).unwrap();
+        assert_eq!(key.get_raw_value("t_i8").unwrap().vtype, REG_DWORD);
+        assert_eq!(key.get_value::<u32, _>("t_i32").unwrap(), 0x8000_0000);
+        assert_eq!(key.get_raw_value("t_i64").unwrap().vtype, REG_QWORD);
+        assert_eq!(key.get_value::<u32, _>("t_f32").unwrap(), 3.15f32.to_bits());
+        assert_eq!(key.get_value::<u64, _>("t_f64").unwrap(), (-0.01f64).to_bits());
+
+        let v2: Numbers = key.decode_with_options(decoder_options).unwrap();
+        assert_eq!(v2, v1);
+        // unsigned by default, -1 doesn't fit
+        assert!(key.decode::<Numbers>().is_err());
+
+        // strings are accepted with any options
+        key.encode(&v1).unwrap();
+        assert_eq!(key.get_raw_value("t_i8").unwrap().vtype, REG_SZ);
+        let v3: Numbers = key.decode_with_options(decoder_options).unwrap();
+        assert_eq!(v3, v1);
+
+        // numbers written by other tools
+        key.set_value("t_i8", &100u32).unwrap();
+        key.set_value("t_i64", &7u64).unwrap();
+        key.set_value("t_f64", &5u32).unwrap();
+        let v4: Numbers = key.decode().unwrap();
+        assert_eq!((v4.t_i8, v4.t_i64, v4.t_f64), (100, 7, 5.0));
+    });
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ExactTypes {
+    #[serde(with = "winreg::serde_with::expand_sz")]
+    t_expand: String,
+    #[serde(with = "winreg::serde_with::big_endian")]
+    t_big_endian: u32,
+    #[serde(with = "winreg::serde_with::binary")]
+    t_binary: Vec<u8>,
+    #[serde(with = "winreg::serde_with::none")]
+    t_none: Vec<u8>,
+    #[serde(with = "winreg::serde_with::link")]
+    t_link: String,
+    #[serde(with = "winreg::serde_with::raw")]
+    t_raw: winreg::RegValue<'static>,
+}
+
+#[test]
+fn test_serialization_serde_with() {
+    use winreg::enums::RegType::*;
+
+    let v1 = ExactTypes {
+        t_expand: "%SystemRoot%\\System32".to_owned(),
+        t_big_endian: 0x0102_0304,
+        t_binary: vec![1, 2, 3],
+        t_none: vec![4, 5],
+        t_link: "\\Registry\\Machine\\Software".to_owned(),
+        t_raw: winreg::RegValue {
+            bytes: vec![0xFF, 0x00].into(),
+            vtype: Other(0xFFFF_0012),
+        },
+    };
+
+    with_key!(key, "SerializationSerdeWith" => {
+        key.encode(&v1).unwrap();
+        let types = [
+            ("t_expand", REG_EXPAND_SZ),
+            ("t_big_endian", REG_DWORD_BIG_ENDIAN),
+            ("t_binary", REG_BINARY),
+            ("t_none", REG_NONE),
+            ("t_link", REG_LINK),
+            ("t_raw", Other(0xFFFF_0012)),
+        ];
+        for (name, vtype) in types.iter() {
+            assert_eq!(&key.get_raw_value(name).unwrap().vtype, vtype);
+        }
+        assert_eq!(key.get_raw_value("t_big_endian").unwrap().bytes, vec![1, 2, 3, 4]);
+
+        let v2: ExactTypes = key.decode().unwrap();
+        assert_eq!(v2, v1);
+
+        // wrong type
+        key.set_value("t_expand", &"plain").unwrap();
+        assert!(key.decode::<ExactTypes>().is_err());
+    });
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+enum TaggedExact {
+    Paths {
+        #[serde(with = "winreg::serde_with::expand_sz")]
+        expand: String,
+        #[serde(with = "winreg::serde_with::link")]
+        link: String,
+        #[serde(with = "winreg::serde_with::big_endian")]
+        big_endian: u32,
+        #[serde(with = "winreg::serde_with::binary")]
+        binary: Vec<u8>,
+        #[serde(with = "winreg::serde_with::raw")]
+        raw: winreg::RegValue<'static>,
+        #[serde(with = "winreg::serde_with::raw")]
+        raw_sz: winreg::RegValue<'static>,
+        plain: String,
+    },
+}
+
+#[test]
+fn test_serialization_serde_with_tagged() {
+    use winreg::enums::RegType::*;
+    use winreg::types::ToRegValue;
+
+    let v1 = TaggedExact::Paths {
+        expand: "%TEMP%".to_owned(),
+        link: "\\Registry\\Machine".to_owned(),
+        big_endian: 7,
+        binary: vec![1, 2],
+        raw: winreg::RegValue {
+            bytes: vec![0xFF].into(),
+            vtype: Other(0xFFFF_0012),
+        },
+        raw_sz: "sz".to_reg_value(),
+        plain: "plain".to_owned(),
+    };
+
+    with_key!(key, "SerializationSerdeWithTagged" => {
+        key.encode(&v1).unwrap();
+        assert_eq!(key.get_raw_value("expand").unwrap().vtype, REG_EXPAND_SZ);
+        let v2: TaggedExact = key.decode().unwrap();
+        assert_eq!(v2, v1);
+    });
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct App {
+    name: String,
+    version: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct Unsupported {
+    unit: (),
+}
+
+#[derive(Debug, Serialize)]
+struct Outer {
+    inner: Unsupported,
+}
+
+#[test]
+fn test_serialization_error_paths() {
+    with_key!(key, "SerializationErrorPaths" => {
+        let v1 = Rectangle {
+            coords: Some(Coords { x: 55, y: 77 }),
+            size: Size { w: 500, h: 300 },
+        };
+        key.encode(&v1).unwrap();
+        let (coords, _) = key.create_subkey("coords").unwrap();
+        coords.set_value("x", &"55").unwrap();
+
+        let err = key.decode::<Rectangle>().unwrap_err();
+        assert_eq!(err.path(), Some("coords"));
+        assert_eq!(err.value_name(), Some("x"));
+        match err.inner() {
+            winreg::decoder::DecoderError::IoError(e) => assert_eq!(e.raw_os_error(), Some(222)),
+            e => panic!("unexpected error {:?}", e),
+        }
+        assert!(err.to_string().starts_with("key \"coords\", value \"x\": "));
+
+        let err = key.encode(&Outer { inner: Unsupported { unit: () } }).unwrap_err();
+        assert_eq!(err.path(), Some("inner"));
+        assert_eq!(err.value_name(), Some("unit"));
+        assert_eq!(
+            err.to_string(),
+            "key \"inner\", value \"unit\": not implemented: serialize_unit"
+        );
+    });
+}
+
+#[test]
+fn test_serialization_lenient() {
+    let mut apps = HashMap::new();
+    apps.insert(
+        "a".to_owned(),
+        App {
+            name: "A".to_owned(),
+            version: Some(1),
+        },
+    );
+    apps.insert(
+        "b".to_owned(),
+        App {
+            name: "B".to_owned(),
+            version: Some(2),
+        },
+    );
+    apps.insert(
+        "c".to_owned(),
+        App {
+            name: "C".to_owned(),
+            version: None,
+        },
+    );
+
+    with_key!(key, "SerializationLenient" => {
+        key.encode(&apps).unwrap();
+        key.create_subkey("b").unwrap().0.set_value("version", &"two").unwrap();
+        key.create_subkey("c").unwrap().0.delete_value("name").unwrap();
+        assert!(key.decode::<HashMap<String, App>>().is_err());
+
+        let (decoded, errors): (HashMap<String, App>, _) = key.decode_lenient().unwrap();
+        apps.get_mut("b").unwrap().version = None;
+        apps.remove("c");
+        assert_eq!(decoded, apps);
+        let locations: Vec<_> = errors.iter().map(|e| (e.path(), e.value_name())).collect();
+        assert_eq!(locations, vec![(Some("b"), Some("version")), (Some("c"), None)]);
+        assert_eq!(errors[1].to_string(), "key \"c\": missing field `name`");
+
+        // nothing to skip at the root
+        let c = key.open_subkey("c").unwrap();
+        assert!(c.decode_lenient::<App>().is_err());
+    });
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Required {
+    a: u32,
+    c: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Nested {
+    n: Option<Required>,
+    v: Vec<Option<u32>>,
+    m: HashMap<String, Required>,
+}
+
+#[test]
+fn test_serialization_lenient_errors() {
+    with_key!(key, "SerializationLenientErrors" => {
+        // a required value keeps its location when it can't be skipped
+        key.set_value("a", &"notnum").unwrap();
+        key.set_value("c", &"c").unwrap();
+        let err = key.decode_lenient::<Required>().unwrap_err();
+        assert_eq!((err.path(), err.value_name()), (Some(""), Some("a")));
+
+        let (n, _) = key.create_subkey("n").unwrap();
+        n.set_value("a", &1u32).unwrap();
+        n.set_value("c", &"c").unwrap();
+        let (v, _) = key.create_subkey("v").unwrap();
+        v.set_value("0", &1u32).unwrap();
+        v.set_value("1", &"two").unwrap();
+        let (m, _) = key.create_subkey("m").unwrap();
+        for name in ["x", "y", "z"].iter() {
+            let (entry, _) = m.create_subkey(name).unwrap();
+            entry.set_value("a", &1u32).unwrap();
+            entry.set_value("c", &"c").unwrap();
+        }
+        n.set_value("a", &"notnum").unwrap();
+        m.create_subkey("x").unwrap().0.set_value("a", &"notnum").unwrap();
+        m.create_subkey("z").unwrap().0.delete_value("c").unwrap();
+
+        // one error for each skipped entry
+        let (decoded, errors): (Nested, _) = key.decode_lenient().unwrap();
+        let mut expected = HashMap::new();
+        expected.insert("y".to_owned(), Required { a: 1, c: "c".to_owned() });
+        assert_eq!(decoded, Nested { n: None, v: vec![Some(1), None], m: expected });
+        let locations: Vec<_> = errors.iter().map(|e| (e.path(), e.value_name())).collect();
+        assert_eq!(
+            locations,
+            vec![
+                (Some("m\\x"), Some("a")),
+                (Some("m\\z"), None),
+                (Some("n"), Some("a")),
+                (Some("v"), Some("1")),
+            ]
+        );
+        assert_eq!(errors[1].to_string(), "key \"m\\z\": missing field `c`");
+    });
+}
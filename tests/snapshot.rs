@@ -0,0 +1,135 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+#![cfg(feature = "serialization-serde")]
+use winreg::enums::*;
+use winreg::mem::{MemKey, MemRegistry};
+use winreg::snapshot::KeySnapshot;
+use winreg::RegistryBackend;
+
+mod common;
+
+use common::raw;
+
+fn sample() -> MemKey {
+    let (key, _) = MemRegistry::new()
+        .predef(HKEY_CURRENT_USER)
+        .create_subkey("Software\\MyProduct")
+        .unwrap();
+    key.set_value("", &"default").unwrap();
+    key.set_raw_value("Path", &raw(b"%\0A\0%\0\0\0", REG_EXPAND_SZ))
+        .unwrap();
+    key.set_value("Port", &8080u32).unwrap();
+    key.set_value("Big", &0x1234_5678_9abc_def0u64).unwrap();
+    key.set_raw_value("BigEndian", &raw(&[0, 0, 1, 0], REG_DWORD_BIG_ENDIAN))
+        .unwrap();
+    key.set_value("Servers", &vec!["a", "b"]).unwrap();
+    key.set_raw_value("Empty", &raw(&[0, 0], REG_MULTI_SZ))
+        .unwrap();
+    key.set_raw_value("Link", &raw(b"\\\0R\0", REG_LINK))
+        .unwrap();
+    key.set_raw_value("Unterminated", &raw(b"a\0", REG_SZ))
+        .unwrap();
+    key.set_raw_value("Short", &raw(&[1, 2], REG_DWORD))
+        .unwrap();
+    key.set_raw_value("Resources", &raw(&[1, 0, 0, 0], REG_RESOURCE_LIST))
+        .unwrap();
+    key.set_raw_value("None", &raw(&[], REG_NONE)).unwrap();
+    key.set_raw_value("Device", &raw(&[1, 2], RegType::Other(0xFFFF_0012)))
+        .unwrap();
+    let (sub, _) = key.create_subkey("Plugins\\First").unwrap();
+    sub.set_value("Enabled", &1u32).unwrap();
+    key.create_subkey("Empty").unwrap();
+    key
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let key = sample();
+    let snapshot = KeySnapshot::from_key(&key, "MyProduct").unwrap();
+    let json = serde_json::to_string_pretty(&snapshot).unwrap();
+    let parsed: KeySnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, snapshot);
+
+    let (copy, _) = MemRegistry::new()
+        .predef(HKEY_LOCAL_MACHINE)
+        .create_subkey("Copy")
+        .unwrap();
+    copy.set_value("Stale", &1u32).unwrap();
+    copy.create_subkey("Stale").unwrap();
+    parsed.restore(&copy).unwrap();
+    let restored = KeySnapshot::from_key(&copy, "MyProduct").unwrap();
+    assert_eq!(restored, snapshot);
+    assert_eq!(serde_json::to_string_pretty(&restored).unwrap(), json);
+}
+
+#[test]
+fn test_snapshot_json_schema() {
+    let snapshot = KeySnapshot::from_key(&sample(), "MyProduct").unwrap();
+    let json = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "name": "MyProduct",
+            "values": [
+                { "name": "", "type": "REG_SZ", "data": "default" },
+                { "name": "Big", "type": "REG_QWORD", "data": 0x1234_5678_9abc_def0u64 },
+                { "name": "BigEndian", "type": "REG_DWORD_BIG_ENDIAN", "data": 256 },
+                { "name": "Device", "type": "0xffff0012", "hex": "0102" },
+                { "name": "Empty", "type": "REG_MULTI_SZ", "data": [] },
+                { "name": "Link", "type": "REG_LINK", "data": "\\R" },
+                { "name": "None", "type": "REG_NONE", "hex": "" },
+                { "name": "Path", "type": "REG_EXPAND_SZ", "data": "%A%" },
+                { "name": "Port", "type": "REG_DWORD", "data": 8080 },
+                { "name": "Resources", "type": "REG_RESOURCE_LIST", "hex": "01000000" },
+                { "name": "Servers", "type": "REG_MULTI_SZ", "data": ["a", "b"] },
+                { "name": "Short", "type": "REG_DWORD", "hex": "0102" },
+                { "name": "Unterminated", "type": "REG_SZ", "hex": "6100" },
+            ],
+            "keys": [
+                { "name": "Empty" },
+                {
+                    "name": "Plugins",
+                    "keys": [{
+                        "name": "First",
+                        "values": [{ "name": "Enabled", "type": "REG_DWORD", "data": 1 }],
+                    }],
+                },
+            ],
+        })
+    );
+    assert_eq!(
+        snapshot
+            .subkey("plugins\\FIRST")
+            .unwrap()
+            .get_raw_value("enabled"),
+        Some(&raw(&[1, 0, 0, 0], REG_DWORD))
+    );
+}
+
+#[test]
+fn test_snapshot_errors() {
+    let parse = |json: &str| serde_json::from_str::<KeySnapshot>(json).unwrap_err();
+    let err = parse(r#"{"name": "", "values": [{"name": "a", "type": "REG_FOO", "hex": ""}]}"#);
+    assert!(err.to_string().contains("unknown value type"));
+    let err = parse(r#"{"name": "", "values": [{"name": "a", "type": "REG_SZ", "data": 1}]}"#);
+    assert!(err.to_string().contains("doesn't match type REG_SZ"));
+    let err = parse(
+        r#"{"name": "", "values": [{"name": "a", "type": "REG_DWORD", "data": 4294967296}]}"#,
+    );
+    assert!(err.to_string().contains("doesn't match"));
+    let err = parse(r#"{"name": "", "values": [{"name": "a", "type": "REG_BINARY", "hex": "0"}]}"#);
+    assert!(err.to_string().contains("invalid hex"));
+    let err = parse(r#"{"name": "", "values": [{"name": "a", "type": "REG_BINARY"}]}"#);
+    assert!(err.to_string().contains("either"));
+    let err = parse(r#"{"values": []}"#);
+    assert!(err.to_string().contains("missing field `name`"));
+    let err = parse(r#"{"name": "", "values": [{"name": "a", "hex": ""}]}"#);
+    assert!(err.to_string().contains("missing field `type`"));
+    let err = parse(r#"{"name": "", "name": ""}"#);
+    assert!(err.to_string().contains("duplicate field `name`"));
+    let err = parse(r#"{"name": "", "values": [{"name": "a", "type": "REG_DWORD", "data": -1}]}"#);
+    assert!(err.to_string().contains("invalid value"));
+}
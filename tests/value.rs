@@ -0,0 +1,90 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use std::convert::TryFrom;
+use windows_sys::Win32::Foundation;
+use winreg::enums::*;
+use winreg::mem::MemRegistry;
+use winreg::{RegValue, RegistryBackend, Value};
+
+mod common;
+
+use common::raw;
+
+#[test]
+fn test_value_round_trip() {
+    let values = vec![
+        raw(b"a\0b\0\0\0", REG_SZ),
+        raw(b"%\0A\0%\0\0\0", REG_EXPAND_SZ),
+        raw(b"a\0\0\0b\0\0\0\0\0", REG_MULTI_SZ),
+        raw(&[0, 0], REG_MULTI_SZ),
+        raw(&[1, 2, 3, 4], REG_DWORD),
+        raw(&[1, 2, 3, 4], REG_DWORD_BIG_ENDIAN),
+        raw(&[1, 2, 3, 4, 5, 6, 7, 8], REG_QWORD),
+        raw(&[1, 2, 3], REG_BINARY),
+        raw(b"\\\0R\0", REG_LINK),
+        raw(&[1, 0, 0, 0], REG_RESOURCE_LIST),
+        raw(&[1], REG_FULL_RESOURCE_DESCRIPTOR),
+        raw(&[2], REG_RESOURCE_REQUIREMENTS_LIST),
+        raw(&[], REG_NONE),
+        raw(&[1, 2], RegType::Other(0xFFFF_0012)),
+    ];
+    for val in values {
+        let typed = Value::try_from(&val).unwrap();
+        assert_eq!(RegValue::from(&typed), val);
+    }
+    assert_eq!(
+        Value::try_from(raw(&[0, 0], REG_MULTI_SZ)).unwrap(),
+        Value::MultiSz(vec![])
+    );
+    assert_eq!(
+        Value::try_from(raw(&[1, 2, 3, 4], REG_DWORD_BIG_ENDIAN)).unwrap(),
+        Value::DwordBigEndian(0x0102_0304)
+    );
+    assert_eq!(
+        Value::try_from(raw(&[1, 2], RegType::Other(0xFFFF_0012))).unwrap(),
+        Value::Unknown(0xFFFF_0012, vec![1, 2])
+    );
+    assert_eq!(
+        Value::try_from(raw(b"\\\0R\0", REG_LINK)).unwrap(),
+        Value::Link("\\R".to_owned())
+    );
+}
+
+#[test]
+fn test_value_not_canonical() {
+    let values = vec![
+        raw(b"a\0", REG_SZ),
+        raw(b"a\0\0", REG_EXPAND_SZ),
+        raw(&[0x00, 0xd8, 0, 0], REG_SZ),
+        raw(b"a\0\0\0", REG_MULTI_SZ),
+        raw(&[1, 2], REG_DWORD),
+        raw(&[1, 2, 3, 4], REG_QWORD),
+    ];
+    for val in values {
+        let err = Value::try_from(&val).unwrap_err();
+        assert_eq!(
+            err.raw_os_error(),
+            Some(Foundation::ERROR_INVALID_DATA as i32)
+        );
+    }
+}
+
+#[test]
+fn test_typed_value_on_backend() {
+    let (key, _) = MemRegistry::new()
+        .predef(HKEY_CURRENT_USER)
+        .create_subkey("Software\\Typed")
+        .unwrap();
+    let servers = Value::MultiSz(vec!["a".to_owned(), "b".to_owned()]);
+    key.set_typed_value("Servers", &servers).unwrap();
+    assert_eq!(key.get_typed_value("Servers").unwrap(), servers);
+    assert_eq!(
+        key.get_value::<Vec<String>, _>("Servers").unwrap(),
+        vec!["a", "b"]
+    );
+    key.set_value("Port", &8080u32).unwrap();
+    assert_eq!(key.get_typed_value("Port").unwrap(), Value::Dword(8080));
+}
@@ -0,0 +1,168 @@
+// Copyright 2026, Igor Shaula
+// Licensed under the MIT License <LICENSE or
+// http://opensource.org/licenses/MIT>. This file
+// may not be copied, modified, or distributed
+// except according to those terms.
+use std::io;
+use winreg::enums::*;
+use winreg::types::ToRegValue;
+use winreg::wine::WineRegistry;
+
+mod common;
+
+use common::{raw, utf16};
+
+/// Trimmed down `system.reg` of a fresh prefix
+const SAMPLE: &str = "WINE REGISTRY Version 2
+;; All keys relative to \\\\Machine
+
+#arch=win64
+
+[Software\\\\Classes\\\\.txt] 1700000000
+#time=1da1747c66d0000
+@=\"txtfile\"
+\"Content Type\"=\"text/plain\"
+
+[Software\\\\Wine\\\\Test] 1700000000
+#time=1da1747c66d0000
+#class=\"Test \\\"class\\\"\"
+\"Dword\"=dword:0000002a
+\"Expand\"=str(2):\"%SystemRoot%\\\\system32\"
+\"Multi\"=str(7):\"a\\0b\\0\"
+\"Escaped\"=\"tab\\there\\xe9\\n\"
+\"Binary\"=hex:00,01,02,03,04,05,06,07,08,09,0a,0b,0c,0d,0e,0f,10,11,12,13,14,15,\\
+  16,17,18,19
+\"Qword\"=hex(b):2a,00,00,00,00,00,00,00
+
+[System\\\\CurrentControlSet] 1700000000
+#time=1da1747c66d0000
+#link
+\"SymbolicLinkValue\"=hex(6):5c,00,52,00
+";
+
+#[test]
+fn test_parse_wine_registry() {
+    let reg = WineRegistry::parse_str(SAMPLE).unwrap();
+    assert_eq!(reg.relative_to, "\\Machine");
+    assert_eq!(reg.arch.as_deref(), Some("win64"));
+    assert_eq!(reg.keys.len(), 3);
+
+    let txt = reg.key("software\\classes\\.TXT").unwrap();
+    assert_eq!(txt.get_value::<String, _>("").unwrap(), "txtfile");
+    assert_eq!(
+        txt.last_write_time.as_ref().unwrap().as_u64(),
+        0x1da1747c66d0000
+    );
+
+    let test = reg.key("Software\\Wine\\Test").unwrap();
+    assert_eq!(test.class.as_deref(), Some("Test \"class\""));
+    assert_eq!(test.get_value::<u32, _>("Dword").unwrap(), 42);
+    assert_eq!(
+        test.get_raw_value("Expand").unwrap(),
+        &raw(&utf16("%SystemRoot%\\system32"), REG_EXPAND_SZ)
+    );
+    assert_eq!(
+        test.get_value::<Vec<String>, _>("Multi").unwrap(),
+        vec!["a".to_owned(), "b".to_owned()]
+    );
+    assert_eq!(
+        test.get_value::<String, _>("Escaped").unwrap(),
+        "tab\there\u{e9}\n"
+    );
+    assert_eq!(
+        test.get_raw_value("Binary").unwrap(),
+        &raw(&(0..26).collect::<Vec<u8>>(), REG_BINARY)
+    );
+    assert_eq!(test.get_value::<u64, _>("Qword").unwrap(), 42);
+    let err = test.get_value::<u32, _>("Missing").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    let link = reg.key("System\\CurrentControlSet").unwrap();
+    assert!(link.link);
+    assert_eq!(
+        link.get_raw_value("SymbolicLinkValue").unwrap().vtype,
+        REG_LINK
+    );
+}
+
+#[test]
+fn test_wine_registry_round_trip() {
+    let reg = WineRegistry::parse_str(SAMPLE).unwrap();
+    assert_eq!(reg.to_text(), SAMPLE);
+}
+
+#[test]
+fn test_write_wine_registry() {
+    let mut reg = WineRegistry::new("\\User\\S-1-5-21-0-0-0-1000");
+    let key = reg.add_key("Software\\Wine\\Direct3D");
+    key.set_value("renderer", &"vulkan");
+    key.set_value("Odd\"name", &"\u{1}7\u{2}x");
+    key.set_raw_value("Unterminated", &raw(&[0x61, 0], REG_SZ));
+    key.set_value("Gone", &1u32);
+    key.delete_value("GONE");
+    reg.add_key("Software\\Wine\\Direct3D")
+        .set_value("renderer", &"gl");
+    reg.add_key("Software\\Wine\\Direct3D\\Sub")
+        .set_value("", &1u64);
+    reg.add_key("Software\\Wine\\Other");
+    assert_eq!(
+        reg.to_text(),
+        "WINE REGISTRY Version 2
+;; All keys relative to \\\\User\\\\S-1-5-21-0-0-0-1000
+
+[Software\\\\Wine\\\\Direct3D]
+\"renderer\"=\"gl\"
+\"Odd\\\"name\"=\"\\0017\\2x\"
+\"Unterminated\"=hex(1):61,00
+
+[Software\\\\Wine\\\\Direct3D\\\\Sub]
+@=hex(b):01,00,00,00,00,00,00,00
+
+[Software\\\\Wine\\\\Other]
+"
+    );
+
+    let parsed = WineRegistry::parse(&reg.to_bytes()).unwrap();
+    let key = parsed.key("Software\\Wine\\Direct3D").unwrap();
+    assert_eq!(key.values[1].name, "Odd\"name");
+    assert_eq!(
+        key.get_value::<String, _>("Odd\"name").unwrap(),
+        "\u{1}7\u{2}x"
+    );
+
+    reg.delete_key("software\\wine\\direct3d");
+    assert_eq!(reg.keys.len(), 1);
+    assert_eq!(reg.keys[0].path, "Software\\Wine\\Other");
+}
+
+#[test]
+fn test_parse_wine_registry_errors() {
+    let err = WineRegistry::parse_str("REGEDIT4\n").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let err = WineRegistry::parse_str("WINE REGISTRY Version 2\n\"Name\"=\"x\"\n").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: value outside of a key section");
+
+    let err = WineRegistry::parse_str("WINE REGISTRY Version 2\n\n[Key]\n\"Name\"=dword:xyz\n")
+        .unwrap_err();
+    assert_eq!(err.to_string(), "line 4: invalid dword");
+
+    let err = WineRegistry::parse_str("WINE REGISTRY Version 2\n\n[Key\n").unwrap_err();
+    assert_eq!(err.to_string(), "line 3: unterminated string");
+
+    let text = format!("WINE REGISTRY Version 2\n\n[Key] {}\n", u64::MAX);
+    let err = WineRegistry::parse_str(&text).unwrap_err();
+    assert_eq!(err.to_string(), "line 3: invalid timestamp");
+}
+
+#[test]
+fn test_wine_registry_values_match_reg_values() {
+    let mut reg = WineRegistry::new("\\Machine");
+    let key = reg.add_key("Key");
+    key.set_value("String", &"text");
+    key.set_value("Dword", &7u32);
+    let parsed = WineRegistry::parse(&reg.to_bytes()).unwrap();
+    let key = parsed.key("Key").unwrap();
+    assert_eq!(key.get_raw_value("String").unwrap(), &"text".to_reg_value());
+    assert_eq!(key.get_raw_value("Dword").unwrap(), &7u32.to_reg_value());
+}